@@ -13,13 +13,13 @@
 //! ...and follow the instructions.
 //!
 
+use oauth2::authorization::AuthorizationResponse;
 use oauth2::basic::BasicClient;
 
 // Alternatively, this can be `oauth2::curl::http_client` or a custom client.
 use oauth2::reqwest::async_http_client;
 use oauth2::{
-    AuthUrl, AuthorizationCode, ClientId, ClientSecret, CsrfToken, RedirectUrl, Scope,
-    TokenResponse, TokenUrl,
+    AuthUrl, ClientId, ClientSecret, CsrfToken, RedirectUrl, Scope, TokenResponse, TokenUrl,
 };
 use std::env;
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
@@ -70,8 +70,7 @@ async fn main() {
     let mut listener = TcpListener::bind("127.0.0.1:8080").await.unwrap();
     loop {
         if let Ok((mut stream, _)) = listener.accept().await {
-            let code;
-            let state;
+            let authorization_response;
             {
                 let mut reader = BufReader::new(&mut stream);
 
@@ -81,27 +80,7 @@ async fn main() {
                 let redirect_url = request_line.split_whitespace().nth(1).unwrap();
                 let url = Url::parse(&("http://localhost".to_string() + redirect_url)).unwrap();
 
-                let code_pair = url
-                    .query_pairs()
-                    .find(|pair| {
-                        let &(ref key, _) = pair;
-                        key == "code"
-                    })
-                    .unwrap();
-
-                let (_, value) = code_pair;
-                code = AuthorizationCode::new(value.into_owned());
-
-                let state_pair = url
-                    .query_pairs()
-                    .find(|pair| {
-                        let &(ref key, _) = pair;
-                        key == "state"
-                    })
-                    .unwrap();
-
-                let (_, value) = state_pair;
-                state = CsrfToken::new(value.into_owned());
+                authorization_response = AuthorizationResponse::from_url(&url);
             }
 
             let message = "Go back to your terminal :)";
@@ -112,12 +91,22 @@ async fn main() {
             );
             stream.write_all(response.as_bytes()).await.unwrap();
 
+            // Check the returned state against the CSRF token generated above before using the
+            // authorization code.
+            let code = match authorization_response
+                .and_then(|response| response.validate(&csrf_state, None))
+            {
+                Ok(code) => code,
+                Err(err) => {
+                    println!(
+                        "Github returned an invalid authorization response: {}\n",
+                        err
+                    );
+                    break;
+                }
+            };
+
             println!("Github returned the following code:\n{}\n", code.secret());
-            println!(
-                "Github returned the following state:\n{} (expected `{}`)\n",
-                state.secret(),
-                csrf_state.secret()
-            );
 
             // Exchange the code with a token.
             let token_res = client
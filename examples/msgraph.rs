@@ -22,12 +22,13 @@
 //! ...and follow the instructions.
 //!
 
+use oauth2::authorization::AuthorizationResponse;
 use oauth2::basic::BasicClient;
 // Alternatively, this can be `oauth2::curl::http_client` or a custom client.
 use oauth2::reqwest::http_client;
 use oauth2::{
-    AuthType, AuthUrl, AuthorizationCode, ClientId, ClientSecret, CsrfToken, PkceCodeChallenge,
-    RedirectUrl, Scope, TokenUrl,
+    AuthType, AuthUrl, ClientId, ClientSecret, CsrfToken, PkceCodeChallenge, RedirectUrl, Scope,
+    TokenUrl,
 };
 use std::env;
 use std::io::{BufRead, BufReader, Write};
@@ -89,8 +90,7 @@ fn main() {
     let listener = TcpListener::bind("127.0.0.1:3003").unwrap();
     for stream in listener.incoming() {
         if let Ok(mut stream) = stream {
-            let code;
-            let state;
+            let authorization_response;
             {
                 let mut reader = BufReader::new(&stream);
 
@@ -100,27 +100,7 @@ fn main() {
                 let redirect_url = request_line.split_whitespace().nth(1).unwrap();
                 let url = Url::parse(&("http://localhost".to_string() + redirect_url)).unwrap();
 
-                let code_pair = url
-                    .query_pairs()
-                    .find(|pair| {
-                        let &(ref key, _) = pair;
-                        key == "code"
-                    })
-                    .unwrap();
-
-                let (_, value) = code_pair;
-                code = AuthorizationCode::new(value.into_owned());
-
-                let state_pair = url
-                    .query_pairs()
-                    .find(|pair| {
-                        let &(ref key, _) = pair;
-                        key == "state"
-                    })
-                    .unwrap();
-
-                let (_, value) = state_pair;
-                state = CsrfToken::new(value.into_owned());
+                authorization_response = AuthorizationResponse::from_url(&url);
             }
 
             let message = "Go back to your terminal :)";
@@ -131,12 +111,22 @@ fn main() {
             );
             stream.write_all(response.as_bytes()).unwrap();
 
+            // Check the returned state against the CSRF token generated above before using the
+            // authorization code.
+            let code = match authorization_response
+                .and_then(|response| response.validate(&csrf_state, None))
+            {
+                Ok(code) => code,
+                Err(err) => {
+                    println!(
+                        "MS Graph returned an invalid authorization response: {}\n",
+                        err
+                    );
+                    break;
+                }
+            };
+
             println!("MS Graph returned the following code:\n{}\n", code.secret());
-            println!(
-                "MS Graph returned the following state:\n{} (expected `{}`)\n",
-                state.secret(),
-                csrf_state.secret()
-            );
 
             // Exchange the code with a token.
             let token = client
@@ -0,0 +1,498 @@
+use std::collections::HashSet;
+use std::fmt::Error as FormatterError;
+use std::fmt::{Debug, Display, Formatter};
+use std::marker::PhantomData;
+
+use serde_json::{Map, Number, Value};
+use url::{form_urlencoded, Url};
+
+use super::{
+    AuthorizationCode, CsrfToken, ErrorResponseType, IssuerUrl, StandardErrorResponse,
+    TokenResponse, TokenType,
+};
+
+///
+/// Authorization endpoint error types.
+///
+/// These error types are defined in
+/// [Section 4.1.2.1 of RFC 6749](https://tools.ietf.org/html/rfc6749#section-4.1.2.1).
+///
+#[derive(Clone, PartialEq)]
+pub enum AuthorizationErrorResponseType {
+    ///
+    /// The request is missing a required parameter, includes an invalid parameter value,
+    /// includes a parameter more than once, or is otherwise malformed.
+    ///
+    InvalidRequest,
+    ///
+    /// The client is not authorized to request an authorization code using this method.
+    ///
+    UnauthorizedClient,
+    ///
+    /// The resource owner or authorization server denied the request.
+    ///
+    AccessDenied,
+    ///
+    /// The authorization server does not support obtaining an authorization code using this
+    /// method.
+    ///
+    UnsupportedResponseType,
+    ///
+    /// The requested scope is invalid, unknown, or malformed.
+    ///
+    InvalidScope,
+    ///
+    /// The authorization server encountered an unexpected condition that prevented it from
+    /// fulfilling the request.
+    ///
+    ServerError,
+    ///
+    /// The authorization server is currently unable to handle the request due to a temporary
+    /// overloading or maintenance of the server.
+    ///
+    TemporarilyUnavailable,
+    ///
+    /// An extension not defined by RFC 6749.
+    ///
+    Extension(String),
+}
+impl AuthorizationErrorResponseType {
+    fn from_str(s: &str) -> Self {
+        match s {
+            "invalid_request" => AuthorizationErrorResponseType::InvalidRequest,
+            "unauthorized_client" => AuthorizationErrorResponseType::UnauthorizedClient,
+            "access_denied" => AuthorizationErrorResponseType::AccessDenied,
+            "unsupported_response_type" => AuthorizationErrorResponseType::UnsupportedResponseType,
+            "invalid_scope" => AuthorizationErrorResponseType::InvalidScope,
+            "server_error" => AuthorizationErrorResponseType::ServerError,
+            "temporarily_unavailable" => AuthorizationErrorResponseType::TemporarilyUnavailable,
+            ext => AuthorizationErrorResponseType::Extension(ext.to_string()),
+        }
+    }
+}
+impl AsRef<str> for AuthorizationErrorResponseType {
+    fn as_ref(&self) -> &str {
+        match *self {
+            AuthorizationErrorResponseType::InvalidRequest => "invalid_request",
+            AuthorizationErrorResponseType::UnauthorizedClient => "unauthorized_client",
+            AuthorizationErrorResponseType::AccessDenied => "access_denied",
+            AuthorizationErrorResponseType::UnsupportedResponseType => "unsupported_response_type",
+            AuthorizationErrorResponseType::InvalidScope => "invalid_scope",
+            AuthorizationErrorResponseType::ServerError => "server_error",
+            AuthorizationErrorResponseType::TemporarilyUnavailable => "temporarily_unavailable",
+            AuthorizationErrorResponseType::Extension(ref ext) => ext.as_str(),
+        }
+    }
+}
+impl<'de> serde::Deserialize<'de> for AuthorizationErrorResponseType {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::de::Deserializer<'de>,
+    {
+        let variant_str = String::deserialize(deserializer)?;
+        Ok(Self::from_str(&variant_str))
+    }
+}
+impl serde::ser::Serialize for AuthorizationErrorResponseType {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::ser::Serializer,
+    {
+        serializer.serialize_str(self.as_ref())
+    }
+}
+impl ErrorResponseType for AuthorizationErrorResponseType {}
+impl Debug for AuthorizationErrorResponseType {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FormatterError> {
+        Display::fmt(self, f)
+    }
+}
+
+impl Display for AuthorizationErrorResponseType {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FormatterError> {
+        write!(f, "{}", self.as_ref())
+    }
+}
+
+///
+/// Error response specialization for the authorization endpoint.
+///
+pub type AuthorizationErrorResponse = StandardErrorResponse<AuthorizationErrorResponseType>;
+
+///
+/// Error encountered while validating an authorization response.
+///
+#[derive(Debug, thiserror::Error)]
+pub enum AuthorizationResponseError {
+    ///
+    /// Error response returned by the authorization server (e.g., `access_denied` when the
+    /// resource owner denied the request).
+    ///
+    #[error("Server returned error response: {}", _0)]
+    ServerResponse(AuthorizationErrorResponse),
+    ///
+    /// The `state` parameter does not match the expected CSRF token. The response may have
+    /// been forged and must be discarded.
+    ///
+    #[error("CSRF state does not match the expected value")]
+    StateMismatch,
+    ///
+    /// The `iss` parameter does not match the expected issuer. The response may originate from
+    /// a different authorization server (a mix-up attack) and must be discarded.
+    ///
+    #[error("Unexpected issuer `{}`", _0)]
+    IssuerMismatch(String),
+    ///
+    /// A required parameter is missing from the response.
+    ///
+    #[error("Missing `{}` parameter", _0)]
+    MissingParameter(&'static str),
+    ///
+    /// The response is malformed (e.g., a parameter is included more than once).
+    ///
+    #[error("Failed to parse authorization response: {}", _0)]
+    Parse(String),
+}
+
+///
+/// Response returned by the authorization endpoint to the client's redirection URI.
+///
+/// This type parses the query component of the redirection URI, which contains either the
+/// authorization code
+/// ([Section 4.1.2 of RFC 6749](https://tools.ietf.org/html/rfc6749#section-4.1.2)) or an
+/// error response
+/// ([Section 4.1.2.1 of RFC 6749](https://tools.ietf.org/html/rfc6749#section-4.1.2.1)), as
+/// well as the optional `iss` parameter defined in
+/// [RFC 9207](https://tools.ietf.org/html/rfc9207).
+///
+/// Call `validate` to check the `state` (and optionally `iss`) parameter before using the
+/// authorization code.
+///
+#[derive(Clone, Debug)]
+pub struct AuthorizationResponse {
+    code: Option<AuthorizationCode>,
+    state: Option<CsrfToken>,
+    iss: Option<String>,
+    error: Option<AuthorizationErrorResponseType>,
+    error_description: Option<String>,
+    error_uri: Option<String>,
+}
+impl AuthorizationResponse {
+    ///
+    /// Parses the query component of the redirection URI requested by the user-agent.
+    ///
+    pub fn from_url(url: &Url) -> Result<Self, AuthorizationResponseError> {
+        Self::from_query(url.query().unwrap_or(""))
+    }
+
+    ///
+    /// Parses a URL-encoded query string (without the leading `?`).
+    ///
+    pub fn from_query(query: &str) -> Result<Self, AuthorizationResponseError> {
+        let mut response = Self {
+            code: None,
+            state: None,
+            iss: None,
+            error: None,
+            error_description: None,
+            error_uri: None,
+        };
+
+        for (name, value) in parse_params(query)? {
+            match name.as_str() {
+                "code" => response.code = Some(AuthorizationCode::new(value)),
+                "state" => response.state = Some(CsrfToken::new(value)),
+                "iss" => response.iss = Some(value),
+                "error" => response.error = Some(AuthorizationErrorResponseType::from_str(&value)),
+                "error_description" => response.error_description = Some(value),
+                "error_uri" => response.error_uri = Some(value),
+                _ => {}
+            }
+        }
+
+        Ok(response)
+    }
+
+    ///
+    /// The authorization code, if the authorization request succeeded.
+    ///
+    /// Prefer `validate`, which only returns the code after checking the `state` parameter.
+    ///
+    pub fn code(&self) -> Option<&AuthorizationCode> {
+        self.code.as_ref()
+    }
+
+    ///
+    /// The `state` parameter returned by the authorization server.
+    ///
+    pub fn state(&self) -> Option<&CsrfToken> {
+        self.state.as_ref()
+    }
+
+    ///
+    /// The issuer identifier of the authorization server (the `iss` parameter defined in
+    /// [RFC 9207](https://tools.ietf.org/html/rfc9207)).
+    ///
+    pub fn issuer(&self) -> Option<&str> {
+        self.iss.as_deref()
+    }
+
+    ///
+    /// The error response, if the authorization request failed.
+    ///
+    pub fn error(&self) -> Option<AuthorizationErrorResponse> {
+        self.error.as_ref().map(|error| {
+            AuthorizationErrorResponse::new(
+                error.clone(),
+                self.error_description.clone(),
+                self.error_uri.clone(),
+            )
+        })
+    }
+
+    ///
+    /// Validates the response and returns the authorization code.
+    ///
+    /// The `state` parameter is compared to `expected_state` in constant time. If
+    /// `expected_issuer` is provided (i.e., if the authorization server advertises the
+    /// `authorization_response_iss_parameter_supported` metadata), the `iss` parameter is
+    /// required and must match it, as described in
+    /// [Section 2.4 of RFC 9207](https://tools.ietf.org/html/rfc9207#section-2.4). Both checks
+    /// are performed before any error response is returned.
+    ///
+    pub fn validate(
+        self,
+        expected_state: &CsrfToken,
+        expected_issuer: Option<&IssuerUrl>,
+    ) -> Result<AuthorizationCode, AuthorizationResponseError> {
+        verify_state_and_issuer(
+            self.state.as_ref(),
+            self.iss.as_deref(),
+            expected_state,
+            expected_issuer,
+        )?;
+
+        if let Some(error) = self.error() {
+            return Err(AuthorizationResponseError::ServerResponse(error));
+        }
+
+        self.code
+            .ok_or(AuthorizationResponseError::MissingParameter("code"))
+    }
+}
+
+///
+/// Response returned by the authorization endpoint when using the Implicit Grant flow (see
+/// `AuthorizationRequest::use_implicit_flow`).
+///
+/// This type parses the fragment component of the redirection URI, which contains either the
+/// access token response
+/// ([Section 4.2.2 of RFC 6749](https://tools.ietf.org/html/rfc6749#section-4.2.2)) or an
+/// error response
+/// ([Section 4.2.2.1 of RFC 6749](https://tools.ietf.org/html/rfc6749#section-4.2.2.1)).
+///
+/// Call `validate` to check the `state` (and optionally `iss`) parameter and deserialize the
+/// token response.
+///
+#[derive(Clone, Debug)]
+pub struct ImplicitResponse<TR, TT>
+where
+    TR: TokenResponse<TT>,
+    TT: TokenType,
+{
+    state: Option<CsrfToken>,
+    iss: Option<String>,
+    error: Option<AuthorizationErrorResponseType>,
+    error_description: Option<String>,
+    error_uri: Option<String>,
+    token_fields: Vec<(String, String)>,
+    _phantom: PhantomData<(TR, TT)>,
+}
+impl<TR, TT> ImplicitResponse<TR, TT>
+where
+    TR: TokenResponse<TT>,
+    TT: TokenType,
+{
+    ///
+    /// Parses the fragment component of the redirection URI requested by the user-agent.
+    ///
+    pub fn from_url(url: &Url) -> Result<Self, AuthorizationResponseError> {
+        Self::from_fragment(url.fragment().unwrap_or(""))
+    }
+
+    ///
+    /// Parses a URL-encoded fragment (without the leading `#`).
+    ///
+    pub fn from_fragment(fragment: &str) -> Result<Self, AuthorizationResponseError> {
+        let mut response = Self {
+            state: None,
+            iss: None,
+            error: None,
+            error_description: None,
+            error_uri: None,
+            token_fields: Vec::new(),
+            _phantom: PhantomData,
+        };
+
+        for (name, value) in parse_params(fragment)? {
+            match name.as_str() {
+                "state" => response.state = Some(CsrfToken::new(value)),
+                "iss" => response.iss = Some(value),
+                "error" => response.error = Some(AuthorizationErrorResponseType::from_str(&value)),
+                "error_description" => response.error_description = Some(value),
+                "error_uri" => response.error_uri = Some(value),
+                "expires_in" if value.parse::<u64>().is_err() => {
+                    return Err(AuthorizationResponseError::Parse(format!(
+                        "invalid `expires_in` value `{}`",
+                        value
+                    )));
+                }
+                _ => response.token_fields.push((name, value)),
+            }
+        }
+
+        Ok(response)
+    }
+
+    ///
+    /// The `state` parameter returned by the authorization server.
+    ///
+    pub fn state(&self) -> Option<&CsrfToken> {
+        self.state.as_ref()
+    }
+
+    ///
+    /// The issuer identifier of the authorization server (the `iss` parameter defined in
+    /// [RFC 9207](https://tools.ietf.org/html/rfc9207)).
+    ///
+    pub fn issuer(&self) -> Option<&str> {
+        self.iss.as_deref()
+    }
+
+    ///
+    /// The error response, if the authorization request failed.
+    ///
+    pub fn error(&self) -> Option<AuthorizationErrorResponse> {
+        self.error.as_ref().map(|error| {
+            AuthorizationErrorResponse::new(
+                error.clone(),
+                self.error_description.clone(),
+                self.error_uri.clone(),
+            )
+        })
+    }
+
+    ///
+    /// Validates the response and returns the token response.
+    ///
+    /// The `state` and `iss` parameters are checked as described in
+    /// `AuthorizationResponse::validate`. The remaining parameters (including any extra fields)
+    /// are deserialized into `TR`, with `expires_in` converted to a number.
+    ///
+    pub fn validate(
+        self,
+        expected_state: &CsrfToken,
+        expected_issuer: Option<&IssuerUrl>,
+    ) -> Result<TR, AuthorizationResponseError> {
+        verify_state_and_issuer(
+            self.state.as_ref(),
+            self.iss.as_deref(),
+            expected_state,
+            expected_issuer,
+        )?;
+
+        if let Some(error) = self.error() {
+            return Err(AuthorizationResponseError::ServerResponse(error));
+        }
+
+        if !self
+            .token_fields
+            .iter()
+            .any(|(name, _)| name == "access_token")
+        {
+            return Err(AuthorizationResponseError::MissingParameter("access_token"));
+        }
+
+        // Since all form-encoded values are strings, extra fields that look like numbers or
+        // booleans are only converted if the token response can't be deserialized without doing
+        // so (e.g., if `TR` has an extra field of type `u32`).
+        serde_json::from_value(self.token_fields_value(false))
+            .or_else(|err| serde_json::from_value(self.token_fields_value(true)).map_err(|_| err))
+            .map_err(|err| AuthorizationResponseError::Parse(err.to_string()))
+    }
+
+    fn token_fields_value(&self, coerce_extra_fields: bool) -> Value {
+        Value::Object(
+            self.token_fields
+                .iter()
+                .map(|(name, value)| {
+                    let value = match name.as_str() {
+                        "expires_in" => coerce_value(value),
+                        "access_token" | "token_type" | "refresh_token" | "scope" => {
+                            Value::String(value.clone())
+                        }
+                        _ if coerce_extra_fields => coerce_value(value),
+                        _ => Value::String(value.clone()),
+                    };
+                    (name.clone(), value)
+                })
+                .collect::<Map<_, _>>(),
+        )
+    }
+}
+
+fn coerce_value(value: &str) -> Value {
+    if let Ok(boolean) = value.parse::<bool>() {
+        Value::Bool(boolean)
+    } else if let Ok(number) = value.parse::<u64>() {
+        Value::Number(Number::from(number))
+    } else if let Ok(number) = value.parse::<i64>() {
+        Value::Number(Number::from(number))
+    } else if let Some(number) = value.parse::<f64>().ok().and_then(Number::from_f64) {
+        Value::Number(number)
+    } else {
+        Value::String(value.to_string())
+    }
+}
+
+fn parse_params(input: &str) -> Result<Vec<(String, String)>, AuthorizationResponseError> {
+    let mut names = HashSet::new();
+    form_urlencoded::parse(input.as_bytes())
+        .map(|(name, value)| {
+            // Section 3.1 of RFC 6749 forbids including any response parameter more than once.
+            if !names.insert(name.clone()) {
+                return Err(AuthorizationResponseError::Parse(format!(
+                    "parameter `{}` included more than once",
+                    name
+                )));
+            }
+            Ok((name.into_owned(), value.into_owned()))
+        })
+        .collect()
+}
+
+fn verify_state_and_issuer(
+    state: Option<&CsrfToken>,
+    iss: Option<&str>,
+    expected_state: &CsrfToken,
+    expected_issuer: Option<&IssuerUrl>,
+) -> Result<(), AuthorizationResponseError> {
+    let state = state.ok_or(AuthorizationResponseError::MissingParameter("state"))?;
+    if ring::constant_time::verify_slices_are_equal(
+        state.secret().as_bytes(),
+        expected_state.secret().as_bytes(),
+    )
+    .is_err()
+    {
+        return Err(AuthorizationResponseError::StateMismatch);
+    }
+
+    if let Some(expected_issuer) = expected_issuer {
+        let iss = iss.ok_or(AuthorizationResponseError::MissingParameter("iss"))?;
+        if iss != expected_issuer.as_str() {
+            return Err(AuthorizationResponseError::IssuerMismatch(iss.to_string()));
+        }
+    }
+
+    Ok(())
+}
@@ -5,6 +5,7 @@ use super::{
     Client, EmptyExtraTokenFields, ErrorResponseType, RequestTokenError, StandardErrorResponse,
     StandardTokenResponse, TokenType,
 };
+use crate::tokenexchange::TokenExchangeTokenResponse;
 use crate::StandardTokenInspectionResponse;
 
 ///
@@ -24,6 +25,11 @@ pub enum BasicTokenType {
     ///
     Bearer,
     ///
+    /// DPoP-bound token
+    /// ([OAuth 2.0 Demonstrating Proof of Possession (DPoP) - RFC 9449](https://tools.ietf.org/html/rfc9449)).
+    ///
+    DPoP,
+    ///
     /// MAC ([OAuth 2.0 Message Authentication Code (MAC)
     /// Tokens](https://tools.ietf.org/html/draft-ietf-oauth-v2-http-mac-05)).
     ///
@@ -37,6 +43,7 @@ impl BasicTokenType {
     fn from_str(s: &str) -> Self {
         match s {
             "bearer" => BasicTokenType::Bearer,
+            "dpop" => BasicTokenType::DPoP,
             "mac" => BasicTokenType::Mac,
             ext => BasicTokenType::Extension(ext.to_string()),
         }
@@ -46,6 +53,7 @@ impl AsRef<str> for BasicTokenType {
     fn as_ref(&self) -> &str {
         match *self {
             BasicTokenType::Bearer => "bearer",
+            BasicTokenType::DPoP => "DPoP",
             BasicTokenType::Mac => "mac",
             BasicTokenType::Extension(ref ext) => ext.as_str(),
         }
@@ -75,6 +83,12 @@ impl TokenType for BasicTokenType {}
 ///
 pub type BasicTokenResponse = StandardTokenResponse<EmptyExtraTokenFields, BasicTokenType>;
 
+///
+/// Basic OAuth2 token exchange response.
+///
+pub type BasicTokenExchangeTokenResponse =
+    TokenExchangeTokenResponse<EmptyExtraTokenFields, BasicTokenType>;
+
 ///
 /// Basic OAuth2 token inspection response.
 ///
@@ -0,0 +1,263 @@
+use std::fmt::Error as FormatterError;
+use std::fmt::{Debug, Display, Formatter};
+use std::time::Duration;
+
+use http::header::AUTHORIZATION;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    AuthReqId, ClientNotificationToken, ErrorResponseType, HttpRequest, StandardErrorResponse,
+};
+use crate::basic::BasicErrorResponseType;
+
+///
+/// Value of the `grant_type` parameter for the CIBA grant, as defined in
+/// [Section 10.1 of CIBA](https://openid.net/specs/openid-client-initiated-backchannel-authentication-core-1_0.html#rfc.section.10.1).
+///
+pub const CIBA_GRANT_TYPE: &str = "urn:openid:params:grant-type:ciba";
+
+/// The minimum amount of time in seconds that the client SHOULD wait between polling requests
+/// to the token endpoint. If no value is provided, clients MUST use 5 as the default.
+fn default_ciba_interval() -> u64 {
+    5
+}
+
+///
+/// Successful response from the backchannel authentication endpoint, as defined in
+/// [Section 7.3 of CIBA](https://openid.net/specs/openid-client-initiated-backchannel-authentication-core-1_0.html#rfc.section.7.3).
+///
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct BackchannelAuthenticationResponse {
+    /// Unique identifier of the authentication request.
+    auth_req_id: AuthReqId,
+
+    /// The lifetime in seconds of the `auth_req_id`.
+    expires_in: u64,
+
+    /// The minimum amount of time in seconds that the client SHOULD wait between polling
+    /// requests to the token endpoint.
+    #[serde(default = "default_ciba_interval")]
+    interval: u64,
+}
+
+impl BackchannelAuthenticationResponse {
+    ///
+    /// Instantiate a new backchannel authentication response.
+    ///
+    pub fn new(auth_req_id: AuthReqId, expires_in: Duration, interval: Duration) -> Self {
+        Self {
+            auth_req_id,
+            expires_in: expires_in.as_secs(),
+            interval: interval.as_secs(),
+        }
+    }
+
+    /// Unique identifier of the authentication request, used to obtain the access token from
+    /// the token endpoint.
+    pub fn auth_req_id(&self) -> &AuthReqId {
+        &self.auth_req_id
+    }
+
+    /// The lifetime of the `auth_req_id`.
+    pub fn expires_in(&self) -> Duration {
+        Duration::from_secs(self.expires_in)
+    }
+
+    /// The minimum amount of time that the client SHOULD wait between polling requests to the
+    /// token endpoint. If no value is provided, clients MUST use 5 seconds as the default.
+    pub fn interval(&self) -> Duration {
+        Duration::from_secs(self.interval)
+    }
+}
+
+///
+/// Notification sent by the authorization server to the client notification endpoint once the
+/// end-user has completed the authentication in ping mode, as defined in
+/// [Section 10.2 of CIBA](https://openid.net/specs/openid-client-initiated-backchannel-authentication-core-1_0.html#rfc.section.10.2).
+///
+/// Upon receiving a notification, the client should request the access token using
+/// `Client::exchange_auth_req_id`.
+///
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct BackchannelPingNotification {
+    auth_req_id: AuthReqId,
+}
+
+impl BackchannelPingNotification {
+    ///
+    /// Parses and authenticates a request received by the client notification endpoint.
+    ///
+    /// The request must include the `client_notification_token` sent in the authentication
+    /// request as a bearer token.
+    ///
+    pub fn from_request(
+        request: &HttpRequest,
+        client_notification_token: &ClientNotificationToken,
+    ) -> Result<Self, BackchannelNotificationError> {
+        let bearer_token = request
+            .headers
+            .get(AUTHORIZATION)
+            .and_then(|authorization| authorization.to_str().ok())
+            .and_then(|authorization| {
+                let mut parts = authorization.splitn(2, ' ');
+                match (parts.next(), parts.next()) {
+                    (Some(scheme), Some(token)) if scheme.eq_ignore_ascii_case("bearer") => {
+                        Some(token.trim())
+                    }
+                    _ => None,
+                }
+            })
+            .ok_or(BackchannelNotificationError::Unauthorized)?;
+
+        if ring::constant_time::verify_slices_are_equal(
+            bearer_token.as_bytes(),
+            client_notification_token.secret().as_bytes(),
+        )
+        .is_err()
+        {
+            return Err(BackchannelNotificationError::Unauthorized);
+        }
+
+        serde_json::from_slice(&request.body).map_err(BackchannelNotificationError::Parse)
+    }
+
+    /// Identifier of the authentication request that has completed.
+    pub fn auth_req_id(&self) -> &AuthReqId {
+        &self.auth_req_id
+    }
+}
+
+///
+/// Error encountered while processing a ping mode notification.
+///
+#[derive(Debug, thiserror::Error)]
+pub enum BackchannelNotificationError {
+    ///
+    /// The request did not include the expected client notification token.
+    ///
+    #[error("Missing or invalid client notification token")]
+    Unauthorized,
+    ///
+    /// Failed to parse the notification body.
+    ///
+    #[error("Failed to parse notification")]
+    Parse(#[source] serde_json::Error),
+}
+
+///
+/// Backchannel authentication error types.
+///
+/// These error types are defined in
+/// [Section 13 of CIBA](https://openid.net/specs/openid-client-initiated-backchannel-authentication-core-1_0.html#rfc.section.13).
+///
+#[derive(Clone, PartialEq)]
+pub enum BackchannelAuthenticationErrorResponseType {
+    ///
+    /// The `login_hint_token` provided in the authentication request is not valid because it has
+    /// expired.
+    ///
+    ExpiredLoginHintToken,
+    ///
+    /// The authorization server is not able to identify which end-user the client wishes to be
+    /// authenticated by means of the hint provided in the request.
+    ///
+    UnknownUserId,
+    ///
+    /// A user code is required but was missing from the request.
+    ///
+    MissingUserCode,
+    ///
+    /// The user code was invalid.
+    ///
+    InvalidUserCode,
+    ///
+    /// The binding message is invalid or unacceptable for use in the context of the given
+    /// request.
+    ///
+    InvalidBindingMessage,
+    ///
+    /// The resource owner or authorization server denied the request.
+    ///
+    AccessDenied,
+    ///
+    /// A Basic response type
+    ///
+    Basic(BasicErrorResponseType),
+}
+impl BackchannelAuthenticationErrorResponseType {
+    fn from_str(s: &str) -> Self {
+        match BasicErrorResponseType::from_str(s) {
+            BasicErrorResponseType::Extension(ext) => match ext.as_str() {
+                "expired_login_hint_token" => {
+                    BackchannelAuthenticationErrorResponseType::ExpiredLoginHintToken
+                }
+                "unknown_user_id" => BackchannelAuthenticationErrorResponseType::UnknownUserId,
+                "missing_user_code" => BackchannelAuthenticationErrorResponseType::MissingUserCode,
+                "invalid_user_code" => BackchannelAuthenticationErrorResponseType::InvalidUserCode,
+                "invalid_binding_message" => {
+                    BackchannelAuthenticationErrorResponseType::InvalidBindingMessage
+                }
+                "access_denied" => BackchannelAuthenticationErrorResponseType::AccessDenied,
+                _ => BackchannelAuthenticationErrorResponseType::Basic(
+                    BasicErrorResponseType::Extension(ext),
+                ),
+            },
+            basic => BackchannelAuthenticationErrorResponseType::Basic(basic),
+        }
+    }
+}
+impl AsRef<str> for BackchannelAuthenticationErrorResponseType {
+    fn as_ref(&self) -> &str {
+        match self {
+            BackchannelAuthenticationErrorResponseType::ExpiredLoginHintToken => {
+                "expired_login_hint_token"
+            }
+            BackchannelAuthenticationErrorResponseType::UnknownUserId => "unknown_user_id",
+            BackchannelAuthenticationErrorResponseType::MissingUserCode => "missing_user_code",
+            BackchannelAuthenticationErrorResponseType::InvalidUserCode => "invalid_user_code",
+            BackchannelAuthenticationErrorResponseType::InvalidBindingMessage => {
+                "invalid_binding_message"
+            }
+            BackchannelAuthenticationErrorResponseType::AccessDenied => "access_denied",
+            BackchannelAuthenticationErrorResponseType::Basic(basic) => basic.as_ref(),
+        }
+    }
+}
+impl<'de> serde::Deserialize<'de> for BackchannelAuthenticationErrorResponseType {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::de::Deserializer<'de>,
+    {
+        let variant_str = String::deserialize(deserializer)?;
+        Ok(Self::from_str(&variant_str))
+    }
+}
+impl serde::ser::Serialize for BackchannelAuthenticationErrorResponseType {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::ser::Serializer,
+    {
+        serializer.serialize_str(self.as_ref())
+    }
+}
+impl ErrorResponseType for BackchannelAuthenticationErrorResponseType {}
+impl Debug for BackchannelAuthenticationErrorResponseType {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FormatterError> {
+        Display::fmt(self, f)
+    }
+}
+
+impl Display for BackchannelAuthenticationErrorResponseType {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FormatterError> {
+        write!(f, "{}", self.as_ref())
+    }
+}
+
+///
+/// Error response specialization for backchannel authentication requests.
+///
+/// Token requests using the CIBA grant return the same errors as the device authorization grant
+/// (`devicecode::DeviceCodeErrorResponse`).
+///
+pub type BackchannelAuthenticationErrorResponse =
+    StandardErrorResponse<BackchannelAuthenticationErrorResponseType>;
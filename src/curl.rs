@@ -1,11 +1,14 @@
+use std::fmt::Error as FormatterError;
+use std::fmt::{Debug, Formatter};
 use std::io::Read;
+use std::path::PathBuf;
 
 use curl::easy::Easy;
 use http::header::{HeaderMap, HeaderValue, CONTENT_TYPE};
 use http::method::Method;
 use http::status::StatusCode;
 
-use super::{HttpRequest, HttpResponse};
+use super::{HttpRequest, HttpResponse, SyncHttpClient};
 
 ///
 /// Error type returned by failed curl HTTP requests.
@@ -23,11 +26,118 @@ pub enum Error {
     Other(String),
 }
 
+///
+/// TLS client certificate and private key presented by `mtls_http_client`.
+///
+#[derive(Clone)]
+pub enum ClientIdentity {
+    /// PEM-encoded certificate chain and private key files.
+    Pem {
+        /// Path to the certificate chain.
+        certificate: PathBuf,
+        /// Path to the private key.
+        private_key: PathBuf,
+    },
+    /// PKCS#12 archive containing both the certificate chain and the private key.
+    Pkcs12 {
+        /// Path to the archive.
+        archive: PathBuf,
+        /// Password protecting the archive.
+        password: String,
+    },
+}
+impl Debug for ClientIdentity {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FormatterError> {
+        match self {
+            ClientIdentity::Pem {
+                certificate,
+                private_key,
+            } => f
+                .debug_struct("Pem")
+                .field("certificate", certificate)
+                .field("private_key", private_key)
+                .finish(),
+            ClientIdentity::Pkcs12 { archive, .. } => f
+                .debug_struct("Pkcs12")
+                .field("archive", archive)
+                .field("password", &"[redacted]")
+                .finish(),
+        }
+    }
+}
+
 ///
 /// Synchronous HTTP client.
 ///
 pub fn http_client(request: HttpRequest) -> Result<HttpResponse, Error> {
-    let mut easy = Easy::new();
+    perform(Easy::new(), request)
+}
+
+///
+/// Synchronous HTTP client implementing `SyncHttpClient`, equivalent to `http_client`.
+///
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CurlHttpClient;
+impl SyncHttpClient for CurlHttpClient {
+    type Error = Error;
+
+    fn call(self, request: HttpRequest) -> Result<HttpResponse, Error> {
+        http_client(request)
+    }
+}
+impl SyncHttpClient for &CurlHttpClient {
+    type Error = Error;
+
+    fn call(self, request: HttpRequest) -> Result<HttpResponse, Error> {
+        http_client(request)
+    }
+}
+
+///
+/// Returns a synchronous HTTP client that presents a TLS client certificate, for use with
+/// mutual-TLS client authentication and certificate-bound access tokens
+/// ([RFC 8705](https://tools.ietf.org/html/rfc8705)).
+///
+/// # Arguments
+///
+/// * `identity` - Certificate chain and private key of the client.
+/// * `ca_certificate` - Optional path to a PEM-encoded bundle of certificates used to verify
+///   the server instead of the default bundle (e.g., a private certificate authority).
+///
+pub fn mtls_http_client(
+    identity: ClientIdentity,
+    ca_certificate: Option<PathBuf>,
+) -> impl Fn(HttpRequest) -> Result<HttpResponse, Error> {
+    move |request| {
+        let mut easy = Easy::new();
+        match identity {
+            ClientIdentity::Pem {
+                ref certificate,
+                ref private_key,
+            } => {
+                easy.ssl_cert(certificate).map_err(Error::Curl)?;
+                easy.ssl_cert_type("PEM").map_err(Error::Curl)?;
+                easy.ssl_key(private_key).map_err(Error::Curl)?;
+                easy.ssl_key_type("PEM").map_err(Error::Curl)?;
+            }
+            ClientIdentity::Pkcs12 {
+                ref archive,
+                ref password,
+            } => {
+                easy.ssl_cert(archive).map_err(Error::Curl)?;
+                easy.ssl_cert_type("P12").map_err(Error::Curl)?;
+                easy.key_password(password).map_err(Error::Curl)?;
+            }
+        }
+        if let Some(ref ca_certificate) = ca_certificate {
+            easy.cainfo(ca_certificate).map_err(Error::Curl)?;
+        }
+
+        perform(easy, request)
+    }
+}
+
+fn perform(mut easy: Easy, request: HttpRequest) -> Result<HttpResponse, Error> {
     easy.url(&request.url.to_string()[..])
         .map_err(Error::Curl)?;
 
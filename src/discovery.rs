@@ -0,0 +1,507 @@
+use std::error::Error;
+use std::fmt::Debug;
+
+use http::header::{HeaderMap, HeaderValue, ACCEPT};
+use http::method::Method;
+use http::status::StatusCode;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    check_content_type, AsyncHttpClient, AuthUrl, BackchannelAuthenticationUrl, ClientAuthMethod,
+    DeviceAuthorizationUrl, GrantType, HttpRequest, HttpResponse, IntrospectUrl, IssuerUrl,
+    JsonWebKeySetUrl, ParUrl, PkceCodeChallengeMethod, RegistrationUrl, ResponseType,
+    RevocationUrl, Scope, SyncHttpClient, TokenUrl, CONTENT_TYPE_JSON,
+};
+use crate::jwt::JwsSigningAlgorithm;
+
+const WELL_KNOWN_PATH: &str = "/.well-known/oauth-authorization-server";
+
+///
+/// Trait for adding extra fields to the `ProviderMetadata`.
+///
+pub trait ExtraProviderMetadata: Clone + Debug + DeserializeOwned + Serialize {}
+
+///
+/// Empty (default) extra provider metadata fields.
+///
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct EmptyExtraProviderMetadata {}
+impl ExtraProviderMetadata for EmptyExtraProviderMetadata {}
+
+///
+/// Authorization server metadata, as defined in
+/// [Section 2 of RFC 8414](https://tools.ietf.org/html/rfc8414#section-2).
+///
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ProviderMetadata<EF>
+where
+    EF: ExtraProviderMetadata,
+{
+    issuer: IssuerUrl,
+    authorization_endpoint: AuthUrl,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    token_endpoint: Option<TokenUrl>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    jwks_uri: Option<JsonWebKeySetUrl>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    registration_endpoint: Option<RegistrationUrl>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    scopes_supported: Option<Vec<Scope>>,
+    response_types_supported: Vec<ResponseType>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    response_modes_supported: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    grant_types_supported: Option<Vec<GrantType>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    token_endpoint_auth_methods_supported: Option<Vec<ClientAuthMethod>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    token_endpoint_auth_signing_alg_values_supported: Option<Vec<JwsSigningAlgorithmValue>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    service_documentation: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ui_locales_supported: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    op_policy_uri: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    op_tos_uri: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    revocation_endpoint: Option<RevocationUrl>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    revocation_endpoint_auth_methods_supported: Option<Vec<ClientAuthMethod>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    introspection_endpoint: Option<IntrospectUrl>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    introspection_endpoint_auth_methods_supported: Option<Vec<ClientAuthMethod>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    code_challenge_methods_supported: Option<Vec<PkceCodeChallengeMethod>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    device_authorization_endpoint: Option<DeviceAuthorizationUrl>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pushed_authorization_request_endpoint: Option<ParUrl>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    require_pushed_authorization_requests: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    authorization_response_iss_parameter_supported: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    tls_client_certificate_bound_access_tokens: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    mtls_endpoint_aliases: Option<MtlsEndpointAliases>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    backchannel_authentication_endpoint: Option<BackchannelAuthenticationUrl>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    backchannel_token_delivery_modes_supported: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    backchannel_user_code_parameter_supported: bool,
+
+    #[serde(bound = "EF: ExtraProviderMetadata", flatten)]
+    extra_fields: EF,
+}
+
+///
+/// JWS algorithm advertised by the authorization server.
+///
+/// Algorithms not supported by this crate are preserved as strings.
+///
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum JwsSigningAlgorithmValue {
+    ///
+    /// An algorithm supported by this crate.
+    ///
+    Supported(JwsSigningAlgorithm),
+    ///
+    /// An algorithm not supported by this crate.
+    ///
+    Other(String),
+}
+
+///
+/// Alternative endpoints that accept mutual-TLS connections, as defined in
+/// [Section 5 of RFC 8705](https://tools.ietf.org/html/rfc8705#section-5).
+///
+/// Clients using mutual-TLS client authentication or requesting certificate-bound access tokens
+/// should use these endpoints instead of the ones advertised in the top-level metadata (see
+/// `Client::set_mtls_endpoint_aliases`).
+///
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct MtlsEndpointAliases {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    token_endpoint: Option<TokenUrl>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    revocation_endpoint: Option<RevocationUrl>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    introspection_endpoint: Option<IntrospectUrl>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    device_authorization_endpoint: Option<DeviceAuthorizationUrl>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pushed_authorization_request_endpoint: Option<ParUrl>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    registration_endpoint: Option<RegistrationUrl>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    backchannel_authentication_endpoint: Option<BackchannelAuthenticationUrl>,
+}
+
+impl MtlsEndpointAliases {
+    /// Mutual-TLS alias of the token endpoint.
+    pub fn token_endpoint(&self) -> Option<&TokenUrl> {
+        self.token_endpoint.as_ref()
+    }
+
+    /// Mutual-TLS alias of the revocation endpoint.
+    pub fn revocation_endpoint(&self) -> Option<&RevocationUrl> {
+        self.revocation_endpoint.as_ref()
+    }
+
+    /// Mutual-TLS alias of the introspection endpoint.
+    pub fn introspection_endpoint(&self) -> Option<&IntrospectUrl> {
+        self.introspection_endpoint.as_ref()
+    }
+
+    /// Mutual-TLS alias of the device authorization endpoint.
+    pub fn device_authorization_endpoint(&self) -> Option<&DeviceAuthorizationUrl> {
+        self.device_authorization_endpoint.as_ref()
+    }
+
+    /// Mutual-TLS alias of the pushed authorization request endpoint.
+    pub fn pushed_authorization_request_endpoint(&self) -> Option<&ParUrl> {
+        self.pushed_authorization_request_endpoint.as_ref()
+    }
+
+    /// Mutual-TLS alias of the dynamic client registration endpoint.
+    pub fn registration_endpoint(&self) -> Option<&RegistrationUrl> {
+        self.registration_endpoint.as_ref()
+    }
+
+    /// Mutual-TLS alias of the CIBA backchannel authentication endpoint.
+    pub fn backchannel_authentication_endpoint(&self) -> Option<&BackchannelAuthenticationUrl> {
+        self.backchannel_authentication_endpoint.as_ref()
+    }
+}
+
+impl<EF> ProviderMetadata<EF>
+where
+    EF: ExtraProviderMetadata,
+{
+    ///
+    /// Fetches the authorization server metadata for the given issuer from the
+    /// `/.well-known/oauth-authorization-server` endpoint, as described in
+    /// [Section 3 of RFC 8414](https://tools.ietf.org/html/rfc8414#section-3).
+    ///
+    /// The `issuer` value returned by the server is required to match `issuer_url` exactly.
+    ///
+    pub fn discover<F, RE>(
+        issuer_url: &IssuerUrl,
+        http_client: F,
+    ) -> Result<Self, DiscoveryError<RE>>
+    where
+        F: SyncHttpClient<Error = RE>,
+        RE: Error + 'static,
+    {
+        let http_response = http_client
+            .call(Self::discovery_request(issuer_url)?)
+            .map_err(DiscoveryError::Request)?;
+        Self::discovery_response(issuer_url, http_response)
+    }
+
+    ///
+    /// Asynchronously fetches the authorization server metadata for the given issuer from the
+    /// `/.well-known/oauth-authorization-server` endpoint, as described in
+    /// [Section 3 of RFC 8414](https://tools.ietf.org/html/rfc8414#section-3).
+    ///
+    /// The `issuer` value returned by the server is required to match `issuer_url` exactly.
+    ///
+    pub async fn discover_async<C, RE>(
+        issuer_url: &IssuerUrl,
+        http_client: C,
+    ) -> Result<Self, DiscoveryError<RE>>
+    where
+        C: AsyncHttpClient<Error = RE>,
+        RE: Error + 'static,
+    {
+        let http_request = Self::discovery_request(issuer_url)?;
+        let http_response = http_client
+            .call(http_request)
+            .await
+            .map_err(DiscoveryError::Request)?;
+        Self::discovery_response(issuer_url, http_response)
+    }
+
+    fn discovery_request<RE>(issuer_url: &IssuerUrl) -> Result<HttpRequest, DiscoveryError<RE>>
+    where
+        RE: Error + 'static,
+    {
+        let issuer = issuer_url.url();
+        if issuer.query().is_some() || issuer.fragment().is_some() {
+            return Err(DiscoveryError::Validation(
+                "issuer URL must not contain a query or fragment component".to_string(),
+            ));
+        }
+
+        // The well-known path is inserted between the host and path components of the issuer
+        // (see https://tools.ietf.org/html/rfc8414#section-3.1).
+        let mut url = issuer.clone();
+        url.set_path(&format!(
+            "{}{}",
+            WELL_KNOWN_PATH,
+            issuer.path().trim_end_matches('/')
+        ));
+
+        let mut headers = HeaderMap::new();
+        headers.append(ACCEPT, HeaderValue::from_static(CONTENT_TYPE_JSON));
+
+        Ok(HttpRequest {
+            url,
+            method: Method::GET,
+            headers,
+            body: Vec::new(),
+        })
+    }
+
+    fn discovery_response<RE>(
+        issuer_url: &IssuerUrl,
+        http_response: HttpResponse,
+    ) -> Result<Self, DiscoveryError<RE>>
+    where
+        RE: Error + 'static,
+    {
+        if http_response.status_code != StatusCode::OK {
+            return Err(DiscoveryError::Response(
+                http_response.status_code,
+                http_response.body,
+                "unexpected HTTP status code".to_string(),
+            ));
+        }
+
+        check_content_type(&http_response.headers).map_err(|err_msg| {
+            DiscoveryError::Response(
+                http_response.status_code,
+                http_response.body.clone(),
+                err_msg,
+            )
+        })?;
+
+        let provider_metadata = serde_json::from_slice::<Self>(&http_response.body)
+            .map_err(|err| DiscoveryError::Parse(err, http_response.body.clone()))?;
+        provider_metadata.validate(issuer_url)?;
+
+        Ok(provider_metadata)
+    }
+
+    ///
+    /// Validates that the `issuer` in this metadata document is identical to the issuer URL
+    /// used for discovery, as required by
+    /// [Section 3.3 of RFC 8414](https://tools.ietf.org/html/rfc8414#section-3.3).
+    ///
+    pub fn validate<RE>(&self, issuer_url: &IssuerUrl) -> Result<(), DiscoveryError<RE>>
+    where
+        RE: Error + 'static,
+    {
+        if self.issuer != *issuer_url {
+            return Err(DiscoveryError::Validation(format!(
+                "unexpected issuer URI `{}` (expected `{}`)",
+                self.issuer.as_str(),
+                issuer_url.as_str()
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// REQUIRED. The authorization server's issuer identifier.
+    pub fn issuer(&self) -> &IssuerUrl {
+        &self.issuer
+    }
+
+    /// URL of the authorization server's authorization endpoint.
+    pub fn authorization_endpoint(&self) -> &AuthUrl {
+        &self.authorization_endpoint
+    }
+
+    /// URL of the authorization server's token endpoint.
+    pub fn token_endpoint(&self) -> Option<&TokenUrl> {
+        self.token_endpoint.as_ref()
+    }
+
+    /// URL of the authorization server's JWK Set document.
+    pub fn jwks_uri(&self) -> Option<&JsonWebKeySetUrl> {
+        self.jwks_uri.as_ref()
+    }
+
+    /// URL of the authorization server's dynamic client registration endpoint.
+    pub fn registration_endpoint(&self) -> Option<&RegistrationUrl> {
+        self.registration_endpoint.as_ref()
+    }
+
+    /// Scope values that this authorization server supports.
+    pub fn scopes_supported(&self) -> Option<&Vec<Scope>> {
+        self.scopes_supported.as_ref()
+    }
+
+    /// REQUIRED. The `response_type` values that this authorization server supports.
+    pub fn response_types_supported(&self) -> &Vec<ResponseType> {
+        &self.response_types_supported
+    }
+
+    /// The `response_mode` values that this authorization server supports.
+    pub fn response_modes_supported(&self) -> Option<&Vec<String>> {
+        self.response_modes_supported.as_ref()
+    }
+
+    /// The grant types that this authorization server supports.
+    pub fn grant_types_supported(&self) -> Option<&Vec<GrantType>> {
+        self.grant_types_supported.as_ref()
+    }
+
+    /// Client authentication methods supported by the token endpoint.
+    pub fn token_endpoint_auth_methods_supported(&self) -> Option<&Vec<ClientAuthMethod>> {
+        self.token_endpoint_auth_methods_supported.as_ref()
+    }
+
+    /// JWS signing algorithms supported by the token endpoint for JWT client authentication.
+    pub fn token_endpoint_auth_signing_alg_values_supported(
+        &self,
+    ) -> Option<&Vec<JwsSigningAlgorithmValue>> {
+        self.token_endpoint_auth_signing_alg_values_supported
+            .as_ref()
+    }
+
+    /// URL of a page containing human-readable information for developers.
+    pub fn service_documentation(&self) -> Option<&str> {
+        self.service_documentation.as_deref()
+    }
+
+    /// Languages and scripts supported for the user interface.
+    pub fn ui_locales_supported(&self) -> Option<&Vec<String>> {
+        self.ui_locales_supported.as_ref()
+    }
+
+    /// URL of the authorization server's policy on how clients may use the data provided.
+    pub fn op_policy_uri(&self) -> Option<&str> {
+        self.op_policy_uri.as_deref()
+    }
+
+    /// URL of the authorization server's terms of service.
+    pub fn op_tos_uri(&self) -> Option<&str> {
+        self.op_tos_uri.as_deref()
+    }
+
+    /// URL of the authorization server's token revocation endpoint.
+    pub fn revocation_endpoint(&self) -> Option<&RevocationUrl> {
+        self.revocation_endpoint.as_ref()
+    }
+
+    /// Client authentication methods supported by the revocation endpoint.
+    pub fn revocation_endpoint_auth_methods_supported(&self) -> Option<&Vec<ClientAuthMethod>> {
+        self.revocation_endpoint_auth_methods_supported.as_ref()
+    }
+
+    /// URL of the authorization server's token introspection endpoint.
+    pub fn introspection_endpoint(&self) -> Option<&IntrospectUrl> {
+        self.introspection_endpoint.as_ref()
+    }
+
+    /// Client authentication methods supported by the introspection endpoint.
+    pub fn introspection_endpoint_auth_methods_supported(&self) -> Option<&Vec<ClientAuthMethod>> {
+        self.introspection_endpoint_auth_methods_supported.as_ref()
+    }
+
+    /// PKCE code challenge methods supported by this authorization server.
+    pub fn code_challenge_methods_supported(&self) -> Option<&Vec<PkceCodeChallengeMethod>> {
+        self.code_challenge_methods_supported.as_ref()
+    }
+
+    /// URL of the authorization server's device authorization endpoint.
+    pub fn device_authorization_endpoint(&self) -> Option<&DeviceAuthorizationUrl> {
+        self.device_authorization_endpoint.as_ref()
+    }
+
+    /// URL of the authorization server's pushed authorization request endpoint.
+    pub fn pushed_authorization_request_endpoint(&self) -> Option<&ParUrl> {
+        self.pushed_authorization_request_endpoint.as_ref()
+    }
+
+    /// Whether the authorization server accepts authorization requests only via PAR.
+    pub fn require_pushed_authorization_requests(&self) -> bool {
+        self.require_pushed_authorization_requests
+    }
+
+    /// Whether the authorization server includes the `iss` parameter in authorization
+    /// responses, as defined in [RFC 9207](https://tools.ietf.org/html/rfc9207).
+    pub fn authorization_response_iss_parameter_supported(&self) -> bool {
+        self.authorization_response_iss_parameter_supported
+    }
+
+    /// Whether the authorization server supports issuing access tokens bound to the client's TLS
+    /// certificate, as defined in [RFC 8705](https://tools.ietf.org/html/rfc8705#section-3.3).
+    pub fn tls_client_certificate_bound_access_tokens(&self) -> bool {
+        self.tls_client_certificate_bound_access_tokens
+    }
+
+    /// Endpoints that accept mutual-TLS connections, if they differ from the default endpoints.
+    pub fn mtls_endpoint_aliases(&self) -> Option<&MtlsEndpointAliases> {
+        self.mtls_endpoint_aliases.as_ref()
+    }
+
+    /// URL of the authorization server's CIBA backchannel authentication endpoint.
+    pub fn backchannel_authentication_endpoint(&self) -> Option<&BackchannelAuthenticationUrl> {
+        self.backchannel_authentication_endpoint.as_ref()
+    }
+
+    /// CIBA token delivery modes supported by this authorization server (`poll`, `ping` or
+    /// `push`).
+    pub fn backchannel_token_delivery_modes_supported(&self) -> Option<&Vec<String>> {
+        self.backchannel_token_delivery_modes_supported.as_ref()
+    }
+
+    /// Whether the authorization server supports the CIBA `user_code` parameter.
+    pub fn backchannel_user_code_parameter_supported(&self) -> bool {
+        self.backchannel_user_code_parameter_supported
+    }
+
+    /// Any extra fields returned in the metadata document.
+    pub fn extra_fields(&self) -> &EF {
+        &self.extra_fields
+    }
+}
+
+///
+/// Standard implementation of `ProviderMetadata` which throws away extra metadata fields.
+///
+pub type StandardProviderMetadata = ProviderMetadata<EmptyExtraProviderMetadata>;
+
+///
+/// Error encountered while fetching authorization server metadata.
+///
+#[derive(Debug, thiserror::Error)]
+pub enum DiscoveryError<RE>
+where
+    RE: Error + 'static,
+{
+    ///
+    /// An error occurred while sending the request or receiving the response (e.g., network
+    /// connectivity failed).
+    ///
+    #[error("Request failed")]
+    Request(#[source] RE),
+    ///
+    /// Server returned an invalid response.
+    ///
+    #[error("Server returned invalid response: {}", _2)]
+    Response(StatusCode, Vec<u8>, String),
+    ///
+    /// Failed to parse server response.
+    ///
+    #[error("Failed to parse server response")]
+    Parse(#[source] serde_json::Error, Vec<u8>),
+    ///
+    /// Failed to validate the server metadata.
+    ///
+    #[error("Validation error: {}", _0)]
+    Validation(String),
+    ///
+    /// Some other type of error occurred.
+    ///
+    #[error("Other error: {}", _0)]
+    Other(String),
+}
@@ -0,0 +1,234 @@
+use chrono::Utc;
+use http::header::{HeaderName, HeaderValue, AUTHORIZATION, WWW_AUTHENTICATE};
+use http::method::Method;
+use http::status::StatusCode;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use url::Url;
+
+use super::jwt::{
+    self, EcdsaSigningKey, JsonWebKey, JwsHeader, JwsSigningKey, RsaSigningKey, SigningError,
+};
+use super::{AccessToken, HttpRequest, HttpResponse};
+
+///
+/// Name of the HTTP header containing a DPoP proof.
+///
+pub const DPOP_HEADER: HeaderName = HeaderName::from_static("dpop");
+
+///
+/// Name of the HTTP header containing a nonce provided by the server.
+///
+pub const DPOP_NONCE_HEADER: HeaderName = HeaderName::from_static("dpop-nonce");
+
+///
+/// Media type (`typ` header) of DPoP proofs.
+///
+const DPOP_PROOF_TYPE: &str = "dpop+jwt";
+
+///
+/// A key used for signing DPoP proofs.
+///
+/// The public key is included in each proof (`jwk` header), and the authorization server binds
+/// the issued access token to it. The same key must therefore be used for the token request and
+/// for subsequent requests to protected resources. Symmetric keys are not supported.
+///
+pub trait DpopKey: JwsSigningKey {
+    ///
+    /// Returns the public key corresponding to this private key.
+    ///
+    fn public_jwk(&self) -> JsonWebKey;
+}
+impl DpopKey for EcdsaSigningKey {
+    fn public_jwk(&self) -> JsonWebKey {
+        EcdsaSigningKey::public_jwk(self)
+    }
+}
+impl DpopKey for RsaSigningKey {
+    fn public_jwk(&self) -> JsonWebKey {
+        RsaSigningKey::public_jwk(self)
+    }
+}
+
+///
+/// Claims of a DPoP proof, as defined in
+/// [Section 4.2 of RFC 9449](https://tools.ietf.org/html/rfc9449#section-4.2).
+///
+#[derive(Debug, Serialize)]
+struct DpopProofClaims<'a> {
+    jti: String,
+    htm: &'a str,
+    htu: String,
+    iat: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    nonce: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ath: Option<String>,
+}
+
+///
+/// Builder for DPoP proofs
+/// ([Section 4 of RFC 9449](https://tools.ietf.org/html/rfc9449#section-4)).
+///
+/// The token request builders sign proofs automatically when a key is provided using their
+/// `set_dpop_key` methods. This builder is useful for requests to protected resources (see also
+/// `authorize_request`).
+///
+#[derive(Debug)]
+pub struct DpopProofBuilder<'a> {
+    key: &'a dyn DpopKey,
+    method: &'a Method,
+    url: &'a Url,
+    nonce: Option<&'a str>,
+    access_token: Option<&'a AccessToken>,
+}
+impl<'a> DpopProofBuilder<'a> {
+    ///
+    /// Instantiate a new builder for a proof of the HTTP request with the given method and URL.
+    ///
+    pub fn new(key: &'a dyn DpopKey, method: &'a Method, url: &'a Url) -> Self {
+        Self {
+            key,
+            method,
+            url,
+            nonce: None,
+            access_token: None,
+        }
+    }
+
+    ///
+    /// Sets the nonce (`nonce` claim) most recently provided by the server in a `DPoP-Nonce`
+    /// header.
+    ///
+    pub fn set_nonce(mut self, nonce: &'a str) -> Self {
+        self.nonce = Some(nonce);
+        self
+    }
+
+    ///
+    /// Sets the access token sent along with the proof, which is required when accessing a
+    /// protected resource. The proof includes a hash of the access token (`ath` claim).
+    ///
+    pub fn set_access_token(mut self, access_token: &'a AccessToken) -> Self {
+        self.access_token = Some(access_token);
+        self
+    }
+
+    ///
+    /// Signs the proof, returning the value of the `DPoP` header.
+    ///
+    pub fn sign(self) -> Result<String, SigningError> {
+        // The `htu` claim excludes the query and fragment components of the URL.
+        let mut htu = self.url.clone();
+        htu.set_query(None);
+        htu.set_fragment(None);
+
+        let claims = DpopProofClaims {
+            jti: jwt::new_jti(),
+            htm: self.method.as_str(),
+            htu: htu.to_string(),
+            iat: Utc::now().timestamp(),
+            nonce: self.nonce,
+            ath: self.access_token.map(access_token_hash),
+        };
+        jwt::encode(
+            &JwsHeader::new(self.key)
+                .set_type(DPOP_PROOF_TYPE.to_string())
+                .set_jwk(self.key.public_jwk()),
+            &claims,
+            self.key,
+        )
+    }
+}
+
+///
+/// Returns the base64url-encoded SHA-256 hash of the access token (the `ath` claim of a DPoP
+/// proof).
+///
+pub fn access_token_hash(access_token: &AccessToken) -> String {
+    jwt::base64_url_encode(&Sha256::digest(access_token.secret().as_bytes()))
+}
+
+///
+/// Authorizes a request to a protected resource using a DPoP-bound access token, as described
+/// in [Section 7.1 of RFC 9449](https://tools.ietf.org/html/rfc9449#section-7.1).
+///
+/// This sets the `Authorization` header using the `DPoP` scheme and the `DPoP` header to a proof
+/// for the request's method and URL. If a previous response included a `DPoP-Nonce` header (see
+/// `nonce_challenge`), the nonce should be provided.
+///
+pub fn authorize_request(
+    request: &mut HttpRequest,
+    key: &dyn DpopKey,
+    access_token: &AccessToken,
+    nonce: Option<&str>,
+) -> Result<(), SigningError> {
+    let authorization = HeaderValue::from_str(&format!("DPoP {}", access_token.secret()))
+        .map_err(|_| SigningError::Other("access token is not a valid header value".to_string()))?;
+    set_proof_header(request, key, Some(access_token), nonce)?;
+    request.headers.insert(AUTHORIZATION, authorization);
+    Ok(())
+}
+
+///
+/// Returns the nonce the server requires the client to include in its DPoP proofs, if the
+/// response rejected a request because its proof did not include the nonce (the
+/// `use_dpop_nonce` error).
+///
+/// Both authorization server
+/// ([Section 8 of RFC 9449](https://tools.ietf.org/html/rfc9449#section-8)) and resource server
+/// ([Section 9 of RFC 9449](https://tools.ietf.org/html/rfc9449#section-9)) responses are
+/// supported.
+///
+pub fn nonce_challenge(response: &HttpResponse) -> Option<String> {
+    let nonce = response
+        .headers
+        .get(DPOP_NONCE_HEADER)
+        .and_then(|nonce| nonce.to_str().ok())?;
+
+    let use_dpop_nonce = match response.status_code {
+        StatusCode::BAD_REQUEST => serde_json::from_slice::<serde_json::Value>(&response.body)
+            .ok()
+            .and_then(|body| {
+                body.get("error")
+                    .and_then(|error| error.as_str())
+                    .map(|error| error == "use_dpop_nonce")
+            })
+            .unwrap_or(false),
+        StatusCode::UNAUTHORIZED => response
+            .headers
+            .get_all(WWW_AUTHENTICATE)
+            .iter()
+            .filter_map(|challenge| challenge.to_str().ok())
+            .any(|challenge| challenge.contains("use_dpop_nonce")),
+        _ => false,
+    };
+
+    if use_dpop_nonce {
+        Some(nonce.to_string())
+    } else {
+        None
+    }
+}
+
+pub(crate) fn set_proof_header(
+    request: &mut HttpRequest,
+    key: &dyn DpopKey,
+    access_token: Option<&AccessToken>,
+    nonce: Option<&str>,
+) -> Result<(), SigningError> {
+    let mut builder = DpopProofBuilder::new(key, &request.method, &request.url);
+    if let Some(access_token) = access_token {
+        builder = builder.set_access_token(access_token);
+    }
+    if let Some(nonce) = nonce {
+        builder = builder.set_nonce(nonce);
+    }
+    let proof = builder.sign()?;
+
+    request.headers.insert(
+        DPOP_HEADER,
+        HeaderValue::from_str(&proof).map_err(|err| SigningError::Other(err.to_string()))?,
+    );
+    Ok(())
+}
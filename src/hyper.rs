@@ -0,0 +1,141 @@
+use std::future::Future;
+use std::pin::Pin;
+
+use hyper_0_13::client::connect::Connect;
+use hyper_0_13::client::HttpConnector;
+use hyper_0_13::{Body, Client};
+use hyper_rustls::HttpsConnector;
+use thiserror::Error;
+
+use super::{AsyncHttpClient, HttpRequest, HttpResponse};
+
+///
+/// Error type returned by failed hyper HTTP requests.
+///
+#[derive(Debug, Error)]
+pub enum Error {
+    /// Error returned by hyper crate.
+    #[error("request failed")]
+    Hyper(#[source] hyper_0_13::Error),
+    /// Non-hyper HTTP error.
+    #[error("HTTP error")]
+    Http(#[source] http::Error),
+}
+
+///
+/// Future returned by `HyperClient`.
+///
+pub type HyperClientFuture = Pin<Box<dyn Future<Output = Result<HttpResponse, Error>> + Send>>;
+
+///
+/// Asynchronous HTTP client.
+///
+/// A new hyper client is constructed for each request. Applications sending many requests
+/// should use a `HyperClient` instead.
+///
+pub async fn async_http_client(request: HttpRequest) -> Result<HttpResponse, Error> {
+    HyperClient::new().execute(request).await
+}
+
+///
+/// Reusable asynchronous HTTP client backed by the [hyper](https://crates.io/crates/hyper)
+/// crate.
+///
+/// The underlying hyper client maintains a pool of connections, which is shared between clones of
+/// this client. By default, TLS connections are established using rustls and verified against the
+/// Mozilla root certificates bundled with the `webpki-roots` crate. Hyper never follows
+/// redirects, which would otherwise open the client up to SSRF vulnerabilities.
+///
+/// This client (and a reference to it) implements `AsyncHttpClient`, so it may be passed directly
+/// to any of the `request_async` methods of this crate.
+///
+#[derive(Clone, Debug)]
+pub struct HyperClient<C = HttpsConnector<HttpConnector>> {
+    client: Client<C, Body>,
+}
+impl HyperClient {
+    ///
+    /// Creates a new client supporting both HTTP and HTTPS (via rustls) connections.
+    ///
+    pub fn new() -> Self {
+        Self::from_client(Client::builder().build(HttpsConnector::new()))
+    }
+}
+impl Default for HyperClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl<C> HyperClient<C>
+where
+    C: Connect + Clone + Send + Sync + 'static,
+{
+    ///
+    /// Creates a new client from an existing hyper client.
+    ///
+    /// This allows the connection pool and connector to be configured (e.g., to use an
+    /// `HttpsConnector` with a custom rustls `ClientConfig` for mutual-TLS client
+    /// authentication).
+    ///
+    pub fn from_client(client: Client<C, Body>) -> Self {
+        Self { client }
+    }
+
+    ///
+    /// Sends the given request.
+    ///
+    pub async fn execute(&self, request: HttpRequest) -> Result<HttpResponse, Error> {
+        execute(&self.client, request).await
+    }
+}
+impl<C> AsyncHttpClient for HyperClient<C>
+where
+    C: Connect + Clone + Send + Sync + 'static,
+{
+    type Error = Error;
+    type Future = HyperClientFuture;
+
+    fn call(self, request: HttpRequest) -> HyperClientFuture {
+        Box::pin(async move { execute(&self.client, request).await })
+    }
+}
+impl<C> AsyncHttpClient for &HyperClient<C>
+where
+    C: Connect + Clone + Send + Sync + 'static,
+{
+    type Error = Error;
+    type Future = HyperClientFuture;
+
+    fn call(self, request: HttpRequest) -> HyperClientFuture {
+        // The client is reference-counted internally, so cloning it is cheap.
+        let client = self.client.clone();
+        Box::pin(async move { execute(&client, request).await })
+    }
+}
+
+async fn execute<C>(client: &Client<C, Body>, request: HttpRequest) -> Result<HttpResponse, Error>
+where
+    C: Connect + Clone + Send + Sync + 'static,
+{
+    let mut request_builder = http::Request::builder()
+        .method(request.method)
+        .uri(request.url.as_str());
+    for (name, value) in &request.headers {
+        request_builder = request_builder.header(name, value);
+    }
+    let request = request_builder
+        .body(Body::from(request.body))
+        .map_err(Error::Http)?;
+
+    let response = client.request(request).await.map_err(Error::Hyper)?;
+
+    let (parts, body) = response.into_parts();
+    let body = hyper_0_13::body::to_bytes(body)
+        .await
+        .map_err(Error::Hyper)?;
+    Ok(HttpResponse {
+        status_code: parts.status,
+        headers: parts.headers,
+        body: body.to_vec(),
+    })
+}
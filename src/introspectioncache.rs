@@ -0,0 +1,358 @@
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error;
+use std::fmt::Error as FormatterError;
+use std::fmt::{Debug, Formatter};
+use std::marker::PhantomData;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+
+use super::{
+    AccessToken, AsyncHttpClient, Client, ErrorResponse, RequestTokenError, SyncHttpClient,
+    TokenInspectionResponse, TokenResponse, TokenType,
+};
+
+///
+/// Default maximum amount of time for which active introspection responses are cached.
+///
+pub const DEFAULT_MAX_TTL: Duration = Duration::from_secs(300);
+
+///
+/// Default amount of time for which inactive introspection responses are cached.
+///
+pub const DEFAULT_NEGATIVE_TTL: Duration = Duration::from_secs(30);
+
+type CacheKey = [u8; 32];
+
+struct CacheEntry<TIR> {
+    token_info: TIR,
+    // `None` if the entry never expires (it may still be evicted when the cache is full).
+    expires_at: Option<DateTime<Utc>>,
+    last_used: u64,
+}
+
+struct CacheState<TIR> {
+    entries: HashMap<CacheKey, CacheEntry<TIR>>,
+    // Cache keys ordered by the last time they were used, for least-recently-used eviction.
+    lru: BTreeMap<u64, CacheKey>,
+    clock: u64,
+}
+impl<TIR> CacheState<TIR> {
+    fn touch(&mut self, key: &CacheKey) {
+        self.clock += 1;
+        let clock = self.clock;
+        if let Some(entry) = self.entries.get_mut(key) {
+            self.lru.remove(&entry.last_used);
+            entry.last_used = clock;
+            self.lru.insert(clock, *key);
+        }
+    }
+
+    fn remove(&mut self, key: &CacheKey) -> Option<CacheEntry<TIR>> {
+        let entry = self.entries.remove(key)?;
+        self.lru.remove(&entry.last_used);
+        Some(entry)
+    }
+
+    fn remove_expired(&mut self, now: DateTime<Utc>) {
+        let expired = self
+            .entries
+            .iter()
+            .filter(|(_, entry)| is_expired(entry.expires_at, now))
+            .map(|(key, _)| *key)
+            .collect::<Vec<_>>();
+        for key in expired {
+            self.remove(&key);
+        }
+    }
+
+    fn remove_least_recently_used(&mut self) {
+        let key = match self.lru.values().next() {
+            Some(key) => *key,
+            None => return,
+        };
+        self.remove(&key);
+    }
+}
+
+///
+/// Size-bounded cache of token introspection responses
+/// ([RFC 7662](https://tools.ietf.org/html/rfc7662)).
+///
+/// Resource servers that introspect the access token of every request may use this cache to
+/// avoid repeatedly introspecting the same token. Responses are keyed by a SHA-256 hash of the
+/// access token, so the cache never stores the tokens themselves.
+///
+/// Active responses are cached until the token's `exp` time, but for no longer than the
+/// maximum TTL (see `set_max_ttl`), which bounds how long a revoked token may continue to be
+/// accepted. Inactive responses are cached for the negative TTL (see `set_negative_ttl`). Once
+/// the cache reaches its capacity, expired entries are removed, followed by the least recently
+/// used ones.
+///
+/// An `IntrospectionCache` may be shared between threads or tasks (e.g., using an `Arc`). The
+/// cache is not locked while introspection requests are in flight, so concurrent requests for
+/// the same uncached token may each introspect it.
+///
+pub struct IntrospectionCache<TT, TIR>
+where
+    TT: TokenType,
+    TIR: TokenInspectionResponse<TT> + Clone,
+{
+    capacity: usize,
+    max_ttl: Duration,
+    negative_ttl: Duration,
+    state: Mutex<CacheState<TIR>>,
+    time_fn: Arc<dyn Fn() -> DateTime<Utc> + Send + Sync>,
+    _phantom: PhantomData<TT>,
+}
+impl<TT, TIR> IntrospectionCache<TT, TIR>
+where
+    TT: TokenType,
+    TIR: TokenInspectionResponse<TT> + Clone,
+{
+    ///
+    /// Creates a new cache holding at most `capacity` introspection responses.
+    ///
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            max_ttl: DEFAULT_MAX_TTL,
+            negative_ttl: DEFAULT_NEGATIVE_TTL,
+            state: Mutex::new(CacheState {
+                entries: HashMap::new(),
+                lru: BTreeMap::new(),
+                clock: 0,
+            }),
+            time_fn: Arc::new(Utc::now),
+            _phantom: PhantomData,
+        }
+    }
+
+    ///
+    /// Sets the maximum amount of time for which active introspection responses are cached.
+    /// Defaults to `DEFAULT_MAX_TTL`.
+    ///
+    pub fn set_max_ttl(mut self, max_ttl: Duration) -> Self {
+        self.max_ttl = max_ttl;
+        self
+    }
+
+    ///
+    /// Sets the amount of time for which inactive introspection responses are cached. Defaults
+    /// to `DEFAULT_NEGATIVE_TTL`. A zero duration disables caching of inactive responses.
+    ///
+    pub fn set_negative_ttl(mut self, negative_ttl: Duration) -> Self {
+        self.negative_ttl = negative_ttl;
+        self
+    }
+
+    ///
+    /// Specifies a function for returning the current time.
+    ///
+    /// This function is used for computing the expiration time of cache entries.
+    ///
+    pub fn set_time_fn<T>(mut self, time_fn: T) -> Self
+    where
+        T: Fn() -> DateTime<Utc> + Send + Sync + 'static,
+    {
+        self.time_fn = Arc::new(time_fn);
+        self
+    }
+
+    ///
+    /// Returns the cached introspection response for the given token, if any.
+    ///
+    pub fn get(&self, token: &AccessToken) -> Option<TIR> {
+        let key = cache_key(token);
+        let now = (*self.time_fn)();
+        let mut state = self.lock();
+        match state.entries.get(&key) {
+            Some(entry) if !is_expired(entry.expires_at, now) => {}
+            Some(_) => {
+                state.remove(&key);
+                return None;
+            }
+            None => return None,
+        }
+        state.touch(&key);
+        state
+            .entries
+            .get(&key)
+            .map(|entry| entry.token_info.clone())
+    }
+
+    ///
+    /// Caches the introspection response for the given token.
+    ///
+    /// Responses that have already expired (or that would be cached for a zero duration) are
+    /// not cached.
+    ///
+    pub fn insert(&self, token: &AccessToken, token_info: TIR) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        let now = (*self.time_fn)();
+        let ttl = if token_info.active() {
+            self.max_ttl
+        } else {
+            self.negative_ttl
+        };
+        // A TTL too large to represent doesn't limit how long the response is cached.
+        let ttl_expires_at = chrono::Duration::from_std(ttl)
+            .ok()
+            .and_then(|ttl| now.checked_add_signed(ttl));
+        let exp = if token_info.active() {
+            token_info.exp()
+        } else {
+            None
+        };
+        let expires_at = match (ttl_expires_at, exp) {
+            (Some(ttl_expires_at), Some(exp)) => Some(ttl_expires_at.min(exp)),
+            (ttl_expires_at, exp) => ttl_expires_at.or(exp),
+        };
+        if is_expired(expires_at, now) {
+            return;
+        }
+
+        let key = cache_key(token);
+        let mut state = self.lock();
+        state.remove(&key);
+        if state.entries.len() >= self.capacity {
+            state.remove_expired(now);
+        }
+        while state.entries.len() >= self.capacity {
+            state.remove_least_recently_used();
+        }
+        state.clock += 1;
+        let last_used = state.clock;
+        state.entries.insert(
+            key,
+            CacheEntry {
+                token_info,
+                expires_at,
+                last_used,
+            },
+        );
+        state.lru.insert(last_used, key);
+    }
+
+    ///
+    /// Removes the cached introspection response for the given token (e.g., after the token
+    /// has been revoked).
+    ///
+    pub fn remove(&self, token: &AccessToken) {
+        self.lock().remove(&cache_key(token));
+    }
+
+    ///
+    /// Removes all cached introspection responses.
+    ///
+    pub fn clear(&self) {
+        let mut state = self.lock();
+        state.entries.clear();
+        state.lru.clear();
+    }
+
+    ///
+    /// Returns the number of cached introspection responses, including any expired responses
+    /// that have not yet been removed.
+    ///
+    pub fn len(&self) -> usize {
+        self.lock().entries.len()
+    }
+
+    ///
+    /// Returns `true` if the cache contains no introspection responses.
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    ///
+    /// Returns the cached introspection response for the given token, or synchronously
+    /// introspects the token using the given client and caches the response.
+    ///
+    pub fn introspect<TE, TR, F, RE>(
+        &self,
+        client: &Client<TE, TR, TT, TIR>,
+        token: &AccessToken,
+        http_client: F,
+    ) -> Result<TIR, RequestTokenError<RE, TE>>
+    where
+        TE: ErrorResponse + 'static,
+        TR: TokenResponse<TT>,
+        F: SyncHttpClient<Error = RE>,
+        RE: Error + 'static,
+    {
+        if let Some(token_info) = self.get(token) {
+            return Ok(token_info);
+        }
+
+        let token_info = client
+            .introspect(token)
+            .set_token_type_hint("access_token")
+            .request(http_client)?;
+        self.insert(token, token_info.clone());
+        Ok(token_info)
+    }
+
+    ///
+    /// Returns the cached introspection response for the given token, or asynchronously
+    /// introspects the token using the given client and caches the response.
+    ///
+    pub async fn introspect_async<TE, TR, C, RE>(
+        &self,
+        client: &Client<TE, TR, TT, TIR>,
+        token: &AccessToken,
+        http_client: C,
+    ) -> Result<TIR, RequestTokenError<RE, TE>>
+    where
+        TE: ErrorResponse + 'static,
+        TR: TokenResponse<TT>,
+        C: AsyncHttpClient<Error = RE>,
+        RE: Error + 'static,
+    {
+        if let Some(token_info) = self.get(token) {
+            return Ok(token_info);
+        }
+
+        let token_info = client
+            .introspect(token)
+            .set_token_type_hint("access_token")
+            .request_async(http_client)
+            .await?;
+        self.insert(token, token_info.clone());
+        Ok(token_info)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, CacheState<TIR>> {
+        self.state.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+impl<TT, TIR> Debug for IntrospectionCache<TT, TIR>
+where
+    TT: TokenType,
+    TIR: TokenInspectionResponse<TT> + Clone,
+{
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FormatterError> {
+        f.debug_struct("IntrospectionCache")
+            .field("capacity", &self.capacity)
+            .field("max_ttl", &self.max_ttl)
+            .field("negative_ttl", &self.negative_ttl)
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+fn cache_key(token: &AccessToken) -> CacheKey {
+    let mut key = [0u8; 32];
+    key.copy_from_slice(&Sha256::digest(token.secret().as_bytes()));
+    key
+}
+
+fn is_expired(expires_at: Option<DateTime<Utc>>, now: DateTime<Utc>) -> bool {
+    matches!(expires_at, Some(expires_at) if expires_at <= now)
+}
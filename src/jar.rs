@@ -0,0 +1,167 @@
+use std::time::Duration;
+
+use chrono::Utc;
+use url::Url;
+
+use super::jwt::{encode, new_jti, JweEncryptionKey, JwsHeader, JwsSigningKey, SigningError};
+use super::{AuthorizationRequest, CsrfToken, RequestUri};
+
+///
+/// Media type of request objects (`typ` header), as defined in
+/// [Section 4 of RFC 9101](https://tools.ietf.org/html/rfc9101#section-4).
+///
+pub const REQUEST_OBJECT_TYPE: &str = "oauth-authz-req+jwt";
+
+/// Default lifetime of request objects.
+const REQUEST_OBJECT_LIFETIME: Duration = Duration::from_secs(300);
+
+///
+/// Builder for an authorization request whose parameters are passed in a signed (and
+/// optionally encrypted) request object, as defined in
+/// [RFC 9101](https://tools.ietf.org/html/rfc9101).
+///
+/// All of the authorization request parameters (including the scopes, state, PKCE challenge,
+/// redirect URI and extra params) are included as claims of the request object. Only the
+/// `client_id` is duplicated in the query of the authorization URL, as required by
+/// [Section 5 of RFC 9101](https://tools.ietf.org/html/rfc9101#section-5).
+///
+/// Instances are created using `AuthorizationRequest::request_object`.
+///
+#[derive(Debug)]
+pub struct RequestObjectBuilder<'a> {
+    authorization_request: AuthorizationRequest<'a>,
+    audience: String,
+    signing_key: &'a dyn JwsSigningKey,
+    encryption_key: Option<&'a dyn JweEncryptionKey>,
+    lifetime: Duration,
+}
+impl<'a> RequestObjectBuilder<'a> {
+    pub(crate) fn new(
+        authorization_request: AuthorizationRequest<'a>,
+        audience: String,
+        signing_key: &'a dyn JwsSigningKey,
+    ) -> Self {
+        Self {
+            authorization_request,
+            audience,
+            signing_key,
+            encryption_key: None,
+            lifetime: REQUEST_OBJECT_LIFETIME,
+        }
+    }
+
+    ///
+    /// Encrypts the signed request object to the authorization server using the given key
+    /// (e.g., one of the keys published in its JWKS document).
+    ///
+    pub fn set_encryption_key(mut self, encryption_key: &'a dyn JweEncryptionKey) -> Self {
+        self.encryption_key = Some(encryption_key);
+        self
+    }
+
+    ///
+    /// Sets the lifetime of the request object (`exp` claim). Defaults to five minutes.
+    ///
+    pub fn set_lifetime(mut self, lifetime: Duration) -> Self {
+        self.lifetime = lifetime;
+        self
+    }
+
+    ///
+    /// Returns the signed (and, if an encryption key was provided, encrypted) request object.
+    ///
+    pub fn request_object(&self) -> Result<String, SigningError> {
+        if let Some(err) = self.authorization_request.authorization_details.error() {
+            return Err(SigningError::Other(format!(
+                "failed to serialize authorization details: {}",
+                err
+            )));
+        }
+
+        let mut claims = serde_json::Map::new();
+        for (name, value) in self.authorization_request.params() {
+            let value = if name == "authorization_details" {
+                serde_json::Value::Array(
+                    self.authorization_request
+                        .authorization_details
+                        .values()
+                        .to_vec(),
+                )
+            } else {
+                serde_json::Value::String(value.into_owned())
+            };
+
+            // Parameters that may be repeated (e.g., `resource`) are represented as arrays.
+            match claims.get_mut(name) {
+                Some(serde_json::Value::Array(values)) => values.push(value),
+                Some(existing) => {
+                    *existing = serde_json::Value::Array(vec![existing.take(), value])
+                }
+                None => {
+                    claims.insert(name.to_string(), value);
+                }
+            }
+        }
+
+        let now = Utc::now().timestamp();
+        claims.insert(
+            "iss".to_string(),
+            self.authorization_request.client_id.as_str().into(),
+        );
+        claims.insert("aud".to_string(), self.audience.as_str().into());
+        claims.insert("iat".to_string(), now.into());
+        claims.insert("nbf".to_string(), now.into());
+        claims.insert(
+            "exp".to_string(),
+            (now + self.lifetime.as_secs() as i64).into(),
+        );
+        claims.insert("jti".to_string(), new_jti().into());
+
+        let signed = encode(
+            &JwsHeader::new(self.signing_key).set_type(REQUEST_OBJECT_TYPE.to_string()),
+            &claims,
+            self.signing_key,
+        )?;
+        match self.encryption_key {
+            Some(encryption_key) => encryption_key.encrypt(signed.as_bytes(), Some("JWT")),
+            None => Ok(signed),
+        }
+    }
+
+    ///
+    /// Returns the full authorization URL, which passes the request object by value in the
+    /// `request` parameter, and the CSRF state for this authorization request.
+    ///
+    pub fn url(self) -> Result<(Url, CsrfToken), SigningError> {
+        let request_object = self.request_object()?;
+        let url = self.authorization_url(&[("request", &request_object)]);
+        Ok((url, self.authorization_request.state))
+    }
+
+    ///
+    /// Returns the full authorization URL, which passes the request object by reference in the
+    /// `request_uri` parameter, the CSRF state for this authorization request, and the request
+    /// object.
+    ///
+    /// The client must make the returned request object available at `request_uri` (with
+    /// the `application/oauth-authz-req+jwt` content type) before redirecting the user agent
+    /// to the authorization URL, as described in
+    /// [Section 5.2 of RFC 9101](https://tools.ietf.org/html/rfc9101#section-5.2).
+    ///
+    pub fn url_with_request_uri(
+        self,
+        request_uri: &RequestUri,
+    ) -> Result<(Url, CsrfToken, String), SigningError> {
+        let request_object = self.request_object()?;
+        let url = self.authorization_url(&[("request_uri", request_uri.as_str())]);
+        Ok((url, self.authorization_request.state, request_object))
+    }
+
+    fn authorization_url(&self, params: &[(&str, &str)]) -> Url {
+        let mut url: Url = self.authorization_request.auth_url.url().to_owned();
+        url.query_pairs_mut()
+            .append_pair("client_id", self.authorization_request.client_id)
+            .extend_pairs(params);
+        url
+    }
+}
@@ -0,0 +1,1447 @@
+use std::error::Error;
+use std::fmt::Error as FormatterError;
+use std::fmt::{Debug, Formatter};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use http::header::{HeaderMap, HeaderValue, ACCEPT};
+use http::method::Method;
+use http::status::StatusCode;
+use rand::{thread_rng, Rng};
+use ring::rand::{SecureRandom, SystemRandom};
+use ring::signature::{
+    EcdsaKeyPair, KeyPair, RsaKeyPair, ECDSA_P256_SHA256_FIXED_SIGNING, RSA_PKCS1_SHA256,
+};
+use ring::{aead, agreement};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use super::{
+    AsyncHttpClient, ClientId, ClientSecret, HttpRequest, HttpResponse, JsonWebKeySetUrl,
+    JwtBearerAssertion, Scope, SyncHttpClient, TokenUrl,
+};
+
+///
+/// Lifetime of the client assertions generated for JWT-based client authentication.
+///
+const CLIENT_ASSERTION_LIFETIME: Duration = Duration::from_secs(300);
+
+///
+/// Default lifetime of the assertions generated by `JwtBearerAssertionBuilder`.
+///
+const JWT_BEARER_ASSERTION_LIFETIME: Duration = Duration::from_secs(3600);
+
+///
+/// Value of the `grant_type` parameter for the JWT bearer authorization grant, as defined in
+/// [Section 2.1 of RFC 7523](https://tools.ietf.org/html/rfc7523#section-2.1).
+///
+pub const JWT_BEARER_GRANT_TYPE: &str = "urn:ietf:params:oauth:grant-type:jwt-bearer";
+
+///
+/// Value of the `client_assertion_type` parameter for JWT client assertions, as defined in
+/// [Section 2.2 of RFC 7523](https://tools.ietf.org/html/rfc7523#section-2.2).
+///
+pub const CLIENT_ASSERTION_TYPE_JWT_BEARER: &str =
+    "urn:ietf:params:oauth:client-assertion-type:jwt-bearer";
+
+///
+/// Error encountered while signing or encrypting a JSON Web Token.
+///
+#[derive(Debug, thiserror::Error)]
+pub enum SigningError {
+    ///
+    /// The signing key is malformed or unsupported.
+    ///
+    #[error("Invalid key: {}", _0)]
+    InvalidKey(String),
+    ///
+    /// The cryptographic backend failed to produce a signature.
+    ///
+    #[error("Crypto error")]
+    Crypto,
+    ///
+    /// Failed to serialize the JWT header or claims.
+    ///
+    #[error("Failed to serialize JWT")]
+    Serialize(#[source] serde_json::Error),
+    ///
+    /// Some other type of error occurred.
+    ///
+    #[error("Other error: {}", _0)]
+    Other(String),
+}
+
+///
+/// JSON Web Signature (JWS) algorithms supported by this crate, as defined in
+/// [Section 3.1 of RFC 7518](https://tools.ietf.org/html/rfc7518#section-3.1).
+///
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum JwsSigningAlgorithm {
+    ///
+    /// HMAC using SHA-256 (`HS256`).
+    ///
+    #[serde(rename = "HS256")]
+    HmacSha256,
+    ///
+    /// RSASSA-PKCS1-v1_5 using SHA-256 (`RS256`).
+    ///
+    #[serde(rename = "RS256")]
+    RsaSsaPkcs1V15Sha256,
+    ///
+    /// ECDSA using P-256 and SHA-256 (`ES256`).
+    ///
+    #[serde(rename = "ES256")]
+    EcdsaP256Sha256,
+}
+impl AsRef<str> for JwsSigningAlgorithm {
+    fn as_ref(&self) -> &str {
+        match self {
+            JwsSigningAlgorithm::HmacSha256 => "HS256",
+            JwsSigningAlgorithm::RsaSsaPkcs1V15Sha256 => "RS256",
+            JwsSigningAlgorithm::EcdsaP256Sha256 => "ES256",
+        }
+    }
+}
+
+///
+/// A key used for signing JSON Web Tokens.
+///
+/// This trait is implemented by the key types in this module, and may be implemented by
+/// callers to support other algorithms or keys stored in hardware security modules.
+///
+pub trait JwsSigningKey: Debug + Send + Sync {
+    ///
+    /// The JWS algorithm (`alg` header) used by this key.
+    ///
+    fn signing_alg(&self) -> JwsSigningAlgorithm;
+
+    ///
+    /// The key ID (`kid` header), if any.
+    ///
+    fn key_id(&self) -> Option<&str>;
+
+    ///
+    /// Signs the given message (the JWS signing input), returning the raw signature bytes.
+    ///
+    fn sign(&self, message: &[u8]) -> Result<Vec<u8>, SigningError>;
+}
+
+///
+/// Symmetric key for signing JWTs using HMAC SHA-256 (`HS256`).
+///
+pub struct HmacSha256Key {
+    key: ring::hmac::Key,
+    key_id: Option<String>,
+}
+impl HmacSha256Key {
+    ///
+    /// Creates a new key from the given shared secret.
+    ///
+    pub fn new(secret: &[u8]) -> Self {
+        Self {
+            key: ring::hmac::Key::new(ring::hmac::HMAC_SHA256, secret),
+            key_id: None,
+        }
+    }
+
+    ///
+    /// Creates a new key from a client secret, as used by the `client_secret_jwt` client
+    /// authentication method.
+    ///
+    pub fn from_client_secret(client_secret: &ClientSecret) -> Self {
+        Self::new(client_secret.secret().as_bytes())
+    }
+
+    ///
+    /// Sets the key ID (`kid` header) included in signed JWTs.
+    ///
+    pub fn set_key_id(mut self, key_id: String) -> Self {
+        self.key_id = Some(key_id);
+        self
+    }
+}
+impl Debug for HmacSha256Key {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FormatterError> {
+        f.debug_struct("HmacSha256Key")
+            .field("key", &"[redacted]")
+            .field("key_id", &self.key_id)
+            .finish()
+    }
+}
+impl JwsSigningKey for HmacSha256Key {
+    fn signing_alg(&self) -> JwsSigningAlgorithm {
+        JwsSigningAlgorithm::HmacSha256
+    }
+
+    fn key_id(&self) -> Option<&str> {
+        self.key_id.as_deref()
+    }
+
+    fn sign(&self, message: &[u8]) -> Result<Vec<u8>, SigningError> {
+        Ok(ring::hmac::sign(&self.key, message).as_ref().to_vec())
+    }
+}
+
+///
+/// RSA private key for signing JWTs using RSASSA-PKCS1-v1_5 with SHA-256 (`RS256`).
+///
+pub struct RsaSigningKey {
+    key_pair: RsaKeyPair,
+    key_id: Option<String>,
+}
+impl RsaSigningKey {
+    ///
+    /// Parses a PEM-encoded RSA private key in either PKCS#8 (`BEGIN PRIVATE KEY`) or PKCS#1
+    /// (`BEGIN RSA PRIVATE KEY`) format.
+    ///
+    pub fn from_pem(pem: &str) -> Result<Self, SigningError> {
+        let (label, der) = decode_pem(pem)?;
+        match label.as_str() {
+            "PRIVATE KEY" => Self::from_pkcs8_der(&der),
+            "RSA PRIVATE KEY" => RsaKeyPair::from_der(&der)
+                .map(|key_pair| Self {
+                    key_pair,
+                    key_id: None,
+                })
+                .map_err(|err| SigningError::InvalidKey(err.to_string())),
+            other => Err(SigningError::InvalidKey(format!(
+                "unsupported PEM label `{}`",
+                other
+            ))),
+        }
+    }
+
+    ///
+    /// Parses a DER-encoded PKCS#8 RSA private key.
+    ///
+    pub fn from_pkcs8_der(der: &[u8]) -> Result<Self, SigningError> {
+        RsaKeyPair::from_pkcs8(der)
+            .map(|key_pair| Self {
+                key_pair,
+                key_id: None,
+            })
+            .map_err(|err| SigningError::InvalidKey(err.to_string()))
+    }
+
+    ///
+    /// Sets the key ID (`kid` header) included in signed JWTs.
+    ///
+    pub fn set_key_id(mut self, key_id: String) -> Self {
+        self.key_id = Some(key_id);
+        self
+    }
+
+    ///
+    /// Returns the public key corresponding to this private key as a JSON Web Key.
+    ///
+    pub fn public_jwk(&self) -> JsonWebKey {
+        let public_key = self.key_pair.public_key();
+        JsonWebKey {
+            kty: "RSA".to_string(),
+            kid: self.key_id.clone(),
+            alg: None,
+            key_use: None,
+            crv: None,
+            x: None,
+            y: None,
+            n: Some(base64_url_encode(
+                public_key.modulus().big_endian_without_leading_zero(),
+            )),
+            e: Some(base64_url_encode(
+                public_key.exponent().big_endian_without_leading_zero(),
+            )),
+        }
+    }
+}
+impl Debug for RsaSigningKey {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FormatterError> {
+        f.debug_struct("RsaSigningKey")
+            .field("key_pair", &"[redacted]")
+            .field("key_id", &self.key_id)
+            .finish()
+    }
+}
+impl JwsSigningKey for RsaSigningKey {
+    fn signing_alg(&self) -> JwsSigningAlgorithm {
+        JwsSigningAlgorithm::RsaSsaPkcs1V15Sha256
+    }
+
+    fn key_id(&self) -> Option<&str> {
+        self.key_id.as_deref()
+    }
+
+    fn sign(&self, message: &[u8]) -> Result<Vec<u8>, SigningError> {
+        let mut signature = vec![0; self.key_pair.public_modulus_len()];
+        self.key_pair
+            .sign(
+                &RSA_PKCS1_SHA256,
+                &SystemRandom::new(),
+                message,
+                &mut signature,
+            )
+            .map_err(|_| SigningError::Crypto)?;
+        Ok(signature)
+    }
+}
+
+///
+/// Elliptic curve private key for signing JWTs using ECDSA with P-256 and SHA-256 (`ES256`).
+///
+pub struct EcdsaSigningKey {
+    key_pair: EcdsaKeyPair,
+    key_id: Option<String>,
+}
+impl EcdsaSigningKey {
+    ///
+    /// Parses a PEM-encoded PKCS#8 (`BEGIN PRIVATE KEY`) P-256 private key.
+    ///
+    pub fn from_pem(pem: &str) -> Result<Self, SigningError> {
+        let (label, der) = decode_pem(pem)?;
+        if label != "PRIVATE KEY" {
+            return Err(SigningError::InvalidKey(format!(
+                "unsupported PEM label `{}`; expected a PKCS#8 `PRIVATE KEY`",
+                label
+            )));
+        }
+        Self::from_pkcs8_der(&der)
+    }
+
+    ///
+    /// Parses a DER-encoded PKCS#8 P-256 private key.
+    ///
+    pub fn from_pkcs8_der(der: &[u8]) -> Result<Self, SigningError> {
+        EcdsaKeyPair::from_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, der)
+            .map(|key_pair| Self {
+                key_pair,
+                key_id: None,
+            })
+            .map_err(|err| SigningError::InvalidKey(err.to_string()))
+    }
+
+    ///
+    /// Generates a new random P-256 private key.
+    ///
+    pub fn new_random() -> Result<Self, SigningError> {
+        let pkcs8 =
+            EcdsaKeyPair::generate_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, &SystemRandom::new())
+                .map_err(|_| SigningError::Crypto)?;
+        Self::from_pkcs8_der(pkcs8.as_ref())
+    }
+
+    ///
+    /// Sets the key ID (`kid` header) included in signed JWTs.
+    ///
+    pub fn set_key_id(mut self, key_id: String) -> Self {
+        self.key_id = Some(key_id);
+        self
+    }
+
+    ///
+    /// Returns the public key corresponding to this private key as a JSON Web Key.
+    ///
+    pub fn public_jwk(&self) -> JsonWebKey {
+        // The public key is an uncompressed point (0x04 followed by the X and Y coordinates).
+        let point = &self.key_pair.public_key().as_ref()[1..];
+        let (x, y) = point.split_at(point.len() / 2);
+        JsonWebKey {
+            kty: "EC".to_string(),
+            kid: self.key_id.clone(),
+            alg: None,
+            key_use: None,
+            crv: Some("P-256".to_string()),
+            x: Some(base64_url_encode(x)),
+            y: Some(base64_url_encode(y)),
+            n: None,
+            e: None,
+        }
+    }
+}
+impl Debug for EcdsaSigningKey {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FormatterError> {
+        f.debug_struct("EcdsaSigningKey")
+            .field("key_pair", &"[redacted]")
+            .field("key_id", &self.key_id)
+            .finish()
+    }
+}
+impl JwsSigningKey for EcdsaSigningKey {
+    fn signing_alg(&self) -> JwsSigningAlgorithm {
+        JwsSigningAlgorithm::EcdsaP256Sha256
+    }
+
+    fn key_id(&self) -> Option<&str> {
+        self.key_id.as_deref()
+    }
+
+    fn sign(&self, message: &[u8]) -> Result<Vec<u8>, SigningError> {
+        self.key_pair
+            .sign(&SystemRandom::new(), message)
+            .map(|signature| signature.as_ref().to_vec())
+            .map_err(|_| SigningError::Crypto)
+    }
+}
+
+///
+/// Public key in JSON Web Key (JWK) format, as defined in
+/// [RFC 7517](https://tools.ietf.org/html/rfc7517).
+///
+/// Only the parameters of RSA and elliptic curve public keys defined in
+/// [Section 6 of RFC 7518](https://tools.ietf.org/html/rfc7518#section-6) are supported.
+///
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct JsonWebKey {
+    kty: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    kid: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    alg: Option<String>,
+    #[serde(rename = "use", skip_serializing_if = "Option::is_none")]
+    key_use: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    crv: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    x: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    y: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    n: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    e: Option<String>,
+}
+impl JsonWebKey {
+    ///
+    /// The key type (`kty` parameter), such as `RSA` or `EC`.
+    ///
+    pub fn kty(&self) -> &str {
+        &self.kty
+    }
+
+    ///
+    /// The key ID (`kid` parameter), used to select a key from a JSON Web Key Set.
+    ///
+    pub fn kid(&self) -> Option<&str> {
+        self.kid.as_deref()
+    }
+
+    ///
+    /// The algorithm intended for use with the key (`alg` parameter), such as `RS256`.
+    ///
+    pub fn alg(&self) -> Option<&str> {
+        self.alg.as_deref()
+    }
+
+    ///
+    /// The intended use of the key (`use` parameter): `sig` for signatures or `enc` for
+    /// encryption.
+    ///
+    pub fn key_use(&self) -> Option<&str> {
+        self.key_use.as_deref()
+    }
+
+    ///
+    /// The curve of an elliptic curve key (`crv` parameter), such as `P-256`.
+    ///
+    pub fn crv(&self) -> Option<&str> {
+        self.crv.as_deref()
+    }
+
+    ///
+    /// The base64url-encoded X coordinate of an elliptic curve key (`x` parameter).
+    ///
+    pub fn x(&self) -> Option<&str> {
+        self.x.as_deref()
+    }
+
+    ///
+    /// The base64url-encoded Y coordinate of an elliptic curve key (`y` parameter).
+    ///
+    pub fn y(&self) -> Option<&str> {
+        self.y.as_deref()
+    }
+
+    ///
+    /// The base64url-encoded modulus of an RSA key (`n` parameter).
+    ///
+    pub fn n(&self) -> Option<&str> {
+        self.n.as_deref()
+    }
+
+    ///
+    /// The base64url-encoded public exponent of an RSA key (`e` parameter).
+    ///
+    pub fn e(&self) -> Option<&str> {
+        self.e.as_deref()
+    }
+}
+
+///
+/// Set of public keys in JSON Web Key Set (JWKS) format, as defined in
+/// [Section 5 of RFC 7517](https://tools.ietf.org/html/rfc7517#section-5).
+///
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct JsonWebKeySet {
+    keys: Vec<JsonWebKey>,
+}
+impl JsonWebKeySet {
+    ///
+    /// Instantiate a new key set containing the given keys.
+    ///
+    pub fn new(keys: Vec<JsonWebKey>) -> Self {
+        Self { keys }
+    }
+
+    ///
+    /// The keys in this set.
+    ///
+    pub fn keys(&self) -> &Vec<JsonWebKey> {
+        &self.keys
+    }
+}
+
+///
+/// Default maximum age of the keys cached by `JwksCache`.
+///
+pub const DEFAULT_JWKS_MAX_AGE: Duration = Duration::from_secs(3600);
+
+///
+/// Default minimum amount of time between refreshes of the keys cached by `JwksCache` when
+/// looking up an unknown key ID.
+///
+pub const DEFAULT_JWKS_MIN_REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
+struct CachedJwks {
+    jwks: JsonWebKeySet,
+    fetched_at: DateTime<Utc>,
+}
+
+enum KeyLookup {
+    Keys(Vec<JsonWebKey>),
+    Fetch,
+}
+
+///
+/// Cache of the JSON Web Key Set published by an authorization server (e.g., at the
+/// `jwks_uri` of its metadata).
+///
+/// Keys are fetched on first use and refreshed once they are older than the maximum age (see
+/// `set_max_age`). Looking up a key ID that is not in the cached set (e.g., after the
+/// authorization server rotated its keys) also refreshes the cache, but no more often than the
+/// minimum refresh interval (see `set_min_refresh_interval`), so that tokens with random key IDs
+/// cannot be used to flood the JWKS endpoint with requests.
+///
+/// A `JwksCache` may be shared between threads or tasks (e.g., using an `Arc`).
+///
+pub struct JwksCache {
+    jwks_url: JsonWebKeySetUrl,
+    max_age: Duration,
+    min_refresh_interval: Duration,
+    state: Mutex<Option<CachedJwks>>,
+    time_fn: Arc<dyn Fn() -> DateTime<Utc> + Send + Sync>,
+}
+impl JwksCache {
+    ///
+    /// Creates a new, empty cache of the key set published at the given URL.
+    ///
+    pub fn new(jwks_url: JsonWebKeySetUrl) -> Self {
+        Self {
+            jwks_url,
+            max_age: DEFAULT_JWKS_MAX_AGE,
+            min_refresh_interval: DEFAULT_JWKS_MIN_REFRESH_INTERVAL,
+            state: Mutex::new(None),
+            time_fn: Arc::new(Utc::now),
+        }
+    }
+
+    ///
+    /// Sets the maximum age of the cached keys, after which they are fetched again. Defaults to
+    /// `DEFAULT_JWKS_MAX_AGE`.
+    ///
+    pub fn set_max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = max_age;
+        self
+    }
+
+    ///
+    /// Sets the minimum amount of time between refreshes caused by unknown key IDs. Defaults to
+    /// `DEFAULT_JWKS_MIN_REFRESH_INTERVAL`.
+    ///
+    pub fn set_min_refresh_interval(mut self, min_refresh_interval: Duration) -> Self {
+        self.min_refresh_interval = min_refresh_interval;
+        self
+    }
+
+    ///
+    /// Specifies a function for returning the current time.
+    ///
+    /// This function is used for determining when the cached keys need to be refreshed.
+    ///
+    pub fn set_time_fn<T>(mut self, time_fn: T) -> Self
+    where
+        T: Fn() -> DateTime<Utc> + Send + Sync + 'static,
+    {
+        self.time_fn = Arc::new(time_fn);
+        self
+    }
+
+    ///
+    /// URL of the JSON Web Key Set.
+    ///
+    pub fn jwks_url(&self) -> &JsonWebKeySetUrl {
+        &self.jwks_url
+    }
+
+    ///
+    /// Returns the cached key set, if any, without fetching it.
+    ///
+    pub fn cached_keys(&self) -> Option<JsonWebKeySet> {
+        self.lock().as_ref().map(|cached| cached.jwks.clone())
+    }
+
+    ///
+    /// Returns the keys with the given key ID (or all keys if `kid` is `None`), synchronously
+    /// fetching the key set if it has not been cached, is too old, or does not contain the key
+    /// ID.
+    ///
+    pub fn find_keys<F, RE>(
+        &self,
+        kid: Option<&str>,
+        http_client: F,
+    ) -> Result<Vec<JsonWebKey>, JwksError<RE>>
+    where
+        F: SyncHttpClient<Error = RE>,
+        RE: Error + 'static,
+    {
+        match self.lookup(kid) {
+            KeyLookup::Keys(keys) => Ok(keys),
+            KeyLookup::Fetch => Ok(matching_keys(&self.fetch(http_client)?, kid)),
+        }
+    }
+
+    ///
+    /// Returns the keys with the given key ID (or all keys if `kid` is `None`), asynchronously
+    /// fetching the key set if it has not been cached, is too old, or does not contain the key
+    /// ID.
+    ///
+    pub async fn find_keys_async<C, RE>(
+        &self,
+        kid: Option<&str>,
+        http_client: C,
+    ) -> Result<Vec<JsonWebKey>, JwksError<RE>>
+    where
+        C: AsyncHttpClient<Error = RE>,
+        RE: Error + 'static,
+    {
+        match self.lookup(kid) {
+            KeyLookup::Keys(keys) => Ok(keys),
+            KeyLookup::Fetch => Ok(matching_keys(&self.fetch_async(http_client).await?, kid)),
+        }
+    }
+
+    ///
+    /// Synchronously fetches the key set and replaces the cached keys.
+    ///
+    pub fn fetch<F, RE>(&self, http_client: F) -> Result<JsonWebKeySet, JwksError<RE>>
+    where
+        F: SyncHttpClient<Error = RE>,
+        RE: Error + 'static,
+    {
+        let http_response = http_client
+            .call(self.jwks_request())
+            .map_err(JwksError::Request)?;
+        self.jwks_response(http_response)
+    }
+
+    ///
+    /// Asynchronously fetches the key set and replaces the cached keys.
+    ///
+    pub async fn fetch_async<C, RE>(&self, http_client: C) -> Result<JsonWebKeySet, JwksError<RE>>
+    where
+        C: AsyncHttpClient<Error = RE>,
+        RE: Error + 'static,
+    {
+        let http_response = http_client
+            .call(self.jwks_request())
+            .await
+            .map_err(JwksError::Request)?;
+        self.jwks_response(http_response)
+    }
+
+    fn lookup(&self, kid: Option<&str>) -> KeyLookup {
+        let now = (*self.time_fn)();
+        let state = self.lock();
+        let cached = match *state {
+            Some(ref cached) if age(now, cached.fetched_at) < self.max_age => cached,
+            _ => return KeyLookup::Fetch,
+        };
+
+        let keys = matching_keys(&cached.jwks, kid);
+        if keys.is_empty()
+            && kid.is_some()
+            && age(now, cached.fetched_at) >= self.min_refresh_interval
+        {
+            KeyLookup::Fetch
+        } else {
+            KeyLookup::Keys(keys)
+        }
+    }
+
+    fn jwks_request(&self) -> HttpRequest {
+        let mut headers = HeaderMap::new();
+        headers.append(
+            ACCEPT,
+            HeaderValue::from_static("application/jwk-set+json, application/json"),
+        );
+        HttpRequest {
+            url: self.jwks_url.url().clone(),
+            method: Method::GET,
+            headers,
+            body: Vec::new(),
+        }
+    }
+
+    fn jwks_response<RE>(&self, http_response: HttpResponse) -> Result<JsonWebKeySet, JwksError<RE>>
+    where
+        RE: Error + 'static,
+    {
+        if http_response.status_code != StatusCode::OK {
+            return Err(JwksError::Response(
+                http_response.status_code,
+                http_response.body,
+                "unexpected HTTP status code".to_string(),
+            ));
+        }
+
+        let jwks = serde_json::from_slice::<JsonWebKeySet>(&http_response.body)
+            .map_err(|err| JwksError::Parse(err, http_response.body.clone()))?;
+        *self.lock() = Some(CachedJwks {
+            jwks: jwks.clone(),
+            fetched_at: (*self.time_fn)(),
+        });
+        Ok(jwks)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Option<CachedJwks>> {
+        self.state.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+impl Debug for JwksCache {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FormatterError> {
+        f.debug_struct("JwksCache")
+            .field("jwks_url", &self.jwks_url)
+            .field("max_age", &self.max_age)
+            .field("min_refresh_interval", &self.min_refresh_interval)
+            .finish()
+    }
+}
+
+fn matching_keys(jwks: &JsonWebKeySet, kid: Option<&str>) -> Vec<JsonWebKey> {
+    jwks.keys()
+        .iter()
+        .filter(|key| kid.is_none() || key.kid() == kid)
+        .cloned()
+        .collect()
+}
+
+fn age(now: DateTime<Utc>, fetched_at: DateTime<Utc>) -> Duration {
+    (now - fetched_at).to_std().unwrap_or_default()
+}
+
+///
+/// Error encountered while fetching a JSON Web Key Set.
+///
+#[derive(Debug, thiserror::Error)]
+pub enum JwksError<RE>
+where
+    RE: Error + 'static,
+{
+    ///
+    /// An error occurred while sending the request or receiving the response (e.g., network
+    /// connectivity failed).
+    ///
+    #[error("Request failed")]
+    Request(#[source] RE),
+    ///
+    /// Server returned an invalid response.
+    ///
+    #[error("Server returned invalid response: {}", _2)]
+    Response(StatusCode, Vec<u8>, String),
+    ///
+    /// Failed to parse server response.
+    ///
+    #[error("Failed to parse server response")]
+    Parse(#[source] serde_json::Error, Vec<u8>),
+}
+
+///
+/// JOSE header of a signed JWT.
+///
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct JwsHeader {
+    alg: JwsSigningAlgorithm,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    typ: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    kid: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    jwk: Option<JsonWebKey>,
+}
+impl JwsHeader {
+    ///
+    /// Creates a new header with the `alg` and `kid` values of the given signing key.
+    ///
+    pub fn new(key: &dyn JwsSigningKey) -> Self {
+        Self {
+            alg: key.signing_alg(),
+            typ: None,
+            kid: key.key_id().map(str::to_string),
+            jwk: None,
+        }
+    }
+
+    ///
+    /// Sets the media type (`typ` header) of the JWT (e.g., `JWT`).
+    ///
+    pub fn set_type(mut self, typ: String) -> Self {
+        self.typ = Some(typ);
+        self
+    }
+
+    ///
+    /// Sets the public key (`jwk` header) corresponding to the signing key.
+    ///
+    pub fn set_jwk(mut self, jwk: JsonWebKey) -> Self {
+        self.jwk = Some(jwk);
+        self
+    }
+
+    ///
+    /// The JWS algorithm (`alg` header).
+    ///
+    pub fn alg(&self) -> JwsSigningAlgorithm {
+        self.alg
+    }
+
+    ///
+    /// The media type (`typ` header), if any.
+    ///
+    pub fn typ(&self) -> Option<&str> {
+        self.typ.as_deref()
+    }
+
+    ///
+    /// The key ID (`kid` header), if any.
+    ///
+    pub fn kid(&self) -> Option<&str> {
+        self.kid.as_deref()
+    }
+
+    ///
+    /// The public key (`jwk` header), if any.
+    ///
+    pub fn jwk(&self) -> Option<&JsonWebKey> {
+        self.jwk.as_ref()
+    }
+}
+
+///
+/// Serializes and signs a JWT using the JWS Compact Serialization defined in
+/// [Section 7.1 of RFC 7515](https://tools.ietf.org/html/rfc7515#section-7.1).
+///
+pub fn encode<C>(
+    header: &JwsHeader,
+    claims: &C,
+    key: &dyn JwsSigningKey,
+) -> Result<String, SigningError>
+where
+    C: Serialize,
+{
+    if header.alg != key.signing_alg() {
+        return Err(SigningError::Other(format!(
+            "header algorithm {} does not match signing key algorithm {}",
+            header.alg.as_ref(),
+            key.signing_alg().as_ref()
+        )));
+    }
+
+    let signing_input = format!(
+        "{}.{}",
+        base64_url_encode(&serde_json::to_vec(header).map_err(SigningError::Serialize)?),
+        base64_url_encode(&serde_json::to_vec(claims).map_err(SigningError::Serialize)?)
+    );
+    let signature = key.sign(signing_input.as_bytes())?;
+
+    Ok(format!(
+        "{}.{}",
+        signing_input,
+        base64_url_encode(&signature)
+    ))
+}
+
+///
+/// JSON Web Encryption (JWE) content encryption algorithms supported by this crate, as defined
+/// in [Section 5.1 of RFC 7518](https://tools.ietf.org/html/rfc7518#section-5.1).
+///
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum JweContentEncryptionAlgorithm {
+    ///
+    /// AES GCM using a 128-bit key (`A128GCM`).
+    ///
+    #[serde(rename = "A128GCM")]
+    Aes128Gcm,
+    ///
+    /// AES GCM using a 256-bit key (`A256GCM`).
+    ///
+    #[serde(rename = "A256GCM")]
+    Aes256Gcm,
+}
+impl JweContentEncryptionAlgorithm {
+    fn aead_algorithm(self) -> &'static aead::Algorithm {
+        match self {
+            JweContentEncryptionAlgorithm::Aes128Gcm => &aead::AES_128_GCM,
+            JweContentEncryptionAlgorithm::Aes256Gcm => &aead::AES_256_GCM,
+        }
+    }
+
+    fn key_len(self) -> usize {
+        self.aead_algorithm().key_len()
+    }
+}
+impl AsRef<str> for JweContentEncryptionAlgorithm {
+    fn as_ref(&self) -> &str {
+        match self {
+            JweContentEncryptionAlgorithm::Aes128Gcm => "A128GCM",
+            JweContentEncryptionAlgorithm::Aes256Gcm => "A256GCM",
+        }
+    }
+}
+
+///
+/// A key used for encrypting JSON Web Tokens (e.g., request objects).
+///
+/// This trait is implemented by the key types in this module, and may be implemented by
+/// callers to support other algorithms.
+///
+pub trait JweEncryptionKey: Debug + Send + Sync {
+    ///
+    /// Encrypts the given plaintext, returning the JWE Compact Serialization defined in
+    /// [Section 7.1 of RFC 7516](https://tools.ietf.org/html/rfc7516#section-7.1).
+    ///
+    /// The `content_type` is included as the `cty` header (e.g., `JWT` for nested JWTs).
+    ///
+    fn encrypt(&self, plaintext: &[u8], content_type: Option<&str>)
+        -> Result<String, SigningError>;
+}
+
+///
+/// Recipient's elliptic curve public key for encrypting JWTs using Elliptic Curve
+/// Diffie-Hellman Ephemeral Static key agreement (`ECDH-ES`) with the P-256 curve, as defined in
+/// [Section 4.6 of RFC 7518](https://tools.ietf.org/html/rfc7518#section-4.6).
+///
+/// A new ephemeral key is generated for every encrypted JWT, and the agreed-upon key is used
+/// directly as the content encryption key.
+///
+#[derive(Clone, Debug)]
+pub struct EcdhEsEncryptionKey {
+    public_key: Vec<u8>,
+    key_id: Option<String>,
+    enc: JweContentEncryptionAlgorithm,
+}
+impl EcdhEsEncryptionKey {
+    ///
+    /// Creates an encryption key from the recipient's P-256 public key in JSON Web Key format
+    /// (e.g., from the authorization server's JWKS document).
+    ///
+    pub fn from_jwk(
+        jwk: &JsonWebKey,
+        enc: JweContentEncryptionAlgorithm,
+    ) -> Result<Self, SigningError> {
+        if jwk.kty != "EC" || jwk.crv.as_deref() != Some("P-256") {
+            return Err(SigningError::InvalidKey(
+                "only P-256 elliptic curve keys are supported".to_string(),
+            ));
+        }
+        let decode_coordinate = |coordinate: Option<&String>| {
+            coordinate
+                .and_then(|coordinate| {
+                    base64::decode_config(coordinate, base64::URL_SAFE_NO_PAD).ok()
+                })
+                .filter(|coordinate| coordinate.len() == 32)
+                .ok_or_else(|| SigningError::InvalidKey("invalid EC coordinates".to_string()))
+        };
+
+        // The public key is an uncompressed point (0x04 followed by the X and Y coordinates).
+        let mut public_key = vec![0x04];
+        public_key.extend(decode_coordinate(jwk.x.as_ref())?);
+        public_key.extend(decode_coordinate(jwk.y.as_ref())?);
+        Ok(Self {
+            public_key,
+            key_id: jwk.kid.clone(),
+            enc,
+        })
+    }
+}
+impl JweEncryptionKey for EcdhEsEncryptionKey {
+    fn encrypt(
+        &self,
+        plaintext: &[u8],
+        content_type: Option<&str>,
+    ) -> Result<String, SigningError> {
+        let rng = SystemRandom::new();
+        let ephemeral_key = agreement::EphemeralPrivateKey::generate(&agreement::ECDH_P256, &rng)
+            .map_err(|_| SigningError::Crypto)?;
+        let ephemeral_public_key = ephemeral_key
+            .compute_public_key()
+            .map_err(|_| SigningError::Crypto)?;
+        let point = &ephemeral_public_key.as_ref()[1..];
+        let (x, y) = point.split_at(point.len() / 2);
+
+        let cek = agreement::agree_ephemeral(
+            ephemeral_key,
+            &agreement::UnparsedPublicKey::new(&agreement::ECDH_P256, &self.public_key),
+            SigningError::InvalidKey("invalid EC public key".to_string()),
+            |shared_secret| Ok(concat_kdf(shared_secret, self.enc, b"", b"")),
+        )?;
+
+        let mut header = serde_json::json!({
+            "alg": "ECDH-ES",
+            "enc": self.enc,
+            "epk": {
+                "kty": "EC",
+                "crv": "P-256",
+                "x": base64_url_encode(x),
+                "y": base64_url_encode(y),
+            },
+        });
+        if let Some(ref key_id) = self.key_id {
+            header["kid"] = serde_json::Value::String(key_id.clone());
+        }
+        if let Some(content_type) = content_type {
+            header["cty"] = serde_json::Value::String(content_type.to_string());
+        }
+        encrypt_compact(&header, &cek, self.enc, plaintext)
+    }
+}
+
+///
+/// Symmetric key used directly as the content encryption key (`dir`), as defined in
+/// [Section 4.5 of RFC 7518](https://tools.ietf.org/html/rfc7518#section-4.5).
+///
+pub struct DirectEncryptionKey {
+    key: Vec<u8>,
+    key_id: Option<String>,
+    enc: JweContentEncryptionAlgorithm,
+}
+impl DirectEncryptionKey {
+    ///
+    /// Creates a new key, which must be 16 bytes long for `A128GCM` or 32 bytes long for
+    /// `A256GCM`.
+    ///
+    pub fn new(key: &[u8], enc: JweContentEncryptionAlgorithm) -> Result<Self, SigningError> {
+        if key.len() != enc.key_len() {
+            return Err(SigningError::InvalidKey(format!(
+                "{} requires a {}-byte key",
+                enc.as_ref(),
+                enc.key_len()
+            )));
+        }
+        Ok(Self {
+            key: key.to_vec(),
+            key_id: None,
+            enc,
+        })
+    }
+
+    ///
+    /// Creates a new key from the client secret, using the left-most bits of its SHA-256 hash
+    /// as described in
+    /// [Section 10.2 of OpenID Connect Core](https://openid.net/specs/openid-connect-core-1_0.html#Encryption).
+    ///
+    pub fn from_client_secret(
+        client_secret: &ClientSecret,
+        enc: JweContentEncryptionAlgorithm,
+    ) -> Self {
+        let hash = Sha256::digest(client_secret.secret().as_bytes());
+        Self {
+            key: hash[..enc.key_len()].to_vec(),
+            key_id: None,
+            enc,
+        }
+    }
+
+    ///
+    /// Sets the key ID (`kid` header) included in encrypted JWTs.
+    ///
+    pub fn set_key_id(mut self, key_id: String) -> Self {
+        self.key_id = Some(key_id);
+        self
+    }
+}
+impl Debug for DirectEncryptionKey {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FormatterError> {
+        f.debug_struct("DirectEncryptionKey")
+            .field("key_id", &self.key_id)
+            .field("enc", &self.enc)
+            .finish()
+    }
+}
+impl JweEncryptionKey for DirectEncryptionKey {
+    fn encrypt(
+        &self,
+        plaintext: &[u8],
+        content_type: Option<&str>,
+    ) -> Result<String, SigningError> {
+        let mut header = serde_json::json!({
+            "alg": "dir",
+            "enc": self.enc,
+        });
+        if let Some(ref key_id) = self.key_id {
+            header["kid"] = serde_json::Value::String(key_id.clone());
+        }
+        if let Some(content_type) = content_type {
+            header["cty"] = serde_json::Value::String(content_type.to_string());
+        }
+        encrypt_compact(&header, &self.key, self.enc, plaintext)
+    }
+}
+
+///
+/// Encrypts the plaintext using the given content encryption key, returning the JWE Compact
+/// Serialization with an empty encrypted key (as used by the `dir` and `ECDH-ES` algorithms).
+///
+fn encrypt_compact(
+    header: &serde_json::Value,
+    cek: &[u8],
+    enc: JweContentEncryptionAlgorithm,
+    plaintext: &[u8],
+) -> Result<String, SigningError> {
+    let encoded_header =
+        base64_url_encode(&serde_json::to_vec(header).map_err(SigningError::Serialize)?);
+
+    let mut iv = [0u8; aead::NONCE_LEN];
+    SystemRandom::new()
+        .fill(&mut iv)
+        .map_err(|_| SigningError::Crypto)?;
+    let key = aead::LessSafeKey::new(
+        aead::UnboundKey::new(enc.aead_algorithm(), cek).map_err(|_| SigningError::Crypto)?,
+    );
+    let mut ciphertext = plaintext.to_vec();
+    let tag = key
+        .seal_in_place_separate_tag(
+            aead::Nonce::assume_unique_for_key(iv),
+            aead::Aad::from(encoded_header.as_bytes()),
+            &mut ciphertext,
+        )
+        .map_err(|_| SigningError::Crypto)?;
+
+    Ok(format!(
+        "{}..{}.{}.{}",
+        encoded_header,
+        base64_url_encode(&iv),
+        base64_url_encode(&ciphertext),
+        base64_url_encode(tag.as_ref())
+    ))
+}
+
+///
+/// Derives a content encryption key from an ECDH shared secret using the Concat KDF, as
+/// described in [Section 4.6.2 of RFC 7518](https://tools.ietf.org/html/rfc7518#section-4.6.2).
+///
+pub(crate) fn concat_kdf(
+    shared_secret: &[u8],
+    enc: JweContentEncryptionAlgorithm,
+    apu: &[u8],
+    apv: &[u8],
+) -> Vec<u8> {
+    let key_len = enc.key_len();
+    let mut key = Vec::with_capacity(key_len);
+    let mut counter = 1u32;
+    while key.len() < key_len {
+        let mut hasher = Sha256::new();
+        hasher.update(counter.to_be_bytes());
+        hasher.update(shared_secret);
+        for info in &[enc.as_ref().as_bytes(), apu, apv] {
+            hasher.update((info.len() as u32).to_be_bytes());
+            hasher.update(info);
+        }
+        hasher.update(((key_len * 8) as u32).to_be_bytes());
+        key.extend_from_slice(&hasher.finalize());
+        counter += 1;
+    }
+    key.truncate(key_len);
+    key
+}
+
+///
+/// Claims of a JWT used for client authentication, as defined in
+/// [Section 3 of RFC 7523](https://tools.ietf.org/html/rfc7523#section-3).
+///
+#[derive(Debug, Serialize)]
+struct ClientAssertionClaims<'a> {
+    iss: &'a str,
+    sub: &'a str,
+    aud: &'a str,
+    jti: String,
+    exp: i64,
+    iat: i64,
+}
+
+///
+/// Builds a signed client assertion for the `client_secret_jwt` and `private_key_jwt` client
+/// authentication methods.
+///
+pub(crate) fn client_assertion(
+    client_id: &ClientId,
+    audience: &str,
+    key: &dyn JwsSigningKey,
+) -> Result<String, SigningError> {
+    let now = Utc::now().timestamp();
+    let claims = ClientAssertionClaims {
+        iss: client_id,
+        sub: client_id,
+        aud: audience,
+        jti: new_jti(),
+        exp: now + CLIENT_ASSERTION_LIFETIME.as_secs() as i64,
+        iat: now,
+    };
+    encode(
+        &JwsHeader::new(key).set_type("JWT".to_string()),
+        &claims,
+        key,
+    )
+}
+
+///
+/// Claims of a JWT used as an authorization grant, as defined in
+/// [Section 3 of RFC 7523](https://tools.ietf.org/html/rfc7523#section-3).
+///
+#[derive(Debug, Serialize)]
+struct JwtBearerAssertionClaims<'a> {
+    iss: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    sub: Option<&'a str>,
+    aud: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    scope: Option<String>,
+    jti: String,
+    exp: i64,
+    iat: i64,
+}
+
+///
+/// Builder for signed JWT assertions used with the JWT bearer authorization grant
+/// (see `Client::exchange_jwt_bearer`).
+///
+/// The assertion contains the `iss`, `aud`, `jti`, `iat` and `exp` claims defined in
+/// [Section 3 of RFC 7523](https://tools.ietf.org/html/rfc7523#section-3), as well as the
+/// optional `sub` claim and a space-delimited `scope` claim, which some authorization servers
+/// (e.g., Google) require in place of the `scope` request parameter.
+///
+#[derive(Debug)]
+pub struct JwtBearerAssertionBuilder<'a> {
+    key: &'a dyn JwsSigningKey,
+    issuer: String,
+    audience: String,
+    subject: Option<String>,
+    scopes: Vec<Scope>,
+    lifetime: Duration,
+}
+impl<'a> JwtBearerAssertionBuilder<'a> {
+    ///
+    /// Creates a new assertion builder.
+    ///
+    /// # Arguments
+    ///
+    /// * `issuer` - Identifier of the party issuing the assertion (e.g., a service account's
+    ///   email address).
+    /// * `audience` - Identifier of the authorization server that the assertion is intended for,
+    ///   typically the URL of its token endpoint.
+    /// * `key` - Key used to sign the assertion.
+    ///
+    pub fn new(issuer: String, audience: String, key: &'a dyn JwsSigningKey) -> Self {
+        Self {
+            key,
+            issuer,
+            audience,
+            subject: None,
+            scopes: Vec::new(),
+            lifetime: JWT_BEARER_ASSERTION_LIFETIME,
+        }
+    }
+
+    ///
+    /// Sets the subject (`sub` claim) of the assertion, such as the user being impersonated
+    /// by a service account with domain-wide delegation.
+    ///
+    pub fn set_subject(mut self, subject: String) -> Self {
+        self.subject = Some(subject);
+        self
+    }
+
+    ///
+    /// Appends a new scope to the `scope` claim of the assertion.
+    ///
+    pub fn add_scope(mut self, scope: Scope) -> Self {
+        self.scopes.push(scope);
+        self
+    }
+
+    ///
+    /// Sets the lifetime of the assertion. Defaults to one hour.
+    ///
+    pub fn set_lifetime(mut self, lifetime: Duration) -> Self {
+        self.lifetime = lifetime;
+        self
+    }
+
+    ///
+    /// Builds and signs the assertion.
+    ///
+    pub fn sign(self) -> Result<JwtBearerAssertion, SigningError> {
+        let now = Utc::now().timestamp();
+        let claims = JwtBearerAssertionClaims {
+            iss: &self.issuer,
+            sub: self.subject.as_deref(),
+            aud: &self.audience,
+            scope: if self.scopes.is_empty() {
+                None
+            } else {
+                Some(
+                    self.scopes
+                        .iter()
+                        .map(|scope| scope.as_str())
+                        .collect::<Vec<_>>()
+                        .join(" "),
+                )
+            },
+            jti: new_jti(),
+            exp: now + self.lifetime.as_secs() as i64,
+            iat: now,
+        };
+        encode(
+            &JwsHeader::new(self.key).set_type("JWT".to_string()),
+            &claims,
+            self.key,
+        )
+        .map(JwtBearerAssertion::new)
+    }
+}
+
+#[derive(Deserialize)]
+struct ServiceAccountKeyFile {
+    client_email: String,
+    private_key: String,
+    #[serde(default)]
+    private_key_id: Option<String>,
+    token_uri: String,
+}
+
+///
+/// Service account credentials in the JSON key file format used by providers such as Google
+/// Cloud.
+///
+/// The key file must contain the `client_email`, `private_key` (PEM-encoded RSA key) and
+/// `token_uri` fields. The optional `private_key_id` field is used as the `kid` header of
+/// signed assertions.
+///
+#[derive(Debug)]
+pub struct ServiceAccountKey {
+    client_email: String,
+    token_uri: TokenUrl,
+    signing_key: RsaSigningKey,
+}
+impl ServiceAccountKey {
+    ///
+    /// Parses a service account JSON key file.
+    ///
+    pub fn from_json(json: &str) -> Result<Self, SigningError> {
+        let key_file: ServiceAccountKeyFile = serde_json::from_str(json).map_err(|err| {
+            SigningError::InvalidKey(format!("invalid service account key file: {}", err))
+        })?;
+        let token_uri = TokenUrl::new(key_file.token_uri)
+            .map_err(|err| SigningError::InvalidKey(format!("invalid token_uri: {}", err)))?;
+        let signing_key = RsaSigningKey::from_pem(&key_file.private_key)?;
+        Ok(Self {
+            client_email: key_file.client_email,
+            token_uri,
+            signing_key: match key_file.private_key_id {
+                Some(key_id) => signing_key.set_key_id(key_id),
+                None => signing_key,
+            },
+        })
+    }
+
+    ///
+    /// The service account's email address, used as the assertion issuer.
+    ///
+    pub fn client_email(&self) -> &str {
+        &self.client_email
+    }
+
+    ///
+    /// URL of the token endpoint, used as the assertion audience.
+    ///
+    pub fn token_uri(&self) -> &TokenUrl {
+        &self.token_uri
+    }
+
+    ///
+    /// The service account's private key.
+    ///
+    pub fn signing_key(&self) -> &RsaSigningKey {
+        &self.signing_key
+    }
+
+    ///
+    /// Returns an assertion builder with the issuer, audience and signing key of this service
+    /// account.
+    ///
+    pub fn assertion_builder(&self) -> JwtBearerAssertionBuilder<'_> {
+        JwtBearerAssertionBuilder::new(
+            self.client_email.clone(),
+            self.token_uri.to_string(),
+            &self.signing_key,
+        )
+    }
+}
+
+///
+/// Generates a new random, base64-encoded 128-bit JWT ID (`jti` claim).
+///
+pub(crate) fn new_jti() -> String {
+    let random_bytes: Vec<u8> = (0..16).map(|_| thread_rng().gen::<u8>()).collect();
+    base64_url_encode(&random_bytes)
+}
+
+pub(crate) fn base64_url_encode(input: &[u8]) -> String {
+    base64::encode_config(input, base64::URL_SAFE_NO_PAD)
+}
+
+///
+/// Decodes a PEM document, returning its label (e.g., `PRIVATE KEY`) and DER contents.
+///
+fn decode_pem(pem: &str) -> Result<(String, Vec<u8>), SigningError> {
+    let pem = pem.trim();
+    let label = pem
+        .lines()
+        .next()
+        .and_then(|line| line.trim().strip_prefix("-----BEGIN "))
+        .and_then(|line| line.strip_suffix("-----"))
+        .ok_or_else(|| SigningError::InvalidKey("missing PEM header".to_string()))?
+        .to_string();
+    let footer = format!("-----END {}-----", label);
+
+    let body = pem
+        .lines()
+        .skip(1)
+        .map(str::trim)
+        .take_while(|line| *line != footer)
+        .collect::<String>();
+    if !pem.ends_with(&footer) {
+        return Err(SigningError::InvalidKey("missing PEM footer".to_string()));
+    }
+
+    base64::decode(&body)
+        .map(|der| (label, der))
+        .map_err(|err| SigningError::InvalidKey(format!("invalid PEM encoding: {}", err)))
+}
@@ -0,0 +1,515 @@
+use std::error::Error;
+use std::fmt::Error as FormatterError;
+use std::fmt::{Debug, Formatter};
+use std::marker::PhantomData;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use ring::signature;
+use serde::Deserialize;
+
+use super::jwt::{JsonWebKey, JwksCache, JwksError};
+use super::resourceserver::BearerTokenError;
+use super::{
+    AccessToken, AsyncHttpClient, Scope, SyncHttpClient, TokenInspectionResponse, TokenType,
+};
+
+///
+/// Media type of JWT access tokens (`typ` header), as defined in
+/// [Section 2.1 of RFC 9068](https://tools.ietf.org/html/rfc9068#section-2.1).
+///
+pub const JWT_ACCESS_TOKEN_TYPE: &str = "at+jwt";
+
+///
+/// Default clock skew tolerated when checking the `exp` and `nbf` claims of access tokens.
+///
+pub const DEFAULT_LEEWAY: Duration = Duration::from_secs(60);
+
+#[derive(Deserialize)]
+struct JwtHeader {
+    alg: String,
+    #[serde(default)]
+    kid: Option<String>,
+    #[serde(default)]
+    typ: Option<String>,
+}
+
+struct DecodedJwt {
+    header: JwtHeader,
+    claims: serde_json::Map<String, serde_json::Value>,
+    signing_input: String,
+    signature: Vec<u8>,
+}
+
+///
+/// Validates JWT access tokens locally, as described in
+/// [Section 4 of RFC 9068](https://tools.ietf.org/html/rfc9068#section-4).
+///
+/// The validator checks that:
+///
+/// * the `typ` header is `at+jwt` (or `application/at+jwt`);
+/// * the token is signed using an asymmetric algorithm (`RS256`, `RS384`, `RS512`, `PS256`,
+///   `PS384`, `PS512`, `ES256` or `ES384`) by one of the keys published by the authorization
+///   server, which are fetched and cached by a `jwt::JwksCache`;
+/// * the `iss` claim matches the expected issuer and the `aud` claim includes the expected
+///   audience;
+/// * the current time is before `exp` and not before `nbf` (allowing for `set_leeway`);
+/// * the `sub`, `client_id`, `iat` and `jti` claims are present and, if configured, the
+///   `client_id` is one of the allowed clients;
+/// * the token has been granted all of the required scopes.
+///
+/// The claims of valid tokens are returned as a `TokenInspectionResponse` (e.g.,
+/// `basic::BasicTokenInspectionResponse`) with `active` set to `true`, so that resource servers
+/// can switch between token introspection and local validation without other changes. Claims
+/// not defined by [RFC 7662](https://tools.ietf.org/html/rfc7662) are deserialized into the
+/// response's extra fields.
+///
+pub struct JwtAccessTokenValidator<TT, TIR>
+where
+    TT: TokenType,
+    TIR: TokenInspectionResponse<TT>,
+{
+    issuer: String,
+    audience: String,
+    jwks: JwksCache,
+    allowed_client_ids: Vec<String>,
+    leeway: Duration,
+    required_scopes: Vec<Scope>,
+    time_fn: Arc<dyn Fn() -> DateTime<Utc> + Send + Sync>,
+    _phantom: PhantomData<(TT, TIR)>,
+}
+impl<TT, TIR> JwtAccessTokenValidator<TT, TIR>
+where
+    TT: TokenType,
+    TIR: TokenInspectionResponse<TT>,
+{
+    ///
+    /// Creates a new validator.
+    ///
+    /// # Arguments
+    ///
+    /// * `issuer` - Expected `iss` claim, which is the issuer identifier of the authorization
+    ///   server.
+    /// * `audience` - Expected `aud` claim, which identifies this resource server.
+    /// * `jwks` - Cache of the authorization server's signing keys.
+    ///
+    pub fn new(issuer: String, audience: String, jwks: JwksCache) -> Self {
+        Self {
+            issuer,
+            audience,
+            jwks,
+            allowed_client_ids: Vec::new(),
+            leeway: DEFAULT_LEEWAY,
+            required_scopes: Vec::new(),
+            time_fn: Arc::new(Utc::now),
+            _phantom: PhantomData,
+        }
+    }
+
+    ///
+    /// Appends a client that is allowed to use this resource server. If no clients are
+    /// allowed explicitly, tokens issued to any client are accepted.
+    ///
+    pub fn add_allowed_client_id(mut self, client_id: String) -> Self {
+        self.allowed_client_ids.push(client_id);
+        self
+    }
+
+    ///
+    /// Sets the clock skew tolerated when checking the `exp` and `nbf` claims. Defaults to
+    /// `DEFAULT_LEEWAY`.
+    ///
+    pub fn set_leeway(mut self, leeway: Duration) -> Self {
+        self.leeway = leeway;
+        self
+    }
+
+    ///
+    /// Appends a scope that must have been granted to the token.
+    ///
+    pub fn add_required_scope(mut self, scope: Scope) -> Self {
+        self.required_scopes.push(scope);
+        self
+    }
+
+    ///
+    /// Specifies a function for returning the current time.
+    ///
+    /// This function is used while checking the `exp` and `nbf` claims.
+    ///
+    pub fn set_time_fn<T>(mut self, time_fn: T) -> Self
+    where
+        T: Fn() -> DateTime<Utc> + Send + Sync + 'static,
+    {
+        self.time_fn = Arc::new(time_fn);
+        self
+    }
+
+    ///
+    /// The cache of the authorization server's signing keys.
+    ///
+    pub fn jwks(&self) -> &JwksCache {
+        &self.jwks
+    }
+
+    ///
+    /// Synchronously validates the given access token, fetching the authorization server's
+    /// signing keys if needed.
+    ///
+    pub fn validate<F, RE>(
+        &self,
+        token: &AccessToken,
+        http_client: F,
+    ) -> Result<TIR, JwtAccessTokenError<RE>>
+    where
+        F: SyncHttpClient<Error = RE>,
+        RE: Error + 'static,
+    {
+        let jwt = self.decode(token)?;
+        let keys = self
+            .jwks
+            .find_keys(jwt.header.kid.as_deref(), http_client)
+            .map_err(JwtAccessTokenError::Jwks)?;
+        self.verify(jwt, &keys)
+    }
+
+    ///
+    /// Asynchronously validates the given access token, fetching the authorization server's
+    /// signing keys if needed.
+    ///
+    pub async fn validate_async<C, RE>(
+        &self,
+        token: &AccessToken,
+        http_client: C,
+    ) -> Result<TIR, JwtAccessTokenError<RE>>
+    where
+        C: AsyncHttpClient<Error = RE>,
+        RE: Error + 'static,
+    {
+        let jwt = self.decode(token)?;
+        let keys = self
+            .jwks
+            .find_keys_async(jwt.header.kid.as_deref(), http_client)
+            .await
+            .map_err(JwtAccessTokenError::Jwks)?;
+        self.verify(jwt, &keys)
+    }
+
+    fn decode<RE>(&self, token: &AccessToken) -> Result<DecodedJwt, JwtAccessTokenError<RE>>
+    where
+        RE: Error + 'static,
+    {
+        let parts = token.secret().split('.').collect::<Vec<_>>();
+        if parts.len() != 3 {
+            return Err(JwtAccessTokenError::Malformed(
+                "expected a signed JWT with three parts".to_string(),
+            ));
+        }
+        let decode_part = |part: &str, name: &str| {
+            base64::decode_config(part, base64::URL_SAFE_NO_PAD)
+                .map_err(|_| JwtAccessTokenError::Malformed(format!("invalid {} encoding", name)))
+        };
+
+        let header: JwtHeader = serde_json::from_slice(&decode_part(parts[0], "header")?)
+            .map_err(|err| JwtAccessTokenError::Malformed(format!("invalid header: {}", err)))?;
+        let typ_valid = header
+            .typ
+            .as_deref()
+            .map(|typ| {
+                typ.eq_ignore_ascii_case(JWT_ACCESS_TOKEN_TYPE)
+                    || typ.eq_ignore_ascii_case(&format!("application/{}", JWT_ACCESS_TOKEN_TYPE))
+            })
+            .unwrap_or(false);
+        if !typ_valid {
+            return Err(JwtAccessTokenError::InvalidType(header.typ));
+        }
+        if verification_algorithm(&header.alg).is_none() {
+            return Err(JwtAccessTokenError::UnsupportedAlgorithm(header.alg));
+        }
+
+        let claims = serde_json::from_slice(&decode_part(parts[1], "claims")?)
+            .map_err(|err| JwtAccessTokenError::Malformed(format!("invalid claims: {}", err)))?;
+        Ok(DecodedJwt {
+            header,
+            claims,
+            signing_input: format!("{}.{}", parts[0], parts[1]),
+            signature: decode_part(parts[2], "signature")?,
+        })
+    }
+
+    fn verify<RE>(
+        &self,
+        jwt: DecodedJwt,
+        keys: &[JsonWebKey],
+    ) -> Result<TIR, JwtAccessTokenError<RE>>
+    where
+        RE: Error + 'static,
+    {
+        let candidates = keys
+            .iter()
+            .filter(|key| key_matches_algorithm(key, &jwt.header.alg))
+            .collect::<Vec<_>>();
+        if candidates.is_empty() {
+            return Err(JwtAccessTokenError::UnknownKey(jwt.header.kid));
+        }
+        if !candidates.iter().any(|key| {
+            verify_signature(
+                key,
+                &jwt.header.alg,
+                jwt.signing_input.as_bytes(),
+                &jwt.signature,
+            )
+        }) {
+            return Err(JwtAccessTokenError::InvalidSignature);
+        }
+
+        for claim in &["iss", "exp", "aud", "sub", "client_id", "iat", "jti"] {
+            if jwt
+                .claims
+                .get(*claim)
+                .map(|value| value.is_null())
+                .unwrap_or(true)
+            {
+                return Err(JwtAccessTokenError::MissingClaim(claim));
+            }
+        }
+
+        // Valid JWT access tokens are equivalent to an active introspection response.
+        let mut claims = jwt.claims;
+        claims.insert("active".to_string(), serde_json::Value::Bool(true));
+        let token_info: TIR = serde_json::from_value(serde_json::Value::Object(claims))
+            .map_err(|err| JwtAccessTokenError::Malformed(format!("invalid claims: {}", err)))?;
+        self.validate_token_info(&token_info)?;
+        Ok(token_info)
+    }
+
+    fn validate_token_info<RE>(&self, token_info: &TIR) -> Result<(), JwtAccessTokenError<RE>>
+    where
+        RE: Error + 'static,
+    {
+        if token_info.iss() != Some(self.issuer.as_str()) {
+            return Err(JwtAccessTokenError::InvalidIssuer(
+                token_info.iss().map(str::to_string),
+            ));
+        }
+        if !token_info
+            .aud()
+            .map(|aud| aud.contains(&self.audience))
+            .unwrap_or(false)
+        {
+            return Err(JwtAccessTokenError::InvalidAudience);
+        }
+
+        let now = (*self.time_fn)();
+        let leeway =
+            chrono::Duration::from_std(self.leeway).unwrap_or_else(|_| chrono::Duration::zero());
+        match token_info.exp() {
+            Some(exp) if now < exp + leeway => {}
+            _ => return Err(JwtAccessTokenError::Expired),
+        }
+        if let Some(nbf) = token_info.nbf() {
+            if now < nbf - leeway {
+                return Err(JwtAccessTokenError::NotYetValid);
+            }
+        }
+
+        if !self.allowed_client_ids.is_empty()
+            && !token_info
+                .client_id()
+                .map(|client_id| {
+                    self.allowed_client_ids
+                        .iter()
+                        .any(|allowed| allowed == client_id.as_str())
+                })
+                .unwrap_or(false)
+        {
+            return Err(JwtAccessTokenError::InvalidClientId);
+        }
+
+        let granted_scopes = token_info.scopes();
+        if !self.required_scopes.iter().all(|required_scope| {
+            granted_scopes
+                .map(|scopes| scopes.contains(required_scope))
+                .unwrap_or(false)
+        }) {
+            return Err(JwtAccessTokenError::InsufficientScope(
+                self.required_scopes.clone(),
+            ));
+        }
+
+        Ok(())
+    }
+}
+impl<TT, TIR> Debug for JwtAccessTokenValidator<TT, TIR>
+where
+    TT: TokenType,
+    TIR: TokenInspectionResponse<TT>,
+{
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FormatterError> {
+        f.debug_struct("JwtAccessTokenValidator")
+            .field("issuer", &self.issuer)
+            .field("audience", &self.audience)
+            .field("jwks", &self.jwks)
+            .field("allowed_client_ids", &self.allowed_client_ids)
+            .field("leeway", &self.leeway)
+            .field("required_scopes", &self.required_scopes)
+            .finish()
+    }
+}
+
+///
+/// Error encountered while validating a JWT access token.
+///
+#[derive(Debug, thiserror::Error)]
+pub enum JwtAccessTokenError<RE>
+where
+    RE: Error + 'static,
+{
+    ///
+    /// The token is not a well-formed JWT.
+    ///
+    #[error("Malformed token: {}", _0)]
+    Malformed(String),
+    ///
+    /// The `typ` header is not `at+jwt`.
+    ///
+    #[error("Invalid token type: {:?}", _0)]
+    InvalidType(Option<String>),
+    ///
+    /// The token is signed using an unsupported (or symmetric) algorithm.
+    ///
+    #[error("Unsupported signing algorithm: {}", _0)]
+    UnsupportedAlgorithm(String),
+    ///
+    /// None of the authorization server's keys match the `kid` header and algorithm.
+    ///
+    #[error("Unknown signing key: {:?}", _0)]
+    UnknownKey(Option<String>),
+    ///
+    /// The signature is invalid.
+    ///
+    #[error("Invalid signature")]
+    InvalidSignature,
+    ///
+    /// A claim required by RFC 9068 is missing.
+    ///
+    #[error("Missing claim: {}", _0)]
+    MissingClaim(&'static str),
+    ///
+    /// The `iss` claim does not match the expected issuer.
+    ///
+    #[error("Invalid issuer: {:?}", _0)]
+    InvalidIssuer(Option<String>),
+    ///
+    /// The `aud` claim does not include the expected audience.
+    ///
+    #[error("Invalid audience")]
+    InvalidAudience,
+    ///
+    /// The token has expired.
+    ///
+    #[error("Token expired")]
+    Expired,
+    ///
+    /// The token is not valid yet (`nbf` claim).
+    ///
+    #[error("Token not yet valid")]
+    NotYetValid,
+    ///
+    /// The token was issued to a client that is not allowed.
+    ///
+    #[error("Client not allowed")]
+    InvalidClientId,
+    ///
+    /// The token was not granted the given required scopes.
+    ///
+    #[error("Insufficient scope")]
+    InsufficientScope(Vec<Scope>),
+    ///
+    /// Failed to fetch the authorization server's signing keys.
+    ///
+    #[error("Failed to fetch signing keys")]
+    Jwks(#[source] JwksError<RE>),
+}
+
+///
+/// Converts validation errors into bearer token rejections, allowing a
+/// `JwtAccessTokenValidator` to be used as the verifier of a
+/// `resourceserver::BearerTokenValidator`.
+///
+impl<RE> From<JwtAccessTokenError<RE>> for BearerTokenError<JwksError<RE>>
+where
+    RE: Error + 'static,
+{
+    fn from(err: JwtAccessTokenError<RE>) -> Self {
+        match err {
+            JwtAccessTokenError::Jwks(err) => BearerTokenError::Verifier(err),
+            JwtAccessTokenError::InsufficientScope(scopes) => {
+                BearerTokenError::InsufficientScope(scopes)
+            }
+            err => BearerTokenError::InvalidToken(err.to_string()),
+        }
+    }
+}
+
+enum VerificationAlgorithm {
+    Rsa(&'static signature::RsaParameters),
+    Ecdsa(&'static signature::EcdsaVerificationAlgorithm, &'static str),
+}
+
+fn verification_algorithm(alg: &str) -> Option<VerificationAlgorithm> {
+    Some(match alg {
+        "RS256" => VerificationAlgorithm::Rsa(&signature::RSA_PKCS1_2048_8192_SHA256),
+        "RS384" => VerificationAlgorithm::Rsa(&signature::RSA_PKCS1_2048_8192_SHA384),
+        "RS512" => VerificationAlgorithm::Rsa(&signature::RSA_PKCS1_2048_8192_SHA512),
+        "PS256" => VerificationAlgorithm::Rsa(&signature::RSA_PSS_2048_8192_SHA256),
+        "PS384" => VerificationAlgorithm::Rsa(&signature::RSA_PSS_2048_8192_SHA384),
+        "PS512" => VerificationAlgorithm::Rsa(&signature::RSA_PSS_2048_8192_SHA512),
+        "ES256" => VerificationAlgorithm::Ecdsa(&signature::ECDSA_P256_SHA256_FIXED, "P-256"),
+        "ES384" => VerificationAlgorithm::Ecdsa(&signature::ECDSA_P384_SHA384_FIXED, "P-384"),
+        _ => return None,
+    })
+}
+
+fn key_matches_algorithm(key: &JsonWebKey, alg: &str) -> bool {
+    if key
+        .key_use()
+        .map(|key_use| key_use != "sig")
+        .unwrap_or(false)
+        || key.alg().map(|key_alg| key_alg != alg).unwrap_or(false)
+    {
+        return false;
+    }
+    match verification_algorithm(alg) {
+        Some(VerificationAlgorithm::Rsa(_)) => key.kty() == "RSA",
+        Some(VerificationAlgorithm::Ecdsa(_, crv)) => key.kty() == "EC" && key.crv() == Some(crv),
+        None => false,
+    }
+}
+
+fn verify_signature(key: &JsonWebKey, alg: &str, message: &[u8], signature: &[u8]) -> bool {
+    let decode = |value: Option<&str>| {
+        value.and_then(|value| base64::decode_config(value, base64::URL_SAFE_NO_PAD).ok())
+    };
+    match verification_algorithm(alg) {
+        Some(VerificationAlgorithm::Rsa(parameters)) => match (decode(key.n()), decode(key.e())) {
+            (Some(n), Some(e)) => signature::RsaPublicKeyComponents { n, e }
+                .verify(parameters, message, signature)
+                .is_ok(),
+            _ => false,
+        },
+        Some(VerificationAlgorithm::Ecdsa(algorithm, _)) => {
+            match (decode(key.x()), decode(key.y())) {
+                // Uncompressed point (0x04 followed by the X and Y coordinates).
+                (Some(x), Some(y)) => {
+                    signature::UnparsedPublicKey::new(algorithm, [&[0x04][..], &x, &y].concat())
+                        .verify(message, signature)
+                        .is_ok()
+                }
+                _ => false,
+            }
+        }
+        None => false,
+    }
+}
@@ -8,10 +8,24 @@
 //! * [Getting started: Authorization Code Grant w/ PKCE](#getting-started-authorization-code-grant-w-pkce)
 //!   * [Example: Synchronous (blocking) API](#example-synchronous-blocking-api)
 //!   * [Example: Async/Await API](#example-asyncawait-api)
+//! * [Pushed Authorization Requests](#pushed-authorization-requests)
+//! * [Rich Authorization Requests](#rich-authorization-requests)
+//! * [JWT-Secured Authorization Requests](#jwt-secured-authorization-requests)
 //! * [Implicit Grant](#implicit-grant)
 //! * [Resource Owner Password Credentials Grant](#resource-owner-password-credentials-grant)
 //! * [Client Credentials Grant](#client-credentials-grant)
 //! * [Device Code Flow](#device-code-flow)
+//! * [Client-Initiated Backchannel Authentication](#client-initiated-backchannel-authentication)
+//! * [JWT Bearer Grant](#jwt-bearer-grant)
+//! * [Token Exchange](#token-exchange)
+//! * [DPoP-Bound Access Tokens](#dpop-bound-access-tokens)
+//! * [Mutual-TLS Client Authentication](#mutual-tls-client-authentication)
+//! * [Token Refresh](#token-refresh)
+//! * [Tower Integration](#tower-integration)
+//! * [Token Revocation](#token-revocation)
+//! * [Protecting Resources with Bearer Tokens](#protecting-resources-with-bearer-tokens)
+//! * [JWT Access Tokens](#jwt-access-tokens)
+//! * [Dynamic Client Registration](#dynamic-client-registration)
 //! * [Other examples](#other-examples)
 //!   * [Contributed Examples](#contributed-examples)
 //!
@@ -38,6 +52,11 @@
 //!
 //!    Async/await `futures` 0.3 client: [`reqwest::async_http_client`]
 //!
+//!    These functions construct a new `reqwest` client for every request. To reuse connections
+//!    or to configure timeouts, proxies, root certificates or the user agent, create a
+//!    long-lived client using [`reqwest::ReqwestClientBuilder`] and pass it (or a clone of it)
+//!    instead.
+//!
 //!  * **[`curl`]**
 //!
 //!    The `curl` HTTP client only supports the synchronous HTTP client mode and can be enabled in
@@ -45,6 +64,28 @@
 //!
 //!    Synchronous client: [`curl::http_client`]
 //!
+//!  * **[`hyper`]**
+//!
+//!    The `hyper` HTTP client only supports the async/await mode and can be enabled in
+//!    `Cargo.toml` via the `hyper` feature flag. HTTPS connections use `rustls`, so no system
+//!    TLS library is required.
+//!
+//!    Async/await `futures` 0.3 client: [`hyper::async_http_client`]
+//!
+//!    To reuse pooled connections, create a long-lived [`hyper::HyperClient`] and pass it (or a
+//!    clone of it) instead.
+//!
+//!  * **[`ureq`]**
+//!
+//!    The `ureq` HTTP client only supports the synchronous HTTP client mode and can be enabled in
+//!    `Cargo.toml` via the `ureq` feature flag. Unlike `reqwest`, it does not require an async
+//!    runtime, and unlike `curl`, it does not depend on any system libraries.
+//!
+//!    Synchronous client: [`ureq::http_client`]
+//!
+//!    To reuse connections or to configure timeouts, create a long-lived client using
+//!    [`ureq::UreqClientBuilder`] and pass it (or a clone of it) instead.
+//!
 //!  * **Custom**
 //!
 //!    In addition to the clients above, users may define their own HTTP clients, which must accept
@@ -55,20 +96,28 @@
 //!    oauth2 = { version = "3.0", default-features = false }
 //!    ```
 //!
-//!    Synchronous HTTP clients should implement the following trait:
+//!    Synchronous HTTP clients should implement the [`SyncHttpClient`] trait, and async/await
+//!    HTTP clients should implement the [`AsyncHttpClient`] trait. Both traits are implemented
+//!    for closures of the following forms, respectively:
 //!    ```rust,ignore
 //!    FnOnce(HttpRequest) -> Result<HttpResponse, RE>
 //!    where RE: std::error::Error + 'static
 //!    ```
 //!
-//!    Async/await HTTP clients should implement the following trait:
 //!    ```rust,ignore
 //!    FnOnce(HttpRequest) -> F
 //!    where
-//!      F: Future<Output = Result<HttpResponse, RE>>,
+//!      F: Future<Output = Result<HttpResponse, RE>> + Send,
 //!      RE: std::error::Error + 'static
 //!    ```
 //!
+//!    Token requests may send more than one HTTP request (e.g., when polling the token endpoint
+//!    during the Device Code Flow, or when retrying a request with a DPoP nonce) and therefore
+//!    require a client that can be called through a shared reference, such as an `Fn` closure.
+//!    Custom clients implementing the traits directly should also implement them for references
+//!    to themselves. Clients may be boxed as `Box<dyn Fn(HttpRequest) -> ...>`, which implements
+//!    the same traits.
+//!
 //! # Getting started: Authorization Code Grant w/ PKCE
 //!
 //! This is the most common OAuth2 flow. PKCE is recommended whenever the OAuth2 client has no
@@ -129,7 +178,8 @@
 //!
 //! // Once the user has been redirected to the redirect URL, you'll have access to the
 //! // authorization code. For security reasons, your code should verify that the `state`
-//! // parameter returned by the server matches `csrf_state`.
+//! // parameter returned by the server matches `csrf_state` (e.g., using
+//! // `authorization::AuthorizationResponse::validate`).
 //!
 //! // Now you can trade it for an access token.
 //! let token_result =
@@ -200,7 +250,8 @@
 //!
 //! // Once the user has been redirected to the redirect URL, you'll have access to the
 //! // authorization code. For security reasons, your code should verify that the `state`
-//! // parameter returned by the server matches `csrf_state`.
+//! // parameter returned by the server matches `csrf_state` (e.g., using
+//! // `authorization::AuthorizationResponse::validate`).
 //!
 //! // Now you can trade it for an access token.
 //! let token_result = client
@@ -215,6 +266,189 @@
 //! # }
 //! ```
 //!
+//! # Pushed Authorization Requests
+//!
+//! Rather than passing the authorization request parameters in the authorization URL, clients
+//! may push them to the authorization server's PAR endpoint by calling the
+//! `Client::push_authorization_request` method, as described in
+//! [RFC 9126](https://tools.ietf.org/html/rfc9126). The resulting authorization URL contains only
+//! the `client_id` and `request_uri` parameters.
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! use anyhow;
+//! use oauth2::{
+//!     AuthUrl,
+//!     ClientId,
+//!     ClientSecret,
+//!     CsrfToken,
+//!     ParUrl,
+//!     PkceCodeChallenge,
+//!     RedirectUrl,
+//!     Scope,
+//!     TokenUrl
+//! };
+//! use oauth2::basic::BasicClient;
+//! use oauth2::reqwest::http_client;
+//!
+//! # fn err_wrapper() -> Result<(), anyhow::Error> {
+//! let client =
+//!     BasicClient::new(
+//!         ClientId::new("client_id".to_string()),
+//!         Some(ClientSecret::new("client_secret".to_string())),
+//!         AuthUrl::new("http://authorize".to_string())?,
+//!         Some(TokenUrl::new("http://token".to_string())?),
+//!     )
+//!     .set_par_url(ParUrl::new("http://par".to_string())?)
+//!     .set_redirect_url(RedirectUrl::new("http://redirect".to_string())?);
+//!
+//! let (pkce_challenge, pkce_verifier) = PkceCodeChallenge::new_random_sha256();
+//!
+//! let (auth_url, csrf_token, _) = client
+//!     .push_authorization_request(
+//!         client
+//!             .authorize_url(CsrfToken::new_random)
+//!             .add_scope(Scope::new("read".to_string()))
+//!             .set_pkce_challenge(pkce_challenge),
+//!     )
+//!     .request(http_client)?;
+//!
+//! println!("Browse to: {}", auth_url);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! # Rich Authorization Requests
+//!
+//! Clients needing fine-grained permissions (e.g., to initiate a specific payment) may request
+//! structured `authorization_details` instead of or in addition to scopes, as described in
+//! [RFC 9396](https://tools.ietf.org/html/rfc9396). Authorization details may be any type
+//! implementing the `rar::AuthorizationDetail` trait, and the details granted by the
+//! authorization server are returned in the token response's
+//! `rar::AuthorizationDetailsTokenFields`.
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! use anyhow;
+//! use oauth2::{
+//!     AuthUrl,
+//!     Client,
+//!     ClientId,
+//!     ClientSecret,
+//!     CsrfToken,
+//!     TokenResponse,
+//!     TokenUrl
+//! };
+//! use oauth2::basic::{BasicErrorResponse, BasicTokenInspectionResponse, BasicTokenType};
+//! use oauth2::rar::{AuthorizationDetailsTokenResponse, StandardAuthorizationDetail};
+//! use oauth2::reqwest::http_client;
+//!
+//! # fn err_wrapper() -> Result<(), anyhow::Error> {
+//! type RarClient = Client<
+//!     BasicErrorResponse,
+//!     AuthorizationDetailsTokenResponse<StandardAuthorizationDetail, BasicTokenType>,
+//!     BasicTokenType,
+//!     BasicTokenInspectionResponse,
+//! >;
+//!
+//! let client =
+//!     RarClient::new(
+//!         ClientId::new("client_id".to_string()),
+//!         Some(ClientSecret::new("client_secret".to_string())),
+//!         AuthUrl::new("http://authorize".to_string())?,
+//!         Some(TokenUrl::new("http://token".to_string())?),
+//!     );
+//!
+//! let payment = StandardAuthorizationDetail::new("payment_initiation".to_string())
+//!     .set_actions(Some(vec!["initiate".to_string()]))
+//!     .set_extra_field(
+//!         "instructedAmount".to_string(),
+//!         serde_json::json!({"currency": "EUR", "amount": "123.50"}),
+//!     );
+//!
+//! let (auth_url, csrf_token) = client
+//!     .authorize_url(CsrfToken::new_random)
+//!     .add_authorization_detail(&payment)
+//!     .url();
+//!
+//! // Once the user has been redirected back, exchange the authorization code.
+//! # let code = oauth2::AuthorizationCode::new("code".to_string());
+//! let token_result = client.exchange_code(code).request(http_client)?;
+//! let granted_details = token_result.extra_fields().authorization_details();
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! # JWT-Secured Authorization Requests
+//!
+//! Authorization servers requiring signed request objects, as described in
+//! [RFC 9101](https://tools.ietf.org/html/rfc9101), are supported by calling
+//! `AuthorizationRequest::request_object` with the client's signing key. All of the
+//! authorization request parameters are then passed as claims of a JWT in the `request`
+//! parameter (or hosted by the client and referenced by the `request_uri` parameter), and only
+//! the `client_id` remains in the query of the authorization URL. Request objects may optionally
+//! be encrypted to the authorization server using one of the `jwt::JweEncryptionKey`
+//! implementations.
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! use anyhow;
+//! use oauth2::{
+//!     AuthUrl,
+//!     ClientId,
+//!     CsrfToken,
+//!     PkceCodeChallenge,
+//!     RedirectUrl,
+//!     Scope,
+//!     TokenUrl
+//! };
+//! use oauth2::basic::BasicClient;
+//! use oauth2::jwt::{
+//!     EcdhEsEncryptionKey,
+//!     JsonWebKeySet,
+//!     JweContentEncryptionAlgorithm,
+//!     RsaSigningKey
+//! };
+//!
+//! # fn err_wrapper() -> Result<(), anyhow::Error> {
+//! let client =
+//!     BasicClient::new(
+//!         ClientId::new("client_id".to_string()),
+//!         None,
+//!         AuthUrl::new("http://authorize".to_string())?,
+//!         Some(TokenUrl::new("http://token".to_string())?),
+//!     )
+//!     .set_redirect_url(RedirectUrl::new("http://redirect".to_string())?);
+//!
+//! let signing_key = RsaSigningKey::from_pem(&std::fs::read_to_string("client_key.pem")?)?
+//!     .set_key_id("client-key-1".to_string());
+//!
+//! // Optionally, encrypt the request object using the authorization server's public key.
+//! let server_jwks: JsonWebKeySet =
+//!     serde_json::from_str(&std::fs::read_to_string("server_jwks.json")?)?;
+//! let encryption_key = EcdhEsEncryptionKey::from_jwk(
+//!     &server_jwks.keys()[0],
+//!     JweContentEncryptionAlgorithm::Aes128Gcm,
+//! )?;
+//!
+//! let (pkce_challenge, pkce_verifier) = PkceCodeChallenge::new_random_sha256();
+//!
+//! let (auth_url, csrf_token) = client
+//!     .authorize_url(CsrfToken::new_random)
+//!     .add_scope(Scope::new("read".to_string()))
+//!     .set_pkce_challenge(pkce_challenge)
+//!     .request_object("http://authorize".to_string(), &signing_key)
+//!     .set_encryption_key(&encryption_key)
+//!     .url()?;
+//!
+//! println!("Browse to: {}", auth_url);
+//! # Ok(())
+//! # }
+//! ```
+//!
 //! # Implicit Grant
 //!
 //! This flow fetches an access token directly from the authorization endpoint. Be sure to
@@ -233,7 +467,8 @@
 //!     RedirectUrl,
 //!     Scope
 //! };
-//! use oauth2::basic::BasicClient;
+//! use oauth2::authorization::ImplicitResponse;
+//! use oauth2::basic::{BasicClient, BasicTokenResponse};
 //! use url::Url;
 //!
 //! # fn err_wrapper() -> Result<(), anyhow::Error> {
@@ -255,9 +490,12 @@
 //! // process.
 //! println!("Browse to: {}", auth_url);
 //!
-//! // Once the user has been redirected to the redirect URL, you'll have the access code.
-//! // For security reasons, your code should verify that the `state` parameter returned by the
-//! // server matches `csrf_state`.
+//! // Once the user has been redirected to the redirect URL, the access token is included in the
+//! // fragment of the URL. For security reasons, the token response is only returned if the
+//! // `state` parameter returned by the server matches `csrf_token`.
+//! let redirect_url = Url::parse("http://localhost/callback#access_token=...")?;
+//! let token_response: BasicTokenResponse =
+//!     ImplicitResponse::from_url(&redirect_url)?.validate(&csrf_token, None)?;
 //!
 //! # Ok(())
 //! # }
@@ -402,435 +640,2412 @@
 //! # }
 //! ```
 //!
-//! # Other examples
+//! # Client-Initiated Backchannel Authentication
 //!
-//! More specific implementations are available as part of the examples:
+//! Client-Initiated Backchannel Authentication (CIBA) allows a client such as a call-center
+//! application to request that a user authenticate on their own device (e.g., a banking app)
+//! without being redirected. The client first sends a backchannel authentication request
+//! identifying the user with `Client::backchannel_authentication`, then obtains the token in
+//! one of two ways:
 //!
-//! - [Google](https://github.com/ramosbugs/oauth2-rs/blob/main/examples/google.rs)
-//! - [Github](https://github.com/ramosbugs/oauth2-rs/blob/main/examples/github.rs)
-//! - [Microsoft Graph](https://github.com/ramosbugs/oauth2-rs/blob/main/examples/msgraph.rs)
-//! - [Wunderlist](https://github.com/ramosbugs/oauth2-rs/blob/main/examples/wunderlist.rs)
+//!  * **Poll mode**: the client polls the token endpoint, as in the Device Code Flow.
+//!  * **Ping mode**: the authorization server calls the client notification endpoint once the
+//!    user has authenticated (see `ciba::BackchannelPingNotification`), and the handler then
+//!    requests the token using `Client::exchange_auth_req_id`.
 //!
-//! ## Contributed Examples
+//! ## Example
 //!
-//! - [`actix-web-oauth2`](https://github.com/pka/actix-web-oauth2) (version 2.x of this crate)
+//! ```rust,no_run
+//! use anyhow;
+//! use oauth2::{
+//!     AuthUrl,
+//!     BackchannelAuthenticationUrl,
+//!     ClientId,
+//!     ClientSecret,
+//!     Scope,
+//!     TokenResponse,
+//!     TokenUrl
+//! };
+//! use oauth2::basic::BasicClient;
+//! use oauth2::reqwest::http_client;
+//! use std::time::Duration;
 //!
-use chrono::serde::ts_seconds_option;
-use chrono::{DateTime, Utc};
-use std::borrow::Cow;
-use std::error::Error;
-use std::fmt::Error as FormatterError;
-use std::fmt::{Debug, Display, Formatter};
-use std::future::Future;
-use std::marker::PhantomData;
-use std::sync::Arc;
-use std::time::Duration;
-
-use http::header::{HeaderMap, HeaderValue, ACCEPT, AUTHORIZATION, CONTENT_TYPE};
-use http::status::StatusCode;
-use serde::de::DeserializeOwned;
-use serde::{Deserialize, Serialize};
-use url::{form_urlencoded, Url};
-
-///
-/// Basic OAuth2 implementation with no extensions
-/// ([RFC 6749](https://tools.ietf.org/html/rfc6749)).
-///
-pub mod basic;
-
-///
-/// HTTP client backed by the [curl](https://crates.io/crates/curl) crate.
-/// Requires "curl" feature.
-///
-#[cfg(feature = "curl")]
-pub mod curl;
-
-///
-/// Device Code Flow OAuth2 implementation
-/// ([RFC 8628](https://tools.ietf.org/html/rfc8628)).
-///
-pub mod devicecode;
-use devicecode::{
-    DeviceAccessTokenPollResult, DeviceAuthorizationResponse, DeviceCodeErrorResponse,
-    DeviceCodeErrorResponseType, ExtraDeviceAuthorizationFields,
-};
-
-///
-/// Helper methods used by OAuth2 implementations/extensions.
-///
-pub mod helpers;
-
-///
-/// HTTP client backed by the [reqwest](https://crates.io/crates/reqwest) crate.
-/// Requires "reqwest-010" feature.
-///
-#[cfg(feature = "reqwest-010")]
-pub mod reqwest;
-
-#[cfg(test)]
-mod tests;
-
-mod types;
-
-///
-/// Public re-exports of types used for HTTP client interfaces.
-///
-pub use http;
-pub use url;
-
-pub use types::{
-    AccessToken, AuthUrl, AuthorizationCode, ClientId, ClientSecret, CsrfToken,
-    DeviceAuthorizationUrl, DeviceCode, EndUserVerificationUrl, IntrospectUrl, PkceCodeChallenge,
-    PkceCodeChallengeMethod, PkceCodeVerifier, RedirectUrl, RefreshToken, ResourceOwnerPassword,
-    ResourceOwnerUsername, ResponseType, Scope, TokenUrl, UserCode,
-};
-
-const CONTENT_TYPE_JSON: &str = "application/json";
-const CONTENT_TYPE_FORMENCODED: &str = "application/x-www-form-urlencoded";
-
-///
-/// Indicates whether requests to the authorization server should use basic authentication or
-/// include the parameters in the request body for requests in which either is valid.
-///
-/// The default AuthType is *BasicAuth*, following the recommendation of
-/// [Section 2.3.1 of RFC 6749](https://tools.ietf.org/html/rfc6749#section-2.3.1).
-///
-#[derive(Clone, Debug)]
-pub enum AuthType {
-    /// The client_id and client_secret will be included as part of the request body.
-    RequestBody,
-    /// The client_id and client_secret will be included using the basic auth authentication scheme.
-    BasicAuth,
-}
-
-///
-/// Stores the configuration for an OAuth2 client.
-///
-#[derive(Clone, Debug)]
-pub struct Client<TE, TR, TT, TIR>
-where
-    TE: ErrorResponse,
-    TR: TokenResponse<TT>,
-    TT: TokenType,
-    TIR: TokenInspectionResponse<TT>,
-{
-    client_id: ClientId,
-    client_secret: Option<ClientSecret>,
-    auth_url: AuthUrl,
-    auth_type: AuthType,
-    token_url: Option<TokenUrl>,
-    redirect_url: Option<RedirectUrl>,
-    introspect_url: Option<IntrospectUrl>,
-    device_authorization_url: Option<DeviceAuthorizationUrl>,
-    phantom_te: PhantomData<TE>,
-    phantom_tr: PhantomData<TR>,
-    phantom_tt: PhantomData<TT>,
-    phantom_tir: PhantomData<TIR>,
-}
-
+//! # fn err_wrapper() -> Result<(), anyhow::Error> {
+//! let client =
+//!     BasicClient::new(
+//!         ClientId::new("client_id".to_string()),
+//!         Some(ClientSecret::new("client_secret".to_string())),
+//!         AuthUrl::new("http://authorize".to_string())?,
+//!         Some(TokenUrl::new("http://token".to_string())?),
+//!     )
+//!     .set_backchannel_authentication_url(
+//!         BackchannelAuthenticationUrl::new("http://backchannel".to_string())?
+//!     );
+//!
+//! let auth_response = client
+//!     .backchannel_authentication()
+//!     .add_scope(Scope::new("openid".to_string()))
+//!     .set_login_hint("customer-1234")
+//!     .set_binding_message("W4SCT")
+//!     .set_requested_expiry(Duration::from_secs(120))
+//!     .request(http_client)?;
+//!
+//! let token_result =
+//!     client
+//!     .exchange_backchannel_authentication(&auth_response)
+//!     .request(http_client, std::thread::sleep, None)?;
+//!
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! # JWT Bearer Grant
+//!
+//! Service accounts and other clients holding a private key may request an access token using a
+//! signed JWT as an authorization grant by calling the `Client::exchange_jwt_bearer` method, as
+//! described in [RFC 7523](https://tools.ietf.org/html/rfc7523). The assertion may be built from
+//! a service account JSON key file using `jwt::ServiceAccountKey`.
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! use anyhow;
+//! use oauth2::{
+//!     AuthUrl,
+//!     ClientId,
+//!     Scope,
+//!     TokenResponse,
+//! };
+//! use oauth2::basic::BasicClient;
+//! use oauth2::jwt::ServiceAccountKey;
+//! use oauth2::reqwest::http_client;
+//!
+//! # fn err_wrapper() -> Result<(), anyhow::Error> {
+//! let service_account = ServiceAccountKey::from_json(&std::fs::read_to_string("key.json")?)?;
+//! let assertion = service_account
+//!     .assertion_builder()
+//!     .add_scope(Scope::new("https://www.googleapis.com/auth/cloud-platform".to_string()))
+//!     .sign()?;
+//!
+//! let client =
+//!     BasicClient::new(
+//!         ClientId::new(service_account.client_email().to_string()),
+//!         None,
+//!         AuthUrl::new("http://authorize".to_string())?,
+//!         Some(service_account.token_uri().clone()),
+//!     );
+//!
+//! let token_result = client
+//!     .exchange_jwt_bearer(&assertion)
+//!     .request(http_client)?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! # Token Exchange
+//!
+//! A security token (e.g., an access token issued to the end-user) may be exchanged for a new
+//! token, such as a downscoped access token for a different audience, by calling the
+//! `Client::exchange_token` method, as described in
+//! [RFC 8693](https://tools.ietf.org/html/rfc8693).
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! use anyhow;
+//! use oauth2::{
+//!     Audience,
+//!     AuthUrl,
+//!     ClientId,
+//!     ClientSecret,
+//!     Scope,
+//!     SubjectToken,
+//!     TokenResponse,
+//!     TokenUrl
+//! };
+//! use oauth2::basic::{BasicClient, BasicTokenExchangeTokenResponse};
+//! use oauth2::reqwest::http_client;
+//! use oauth2::tokenexchange::TokenTypeIdentifier;
+//!
+//! # fn err_wrapper() -> Result<(), anyhow::Error> {
+//! let client =
+//!     BasicClient::new(
+//!         ClientId::new("client_id".to_string()),
+//!         Some(ClientSecret::new("client_secret".to_string())),
+//!         AuthUrl::new("http://authorize".to_string())?,
+//!         Some(TokenUrl::new("http://token".to_string())?),
+//!     );
+//!
+//! let subject_token = SubjectToken::new("user access token".to_string());
+//! let token_result: BasicTokenExchangeTokenResponse = client
+//!     .exchange_token(&subject_token, TokenTypeIdentifier::AccessToken)
+//!     .add_audience(Audience::new("backend".to_string()))
+//!     .add_scope(Scope::new("read".to_string()))
+//!     .request(http_client)?;
+//!
+//! println!("Issued token type: {}", token_result.issued_token_type());
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! # DPoP-Bound Access Tokens
+//!
+//! Access tokens may be bound to a key held by the client using Demonstrating Proof of
+//! Possession (DPoP), as described in [RFC 9449](https://tools.ietf.org/html/rfc9449). Calling
+//! `set_dpop_key` on a token request attaches a signed `DPoP` proof to the request, and the
+//! `dpop::authorize_request` function attaches a proof to each request to a protected resource.
+//!
+//! Authorization servers may require DPoP proofs to include a server-provided nonce. Token
+//! requests with a DPoP key are automatically retried once with the nonce when the server asks
+//! for one.
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! use anyhow;
+//! use oauth2::{
+//!     AuthUrl,
+//!     ClientId,
+//!     ClientSecret,
+//!     HttpRequest,
+//!     TokenResponse,
+//!     TokenUrl
+//! };
+//! use oauth2::basic::BasicClient;
+//! use oauth2::dpop;
+//! use oauth2::jwt::EcdsaSigningKey;
+//! use oauth2::reqwest::http_client;
+//! use url::Url;
+//!
+//! # fn err_wrapper() -> Result<(), anyhow::Error> {
+//! let client =
+//!     BasicClient::new(
+//!         ClientId::new("client_id".to_string()),
+//!         Some(ClientSecret::new("client_secret".to_string())),
+//!         AuthUrl::new("http://authorize".to_string())?,
+//!         Some(TokenUrl::new("http://token".to_string())?),
+//!     );
+//!
+//! // The same key must be used for all requests made with the access token.
+//! let dpop_key = EcdsaSigningKey::new_random()?;
+//! let token_result = client
+//!     .exchange_client_credentials()
+//!     .set_dpop_key(&dpop_key)
+//!     .request(http_client)?;
+//!
+//! let mut request = HttpRequest {
+//!     url: Url::parse("http://resource")?,
+//!     method: http::method::Method::GET,
+//!     headers: http::HeaderMap::new(),
+//!     body: Vec::new(),
+//! };
+//! dpop::authorize_request(&mut request, &dpop_key, token_result.access_token(), None)?;
+//! let response = http_client(request)?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! # Mutual-TLS Client Authentication
+//!
+//! Clients may authenticate using a TLS client certificate instead of a client secret, as
+//! described in [RFC 8705](https://tools.ietf.org/html/rfc8705). With `AuthType::TlsClientAuth`
+//! or `AuthType::SelfSignedTlsClientAuth`, only the client ID is sent in the request body, and
+//! the certificate is presented by an HTTP client such as `reqwest::mtls_http_client`. Access
+//! tokens issued over such a connection may be bound to the certificate. Authorization servers
+//! may advertise separate endpoints for mutual-TLS connections (`mtls_endpoint_aliases`), which
+//! `Client::from_metadata_mtls` uses in place of the regular endpoints.
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! use anyhow;
+//! use oauth2::{AuthType, ClientId, IssuerUrl};
+//! use oauth2::basic::BasicClient;
+//! use oauth2::discovery::StandardProviderMetadata;
+//! use oauth2::reqwest::{http_client, mtls_http_client};
+//!
+//! # fn err_wrapper() -> Result<(), anyhow::Error> {
+//! let metadata = StandardProviderMetadata::discover(
+//!     &IssuerUrl::new("https://server.example.com".to_string())?,
+//!     http_client,
+//! )?;
+//! let client =
+//!     BasicClient::from_metadata_mtls(&metadata, ClientId::new("client_id".to_string()), None)
+//!         .set_auth_type(AuthType::TlsClientAuth);
+//!
+//! // PEM-encoded private key followed by the client's certificate chain.
+//! let identity = std::fs::read("client.pem")?;
+//! let token_result = client
+//!     .exchange_client_credentials()
+//!     .request(mtls_http_client(&identity, None)?)?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! # Token Refresh
+//!
+//! Instead of tracking when the access token expires and calling
+//! `Client::exchange_refresh_token` directly, applications may wrap the client and token response
+//! in a `tokenmanager::TokenManager`. The manager refreshes the access token shortly before it
+//! expires, retains the refresh token unless the authorization server issues a new one, and
+//! shares a single refresh request between concurrent callers. To persist tokens between runs,
+//! load them from a `tokenstore::TokenStore` using `TokenManager::from_stored_token` and call
+//! `TokenManager::set_token_store` to write refreshed tokens back to the store.
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! use anyhow;
+//! use oauth2::{
+//!     AuthUrl,
+//!     ClientId,
+//!     ClientSecret,
+//!     ResourceOwnerPassword,
+//!     ResourceOwnerUsername,
+//!     TokenUrl
+//! };
+//! use oauth2::basic::BasicClient;
+//! use oauth2::reqwest::http_client;
+//! use oauth2::tokenmanager::TokenManager;
+//! use std::sync::Arc;
+//! use std::time::Duration;
+//!
+//! # fn err_wrapper() -> Result<(), anyhow::Error> {
+//! let client =
+//!     BasicClient::new(
+//!         ClientId::new("client_id".to_string()),
+//!         Some(ClientSecret::new("client_secret".to_string())),
+//!         AuthUrl::new("http://authorize".to_string())?,
+//!         Some(TokenUrl::new("http://token".to_string())?)
+//!     );
+//!
+//! let token_result =
+//!     client
+//!         .exchange_password(
+//!             &ResourceOwnerUsername::new("user".to_string()),
+//!             &ResourceOwnerPassword::new("pass".to_string())
+//!         )
+//!         .request(http_client)?;
+//!
+//! // The manager may be shared between threads.
+//! let token_manager = Arc::new(
+//!     TokenManager::new(client, token_result).set_refresh_skew(Duration::from_secs(30))
+//! );
+//!
+//! // Returns the current access token, or refreshes it first if it's about to expire.
+//! let access_token = token_manager.access_token(http_client)?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! # Tower Integration
+//!
+//! With the `tower` feature flag enabled, any tower `Service` accepting an
+//! `http::Request<Vec<u8>>` and returning an `http::Response<Vec<u8>>` may be used as the HTTP
+//! client for the `request_async` methods by wrapping it in a `tower::TowerHttpClient`, so that
+//! its layers (e.g., retries, timeouts and tracing) also apply to requests sent to the
+//! authorization server.
+//!
+//! The `tower::BearerTokenLayer` attaches an access token to outgoing requests. Access tokens are
+//! obtained using the Client Credentials Grant (`tower::ClientCredentialsTokenSource`) or by
+//! refreshing a `tokenmanager::TokenManager` (`tower::RefreshTokenSource`). If a request is
+//! rejected with `401 Unauthorized`, a new access token is obtained and the request is retried
+//! once.
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! use anyhow;
+//! use oauth2::{AuthUrl, ClientId, ClientSecret, Scope, TokenUrl};
+//! use oauth2::basic::BasicClient;
+//! # #[cfg(all(feature = "reqwest-010", feature = "tower"))]
+//! use oauth2::reqwest::ReqwestClientBuilder;
+//! # #[cfg(all(feature = "reqwest-010", feature = "tower"))]
+//! use oauth2::tower::{BearerTokenLayer, ClientCredentialsTokenSource};
+//!
+//! # #[cfg(all(feature = "reqwest-010", feature = "tower"))]
+//! # fn err_wrapper() -> Result<(), anyhow::Error> {
+//! let client =
+//!     BasicClient::new(
+//!         ClientId::new("client_id".to_string()),
+//!         Some(ClientSecret::new("client_secret".to_string())),
+//!         AuthUrl::new("http://authorize".to_string())?,
+//!         Some(TokenUrl::new("http://token".to_string())?)
+//!     );
+//!
+//! let layer = BearerTokenLayer::new(
+//!     ClientCredentialsTokenSource::new(client, ReqwestClientBuilder::new().build_async()?)
+//!         .add_scope(Scope::new("read".to_string())),
+//! );
+//!
+//! // The layer may be applied to any tower service sending `http::Request`s to the resource
+//! // server, e.g.:
+//! // let service = tower::ServiceBuilder::new().layer(layer).service(http_service);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! # Token Revocation
+//!
+//! Access and refresh tokens may be revoked (e.g., when the end-user logs out) by calling the
+//! `Client::revoke_token` method, as described in
+//! [RFC 7009](https://tools.ietf.org/html/rfc7009).
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! use anyhow;
+//! use oauth2::{
+//!     AccessToken,
+//!     AuthUrl,
+//!     ClientId,
+//!     ClientSecret,
+//!     RevocationUrl,
+//!     TokenUrl
+//! };
+//! use oauth2::basic::BasicClient;
+//! use oauth2::reqwest::http_client;
+//!
+//! # fn err_wrapper() -> Result<(), anyhow::Error> {
+//! let client =
+//!     BasicClient::new(
+//!         ClientId::new("client_id".to_string()),
+//!         Some(ClientSecret::new("client_secret".to_string())),
+//!         AuthUrl::new("http://authorize".to_string())?,
+//!         Some(TokenUrl::new("http://token".to_string())?),
+//!     )
+//!     .set_revocation_url(RevocationUrl::new("http://revoke".to_string())?);
+//!
+//! let access_token = AccessToken::new("access token".to_string());
+//! client
+//!     .revoke_token(&access_token)
+//!     .request(http_client)?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! # Protecting Resources with Bearer Tokens
+//!
+//! Resource servers may validate the bearer tokens included in incoming requests using
+//! `resourceserver::BearerTokenValidator`, which introspects the token (or calls a custom
+//! verifier) and checks its expiration, audience, issuer and scopes. Rejected requests should be
+//! answered with the status code and `WWW-Authenticate` challenge defined in
+//! [RFC 6750](https://tools.ietf.org/html/rfc6750), as returned by
+//! `BearerTokenValidator::error_response`.
+//!
+//! To avoid introspecting the token of every request, introspection responses may be cached
+//! using `introspectioncache::IntrospectionCache` (e.g., by calling its `introspect` method
+//! from a verifier passed to `BearerTokenValidator::validate`).
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! use oauth2::{
+//!     AuthUrl,
+//!     ClientId,
+//!     ClientSecret,
+//!     IntrospectUrl,
+//!     Scope,
+//!     TokenInspectionResponse,
+//! };
+//! use oauth2::basic::BasicClient;
+//! use oauth2::resourceserver::BearerTokenValidator;
+//! use oauth2::reqwest::http_client;
+//!
+//! # fn err_wrapper() -> Result<(), anyhow::Error> {
+//! let client =
+//!     BasicClient::new(
+//!         ClientId::new("resource_server".to_string()),
+//!         Some(ClientSecret::new("resource_server_secret".to_string())),
+//!         AuthUrl::new("http://authorize".to_string())?,
+//!         None,
+//!     )
+//!     .set_introspection_url(IntrospectUrl::new("http://introspect".to_string())?);
+//!
+//! let validator = BearerTokenValidator::new()
+//!     .set_audience("http://api".to_string())
+//!     .add_required_scope(Scope::new("read".to_string()));
+//!
+//! # let request = http::Request::new(Vec::<u8>::new());
+//! let response: http::Response<Vec<u8>> =
+//!     match validator.introspect(&request, &client, http_client) {
+//!         Ok(token_info) => {
+//!             println!("Request authorized for {:?}", token_info.sub());
+//!             http::Response::new(b"protected resource".to_vec())
+//!         }
+//!         Err(err) => validator.error_response(&err),
+//!     };
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! # JWT Access Tokens
+//!
+//! Access tokens issued in the JWT format defined in
+//! [RFC 9068](https://tools.ietf.org/html/rfc9068) may be validated locally using
+//! `jwtaccesstoken::JwtAccessTokenValidator`, which avoids an introspection request per token.
+//! The validator checks the token's `typ` header, signature, issuer, audience, expiration,
+//! client ID and scopes. The authorization server's signing keys are fetched from its JWKS
+//! document and cached by `jwt::JwksCache`, which refreshes them when a token is signed by an
+//! unknown key.
+//!
+//! Validated claims are returned as a `TokenInspectionResponse`, so local validation may be used
+//! in place of introspection (e.g., from a verifier passed to `BearerTokenValidator::validate`).
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! use oauth2::{JsonWebKeySetUrl, Scope, TokenInspectionResponse};
+//! use oauth2::basic::BasicTokenInspectionResponse;
+//! use oauth2::jwt::JwksCache;
+//! use oauth2::jwtaccesstoken::JwtAccessTokenValidator;
+//! use oauth2::resourceserver::BearerTokenValidator;
+//! use oauth2::reqwest::http_client;
+//!
+//! # fn err_wrapper() -> Result<(), anyhow::Error> {
+//! let jwt_validator = JwtAccessTokenValidator::new(
+//!     "https://issuer".to_string(),
+//!     "https://api".to_string(),
+//!     JwksCache::new(JsonWebKeySetUrl::new("https://issuer/jwks".to_string())?),
+//! )
+//! .add_required_scope(Scope::new("read".to_string()));
+//! let validator = BearerTokenValidator::new();
+//!
+//! # let request = http::Request::new(Vec::<u8>::new());
+//! let response: http::Response<Vec<u8>> = match validator.validate(&request, |token| {
+//!     jwt_validator.validate(&token, http_client).map_err(Into::into)
+//! }) {
+//!     Ok(token_info) => {
+//!         let token_info: BasicTokenInspectionResponse = token_info;
+//!         println!("Request authorized for {:?}", token_info.sub());
+//!         http::Response::new(b"protected resource".to_vec())
+//!     }
+//!     Err(err) => validator.error_response(&err),
+//! };
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! # Dynamic Client Registration
+//!
+//! Clients may be registered with the authorization server at runtime using
+//! `registration::ClientRegistrationRequest`, as described in
+//! [RFC 7591](https://tools.ietf.org/html/rfc7591). The registration can later be read,
+//! updated or deleted via its client configuration endpoint
+//! ([RFC 7592](https://tools.ietf.org/html/rfc7592)).
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! use anyhow;
+//! use oauth2::{AuthUrl, ClientAuthMethod, RedirectUrl, RegistrationUrl, TokenUrl};
+//! use oauth2::basic::BasicClient;
+//! use oauth2::registration::{
+//!     ClientRegistrationRequest,
+//!     EmptyExtraClientMetadata,
+//!     StandardClientMetadata
+//! };
+//! use oauth2::reqwest::http_client;
+//!
+//! # fn err_wrapper() -> Result<(), anyhow::Error> {
+//! let metadata = StandardClientMetadata::new(EmptyExtraClientMetadata::default())
+//!     .set_client_name("My Client".to_string())
+//!     .set_redirect_uris(vec![RedirectUrl::new("http://redirect".to_string())?])
+//!     .set_token_endpoint_auth_method(ClientAuthMethod::new("client_secret_basic".to_string()));
+//!
+//! let registration_url = RegistrationUrl::new("http://register".to_string())?;
+//! let registration = ClientRegistrationRequest::new(&registration_url, &metadata)
+//!     .request(http_client)?;
+//!
+//! let client =
+//!     BasicClient::from_registration(
+//!         &registration,
+//!         AuthUrl::new("http://authorize".to_string())?,
+//!         Some(TokenUrl::new("http://token".to_string())?),
+//!     )?;
+//!
+//! // Delete the registration once the client is no longer needed.
+//! if let Some(endpoint) = registration.configuration_endpoint() {
+//!     endpoint.delete(http_client)?;
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! # Other examples
+//!
+//! More specific implementations are available as part of the examples:
+//!
+//! - [Google](https://github.com/ramosbugs/oauth2-rs/blob/main/examples/google.rs)
+//! - [Github](https://github.com/ramosbugs/oauth2-rs/blob/main/examples/github.rs)
+//! - [Microsoft Graph](https://github.com/ramosbugs/oauth2-rs/blob/main/examples/msgraph.rs)
+//! - [Wunderlist](https://github.com/ramosbugs/oauth2-rs/blob/main/examples/wunderlist.rs)
+//!
+//! ## Contributed Examples
+//!
+//! - [`actix-web-oauth2`](https://github.com/pka/actix-web-oauth2) (version 2.x of this crate)
+//!
+use chrono::serde::ts_seconds_option;
+use chrono::{DateTime, Utc};
+use std::borrow::Cow;
+use std::error::Error;
+use std::fmt::Error as FormatterError;
+use std::fmt::{Debug, Display, Formatter};
+use std::future::Future;
+use std::marker::PhantomData;
+use std::sync::Arc;
+use std::time::Duration;
+
+use http::header::{HeaderMap, HeaderValue, ACCEPT, AUTHORIZATION, CONTENT_TYPE};
+use http::status::StatusCode;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use url::{form_urlencoded, Url};
+
+///
+/// Parsing and validation of authorization endpoint responses, including Implicit Grant
+/// responses ([RFC 6749](https://tools.ietf.org/html/rfc6749#section-4.1.2) and
+/// [RFC 9207](https://tools.ietf.org/html/rfc9207)).
+///
+pub mod authorization;
+
+///
+/// Basic OAuth2 implementation with no extensions
+/// ([RFC 6749](https://tools.ietf.org/html/rfc6749)).
+///
+pub mod basic;
+
+///
+/// Client-Initiated Backchannel Authentication (CIBA) implementation
+/// ([OpenID Connect CIBA Core](https://openid.net/specs/openid-client-initiated-backchannel-authentication-core-1_0.html)).
+///
+pub mod ciba;
+use ciba::{
+    BackchannelAuthenticationErrorResponse, BackchannelAuthenticationResponse, CIBA_GRANT_TYPE,
+};
+
+///
+/// HTTP client backed by the [curl](https://crates.io/crates/curl) crate.
+/// Requires "curl" feature.
+///
+#[cfg(feature = "curl")]
+pub mod curl;
+
+///
+/// Device Code Flow OAuth2 implementation
+/// ([RFC 8628](https://tools.ietf.org/html/rfc8628)).
+///
+pub mod devicecode;
+use devicecode::{
+    DeviceAccessTokenPollResult, DeviceAuthorizationResponse, DeviceCodeErrorResponse,
+    DeviceCodeErrorResponseType, ExtraDeviceAuthorizationFields,
+};
+
+///
+/// Authorization Server Metadata discovery
+/// ([RFC 8414](https://tools.ietf.org/html/rfc8414)).
+///
+pub mod discovery;
+use discovery::{ExtraProviderMetadata, MtlsEndpointAliases, ProviderMetadata};
+
+///
+/// Demonstrating Proof of Possession (DPoP) for sender-constrained access tokens
+/// ([RFC 9449](https://tools.ietf.org/html/rfc9449)).
+///
+pub mod dpop;
+use dpop::DpopKey;
+
+///
+/// Helper methods used by OAuth2 implementations/extensions.
+///
+pub mod helpers;
+
+///
+/// HTTP client backed by the [hyper](https://crates.io/crates/hyper) crate.
+/// Requires "hyper" feature.
+///
+#[cfg(feature = "hyper")]
+pub mod hyper;
+
+///
+/// Caching of token introspection responses
+/// ([RFC 7662](https://tools.ietf.org/html/rfc7662)).
+///
+pub mod introspectioncache;
+
+///
+/// JWT-Secured Authorization Requests (JAR) implementation
+/// ([RFC 9101](https://tools.ietf.org/html/rfc9101)).
+///
+pub mod jar;
+use jar::RequestObjectBuilder;
+
+///
+/// JSON Web Token (JWT) signing and encryption used for JWT-based client authentication
+/// ([RFC 7523](https://tools.ietf.org/html/rfc7523)) and request objects.
+///
+pub mod jwt;
+use jwt::JwsSigningKey;
+
+///
+/// JWT Profile for OAuth 2.0 Access Tokens implementation
+/// ([RFC 9068](https://tools.ietf.org/html/rfc9068)).
+///
+pub mod jwtaccesstoken;
+
+///
+/// Pushed Authorization Requests (PAR) implementation
+/// ([RFC 9126](https://tools.ietf.org/html/rfc9126)).
+///
+pub mod par;
+use par::PushedAuthorizationResponse;
+
+///
+/// Rich Authorization Requests (RAR) implementation
+/// ([RFC 9396](https://tools.ietf.org/html/rfc9396)).
+///
+pub mod rar;
+use rar::AuthorizationDetail;
+
+///
+/// OAuth 2.0 Dynamic Client Registration and management
+/// ([RFC 7591](https://tools.ietf.org/html/rfc7591) and
+/// [RFC 7592](https://tools.ietf.org/html/rfc7592)).
+///
+pub mod registration;
+use registration::{ClientFromRegistrationError, ClientRegistrationResponse, ExtraClientMetadata};
+
+///
+/// HTTP client backed by the [reqwest](https://crates.io/crates/reqwest) crate.
+/// Requires "reqwest-010" feature.
+///
+#[cfg(feature = "reqwest-010")]
+pub mod reqwest;
+
+///
+/// Bearer token validation for resource servers
+/// ([RFC 6750](https://tools.ietf.org/html/rfc6750)).
+///
+pub mod resourceserver;
+
+///
+/// OAuth 2.0 Token Revocation implementation
+/// ([RFC 7009](https://tools.ietf.org/html/rfc7009)).
+///
+pub mod revocation;
+use revocation::{RevocableToken, RevocationErrorResponse};
+
+///
+/// OAuth 2.0 Token Exchange implementation
+/// ([RFC 8693](https://tools.ietf.org/html/rfc8693)).
+///
+pub mod tokenexchange;
+
+///
+/// Access token lifecycle management, including automatic refresh
+/// ([Section 6 of RFC 6749](https://tools.ietf.org/html/rfc6749#section-6)).
+///
+pub mod tokenmanager;
+
+///
+/// Persistent token storage.
+///
+pub mod tokenstore;
+use tokenexchange::{TokenExchangeTokenResponse, TokenTypeIdentifier};
+
+///
+/// Integration with [tower](https://crates.io/crates/tower) services and layers.
+/// Requires "tower" feature.
+///
+#[cfg(feature = "tower")]
+pub mod tower;
+
+///
+/// HTTP client backed by the [ureq](https://crates.io/crates/ureq) crate.
+/// Requires "ureq" feature.
+///
+#[cfg(feature = "ureq")]
+pub mod ureq;
+
+#[cfg(test)]
+mod tests;
+
+mod types;
+
+///
+/// Public re-exports of types used for HTTP client interfaces.
+///
+pub use http;
+pub use url;
+
+pub use types::{
+    AccessToken, ActorToken, Audience, AuthReqId, AuthUrl, AuthorizationCode,
+    BackchannelAuthenticationUrl, ClientAuthMethod, ClientConfigurationUrl, ClientId,
+    ClientNotificationToken, ClientSecret, CsrfToken, DeviceAuthorizationUrl, DeviceCode,
+    EndUserVerificationUrl, GrantType, IntrospectUrl, IssuerUrl, JsonWebKeySetUrl,
+    JwtBearerAssertion, ParUrl, PkceCodeChallenge, PkceCodeChallengeMethod, PkceCodeVerifier,
+    RedirectUrl, RefreshToken, RegistrationAccessToken, RegistrationUrl, RequestUri,
+    ResourceOwnerPassword, ResourceOwnerUsername, ResourceUrl, ResourceUrlError, ResponseType,
+    RevocationUrl, Scope, SubjectToken, TokenUrl, UserCode,
+};
+
+const CONTENT_TYPE_JSON: &str = "application/json";
+const CONTENT_TYPE_FORMENCODED: &str = "application/x-www-form-urlencoded";
+
+///
+/// Indicates whether requests to the authorization server should use basic authentication or
+/// include the parameters in the request body for requests in which either is valid.
+///
+/// The default AuthType is *BasicAuth*, following the recommendation of
+/// [Section 2.3.1 of RFC 6749](https://tools.ietf.org/html/rfc6749#section-2.3.1).
+///
+#[derive(Clone, Debug)]
+pub enum AuthType {
+    /// The client_id and client_secret will be included as part of the request body.
+    RequestBody,
+    /// The client_id and client_secret will be included using the basic auth authentication scheme.
+    BasicAuth,
+    /// The client authenticates using a JWT signed with HMAC SHA-256 using the client_secret as
+    /// the key (`client_secret_jwt`), as defined in
+    /// [Section 2.2 of RFC 7523](https://tools.ietf.org/html/rfc7523#section-2.2).
+    ClientSecretJwt,
+    /// The client authenticates using a JWT signed with the given private key
+    /// (`private_key_jwt`), as defined in
+    /// [Section 2.2 of RFC 7523](https://tools.ietf.org/html/rfc7523#section-2.2).
+    PrivateKeyJwt(Arc<dyn JwsSigningKey>),
+    /// The client authenticates using a TLS client certificate issued by a trusted certificate
+    /// authority (`tls_client_auth`), as defined in
+    /// [Section 2.1 of RFC 8705](https://tools.ietf.org/html/rfc8705#section-2.1).
+    ///
+    /// Only the client_id is included in the request body. The certificate must be presented by
+    /// the HTTP client (see `reqwest::mtls_http_client` and `curl::mtls_http_client`).
+    TlsClientAuth,
+    /// The client authenticates using a self-signed TLS client certificate registered with the
+    /// authorization server (`self_signed_tls_client_auth`), as defined in
+    /// [Section 2.2 of RFC 8705](https://tools.ietf.org/html/rfc8705#section-2.2).
+    ///
+    /// Only the client_id is included in the request body. The certificate must be presented by
+    /// the HTTP client (see `reqwest::mtls_http_client` and `curl::mtls_http_client`).
+    SelfSignedTlsClientAuth,
+}
+
+///
+/// Stores the configuration for an OAuth2 client.
+///
+#[derive(Clone, Debug)]
+pub struct Client<TE, TR, TT, TIR>
+where
+    TE: ErrorResponse,
+    TR: TokenResponse<TT>,
+    TT: TokenType,
+    TIR: TokenInspectionResponse<TT>,
+{
+    client_id: ClientId,
+    client_secret: Option<ClientSecret>,
+    auth_url: AuthUrl,
+    auth_type: AuthType,
+    token_url: Option<TokenUrl>,
+    redirect_url: Option<RedirectUrl>,
+    introspect_url: Option<IntrospectUrl>,
+    device_authorization_url: Option<DeviceAuthorizationUrl>,
+    revocation_url: Option<RevocationUrl>,
+    par_url: Option<ParUrl>,
+    backchannel_authentication_url: Option<BackchannelAuthenticationUrl>,
+    phantom_te: PhantomData<TE>,
+    phantom_tr: PhantomData<TR>,
+    phantom_tt: PhantomData<TT>,
+    phantom_tir: PhantomData<TIR>,
+}
+
 impl<TE, TR, TT, TIR> Client<TE, TR, TT, TIR>
 where
     TE: ErrorResponse + 'static,
     TR: TokenResponse<TT>,
     TT: TokenType,
-    TIR: TokenInspectionResponse<TT>,
+    TIR: TokenInspectionResponse<TT>,
+{
+    ///
+    /// Initializes an OAuth2 client with the fields common to most OAuth2 flows.
+    ///
+    /// # Arguments
+    ///
+    /// * `client_id` -  Client ID
+    /// * `client_secret` -  Optional client secret. A client secret is generally used for private
+    ///   (server-side) OAuth2 clients and omitted from public (client-side or native app) OAuth2
+    ///   clients (see [RFC 8252](https://tools.ietf.org/html/rfc8252)).
+    /// * `auth_url` -  Authorization endpoint: used by the client to obtain authorization from
+    ///   the resource owner via user-agent redirection. This URL is used in all standard OAuth2
+    ///   flows except the [Resource Owner Password Credentials
+    ///   Grant](https://tools.ietf.org/html/rfc6749#section-4.3) and the
+    ///   [Client Credentials Grant](https://tools.ietf.org/html/rfc6749#section-4.4).
+    /// * `token_url` - Token endpoint: used by the client to exchange an authorization grant
+    ///   (code) for an access token, typically with client authentication. This URL is used in
+    ///   all standard OAuth2 flows except the
+    ///   [Implicit Grant](https://tools.ietf.org/html/rfc6749#section-4.2). If this value is set
+    ///   to `None`, the `exchange_*` methods will return `Err(RequestTokenError::Other(_))`.
+    ///
+    pub fn new(
+        client_id: ClientId,
+        client_secret: Option<ClientSecret>,
+        auth_url: AuthUrl,
+        token_url: Option<TokenUrl>,
+    ) -> Self {
+        Client {
+            client_id,
+            client_secret,
+            auth_url,
+            auth_type: AuthType::BasicAuth,
+            token_url,
+            redirect_url: None,
+            introspect_url: None,
+            device_authorization_url: None,
+            revocation_url: None,
+            par_url: None,
+            backchannel_authentication_url: None,
+            phantom_te: PhantomData,
+            phantom_tr: PhantomData,
+            phantom_tt: PhantomData,
+            phantom_tir: PhantomData,
+        }
+    }
+
+    ///
+    /// Initializes an OAuth2 client from authorization server metadata obtained via
+    /// [`ProviderMetadata::discover`](discovery/struct.ProviderMetadata.html#method.discover).
+    ///
+    /// Every endpoint advertised by the authorization server is configured on the returned
+    /// client: the authorization and token endpoints, and the introspection, revocation, device
+    /// authorization, PAR and backchannel authentication endpoints if present. Clients using
+    /// mutual-TLS should use `from_metadata_mtls` instead.
+    ///
+    pub fn from_metadata<EF>(
+        provider_metadata: &ProviderMetadata<EF>,
+        client_id: ClientId,
+        client_secret: Option<ClientSecret>,
+    ) -> Self
+    where
+        EF: ExtraProviderMetadata,
+    {
+        Client {
+            introspect_url: provider_metadata.introspection_endpoint().cloned(),
+            device_authorization_url: provider_metadata.device_authorization_endpoint().cloned(),
+            revocation_url: provider_metadata.revocation_endpoint().cloned(),
+            par_url: provider_metadata
+                .pushed_authorization_request_endpoint()
+                .cloned(),
+            backchannel_authentication_url: provider_metadata
+                .backchannel_authentication_endpoint()
+                .cloned(),
+            ..Self::new(
+                client_id,
+                client_secret,
+                provider_metadata.authorization_endpoint().clone(),
+                provider_metadata.token_endpoint().cloned(),
+            )
+        }
+    }
+
+    ///
+    /// Initializes an OAuth2 client that connects to the authorization server using mutual-TLS
+    /// from authorization server metadata obtained via
+    /// [`ProviderMetadata::discover`](discovery/struct.ProviderMetadata.html#method.discover).
+    ///
+    /// This is equivalent to `from_metadata`, except that any endpoints with mutual-TLS aliases
+    /// advertised by the authorization server (`mtls_endpoint_aliases`) are replaced with their
+    /// aliases (see `set_mtls_endpoint_aliases`). Clients authenticating with a TLS client
+    /// certificate should also call `set_auth_type` with `AuthType::TlsClientAuth` or
+    /// `AuthType::SelfSignedTlsClientAuth`.
+    ///
+    pub fn from_metadata_mtls<EF>(
+        provider_metadata: &ProviderMetadata<EF>,
+        client_id: ClientId,
+        client_secret: Option<ClientSecret>,
+    ) -> Self
+    where
+        EF: ExtraProviderMetadata,
+    {
+        let client = Self::from_metadata(provider_metadata, client_id, client_secret);
+        match provider_metadata.mtls_endpoint_aliases() {
+            Some(aliases) => client.set_mtls_endpoint_aliases(aliases),
+            None => client,
+        }
+    }
+
+    ///
+    /// Initializes an OAuth2 client using the credentials returned by the authorization server
+    /// after dynamically registering the client (see `registration::ClientRegistrationRequest`).
+    ///
+    /// The client authentication type is derived from the registered
+    /// `token_endpoint_auth_method`, and the first registered redirect URI (if any) is used as
+    /// the redirect URL. An error is returned if the client was registered for `private_key_jwt`
+    /// authentication, which requires a signing key, or for an authentication method not
+    /// supported by this crate.
+    ///
+    pub fn from_registration<EF>(
+        registration: &ClientRegistrationResponse<EF>,
+        auth_url: AuthUrl,
+        token_url: Option<TokenUrl>,
+    ) -> Result<Self, ClientFromRegistrationError>
+    where
+        EF: ExtraClientMetadata,
+    {
+        let metadata = registration.metadata();
+        let auth_type = match metadata.token_endpoint_auth_method() {
+            // The default authentication method is client_secret_basic (see
+            // https://tools.ietf.org/html/rfc7591#section-2).
+            None => AuthType::BasicAuth,
+            Some(auth_method) => match auth_method.as_str() {
+                "client_secret_basic" => AuthType::BasicAuth,
+                "client_secret_post" | "none" => AuthType::RequestBody,
+                "client_secret_jwt" => AuthType::ClientSecretJwt,
+                "tls_client_auth" => AuthType::TlsClientAuth,
+                "self_signed_tls_client_auth" => AuthType::SelfSignedTlsClientAuth,
+                "private_key_jwt" => return Err(ClientFromRegistrationError::SigningKeyRequired),
+                _ => {
+                    return Err(ClientFromRegistrationError::UnsupportedAuthMethod(
+                        auth_method.clone(),
+                    ))
+                }
+            },
+        };
+
+        Ok(Client {
+            auth_type,
+            redirect_url: metadata
+                .redirect_uris()
+                .and_then(|redirect_uris| redirect_uris.first())
+                .cloned(),
+            ..Self::new(
+                registration.client_id().clone(),
+                registration.client_secret().cloned(),
+                auth_url,
+                token_url,
+            )
+        })
+    }
+
+    ///
+    /// Replaces the configured endpoints with their mutual-TLS aliases advertised by the
+    /// authorization server, as described in
+    /// [Section 5 of RFC 8705](https://tools.ietf.org/html/rfc8705#section-5).
+    ///
+    /// This should be used along with `AuthType::TlsClientAuth` or
+    /// `AuthType::SelfSignedTlsClientAuth`, or when requesting certificate-bound access tokens.
+    /// Endpoints without an alias are left unchanged.
+    ///
+    pub fn set_mtls_endpoint_aliases(mut self, aliases: &MtlsEndpointAliases) -> Self {
+        if let Some(token_url) = aliases.token_endpoint() {
+            self.token_url = Some(token_url.clone());
+        }
+        if let Some(revocation_url) = aliases.revocation_endpoint() {
+            self.revocation_url = Some(revocation_url.clone());
+        }
+        if let Some(introspect_url) = aliases.introspection_endpoint() {
+            self.introspect_url = Some(introspect_url.clone());
+        }
+        if let Some(device_authorization_url) = aliases.device_authorization_endpoint() {
+            self.device_authorization_url = Some(device_authorization_url.clone());
+        }
+        if let Some(par_url) = aliases.pushed_authorization_request_endpoint() {
+            self.par_url = Some(par_url.clone());
+        }
+        if let Some(backchannel_authentication_url) = aliases.backchannel_authentication_endpoint()
+        {
+            self.backchannel_authentication_url = Some(backchannel_authentication_url.clone());
+        }
+
+        self
+    }
+
+    ///
+    /// Configures the type of client authentication used for communicating with the authorization
+    /// server.
+    ///
+    /// The default is to use HTTP Basic authentication, as recommended in
+    /// [Section 2.3.1 of RFC 6749](https://tools.ietf.org/html/rfc6749#section-2.3.1).
+    ///
+    pub fn set_auth_type(mut self, auth_type: AuthType) -> Self {
+        self.auth_type = auth_type;
+
+        self
+    }
+
+    ///
+    /// Sets the the redirect URL used by the authorization endpoint.
+    ///
+    pub fn set_redirect_url(mut self, redirect_url: RedirectUrl) -> Self {
+        self.redirect_url = Some(redirect_url);
+
+        self
+    }
+
+    ///
+    /// Sets the introspect URL used by the introspect endpoint.
+    ///
+    pub fn set_introspection_url(mut self, introspect_url: IntrospectUrl) -> Self {
+        self.introspect_url = Some(introspect_url);
+
+        self
+    }
+
+    ///
+    /// Sets the the device authorization URL used by the device authorization endpoint.
+    /// Used for Device Code Flow, as per [RFC 8628](https://tools.ietf.org/html/rfc8628).
+    ///
+    pub fn set_device_authorization_url(
+        mut self,
+        device_authorization_url: DeviceAuthorizationUrl,
+    ) -> Self {
+        self.device_authorization_url = Some(device_authorization_url);
+
+        self
+    }
+
+    ///
+    /// Sets the revocation URL used by the token revocation endpoint.
+    /// Used for token revocation, as per [RFC 7009](https://tools.ietf.org/html/rfc7009).
+    ///
+    pub fn set_revocation_url(mut self, revocation_url: RevocationUrl) -> Self {
+        self.revocation_url = Some(revocation_url);
+
+        self
+    }
+
+    ///
+    /// Sets the URL of the pushed authorization request (PAR) endpoint.
+    /// Used for pushed authorization requests, as per
+    /// [RFC 9126](https://tools.ietf.org/html/rfc9126).
+    ///
+    pub fn set_par_url(mut self, par_url: ParUrl) -> Self {
+        self.par_url = Some(par_url);
+
+        self
+    }
+
+    ///
+    /// Sets the URL of the backchannel authentication endpoint.
+    /// Used for Client-Initiated Backchannel Authentication, as per
+    /// [CIBA](https://openid.net/specs/openid-client-initiated-backchannel-authentication-core-1_0.html).
+    ///
+    pub fn set_backchannel_authentication_url(
+        mut self,
+        backchannel_authentication_url: BackchannelAuthenticationUrl,
+    ) -> Self {
+        self.backchannel_authentication_url = Some(backchannel_authentication_url);
+
+        self
+    }
+
+    ///
+    /// Generates an authorization URL for a new authorization request.
+    ///
+    /// # Arguments
+    ///
+    /// * `state_fn` - A function that returns an opaque value used by the client to maintain state
+    ///   between the request and callback. The authorization server includes this value when
+    ///   redirecting the user-agent back to the client.
+    ///
+    /// # Security Warning
+    ///
+    /// Callers should use a fresh, unpredictable `state` for each authorization request and verify
+    /// that this value matches the `state` parameter passed by the authorization server to the
+    /// redirect URI. Doing so mitigates
+    /// [Cross-Site Request Forgery](https://tools.ietf.org/html/rfc6749#section-10.12)
+    ///  attacks. To disable CSRF protections (NOT recommended), use `insecure::authorize_url`
+    ///  instead.
+    ///
+    pub fn authorize_url<S>(&self, state_fn: S) -> AuthorizationRequest
+    where
+        S: FnOnce() -> CsrfToken,
+    {
+        AuthorizationRequest {
+            auth_url: &self.auth_url,
+            client_id: &self.client_id,
+            authorization_details: rar::AuthorizationDetails::default(),
+            extra_params: Vec::new(),
+            resources: Vec::new(),
+            pkce_challenge: None,
+            redirect_url: self.redirect_url.as_ref().map(Cow::Borrowed),
+            response_type: "code".into(),
+            scopes: Vec::new(),
+            state: state_fn(),
+        }
+    }
+
+    ///
+    /// Pushes an authorization request to the pushed authorization request (PAR) endpoint,
+    /// authenticating the client, instead of passing its parameters in the authorization URL.
+    ///
+    /// Upon success, the request returns a short authorization URL that contains only the
+    /// `client_id` and `request_uri` parameters. This prevents the authorization request
+    /// parameters (e.g., the PKCE challenge) from leaking via the user-agent.
+    ///
+    /// See https://tools.ietf.org/html/rfc9126#section-2
+    ///
+    pub fn push_authorization_request<'a>(
+        &'a self,
+        authorization_request: AuthorizationRequest<'a>,
+    ) -> PushedAuthorizationRequest<'a, TE> {
+        PushedAuthorizationRequest {
+            auth_type: &self.auth_type,
+            client_id: &self.client_id,
+            client_secret: self.client_secret.as_ref(),
+            par_url: self.par_url.as_ref(),
+            authorization_request,
+            _phantom: PhantomData,
+        }
+    }
+
+    ///
+    /// Exchanges a code produced by a successful authorization process with an access token.
+    ///
+    /// Acquires ownership of the `code` because authorization codes may only be used once to
+    /// retrieve an access token from the authorization server.
+    ///
+    /// See https://tools.ietf.org/html/rfc6749#section-4.1.3
+    ///
+    pub fn exchange_code(&self, code: AuthorizationCode) -> CodeTokenRequest<TE, TR, TT> {
+        CodeTokenRequest {
+            auth_type: &self.auth_type,
+            client_id: &self.client_id,
+            client_secret: self.client_secret.as_ref(),
+            code,
+            dpop_key: None,
+            authorization_details: rar::AuthorizationDetails::default(),
+            extra_params: Vec::new(),
+            resources: Vec::new(),
+            pkce_verifier: None,
+            token_url: self.token_url.as_ref(),
+            redirect_url: self.redirect_url.as_ref(),
+            _phantom: PhantomData,
+        }
+    }
+
+    ///
+    /// Requests an access token for the *password* grant type.
+    ///
+    /// See https://tools.ietf.org/html/rfc6749#section-4.3.2
+    ///
+    pub fn exchange_password<'a, 'b>(
+        &'a self,
+        username: &'b ResourceOwnerUsername,
+        password: &'b ResourceOwnerPassword,
+    ) -> PasswordTokenRequest<'b, TE, TR, TT>
+    where
+        'a: 'b,
+    {
+        PasswordTokenRequest::<'b> {
+            auth_type: &self.auth_type,
+            client_id: &self.client_id,
+            client_secret: self.client_secret.as_ref(),
+            username,
+            password,
+            dpop_key: None,
+            extra_params: Vec::new(),
+            resources: Vec::new(),
+            scopes: Vec::new(),
+            token_url: self.token_url.as_ref(),
+            _phantom: PhantomData,
+        }
+    }
+
+    ///
+    /// Requests an access token for the *client credentials* grant type.
+    ///
+    /// See https://tools.ietf.org/html/rfc6749#section-4.4.2
+    ///
+    pub fn exchange_client_credentials(&self) -> ClientCredentialsTokenRequest<TE, TR, TT> {
+        ClientCredentialsTokenRequest {
+            auth_type: &self.auth_type,
+            client_id: &self.client_id,
+            client_secret: self.client_secret.as_ref(),
+            dpop_key: None,
+            authorization_details: rar::AuthorizationDetails::default(),
+            extra_params: Vec::new(),
+            resources: Vec::new(),
+            scopes: Vec::new(),
+            token_url: self.token_url.as_ref(),
+            _phantom: PhantomData,
+        }
+    }
+
+    ///
+    /// Exchanges a refresh token for an access token
+    ///
+    /// See https://tools.ietf.org/html/rfc6749#section-6
+    ///
+    pub fn exchange_refresh_token<'a, 'b>(
+        &'a self,
+        refresh_token: &'b RefreshToken,
+    ) -> RefreshTokenRequest<'b, TE, TR, TT>
+    where
+        'a: 'b,
+    {
+        RefreshTokenRequest {
+            auth_type: &self.auth_type,
+            client_id: &self.client_id,
+            client_secret: self.client_secret.as_ref(),
+            dpop_key: None,
+            authorization_details: rar::AuthorizationDetails::default(),
+            extra_params: Vec::new(),
+            resources: Vec::new(),
+            refresh_token,
+            scopes: Vec::new(),
+            token_url: self.token_url.as_ref(),
+            _phantom: PhantomData,
+        }
+    }
+
+    ///
+    /// Requests an access token for the *JWT bearer* grant type, using a signed JWT as an
+    /// authorization grant (e.g., for service accounts).
+    ///
+    /// The assertion may be built using `jwt::JwtBearerAssertionBuilder` or
+    /// `jwt::ServiceAccountKey`.
+    ///
+    /// See https://tools.ietf.org/html/rfc7523#section-2.1
+    ///
+    pub fn exchange_jwt_bearer<'a, 'b>(
+        &'a self,
+        assertion: &'b JwtBearerAssertion,
+    ) -> JwtBearerTokenRequest<'b, TE, TR, TT>
+    where
+        'a: 'b,
+    {
+        JwtBearerTokenRequest {
+            auth_type: &self.auth_type,
+            client_id: &self.client_id,
+            client_secret: self.client_secret.as_ref(),
+            assertion,
+            dpop_key: None,
+            extra_params: Vec::new(),
+            scopes: Vec::new(),
+            token_url: self.token_url.as_ref(),
+            _phantom: PhantomData,
+        }
+    }
+
+    ///
+    /// Exchanges a security token (e.g., an access token issued to the end-user) for a new
+    /// token, such as a downscoped access token for a different audience.
+    ///
+    /// See https://tools.ietf.org/html/rfc8693#section-2.1
+    ///
+    pub fn exchange_token<'a, 'b>(
+        &'a self,
+        subject_token: &'b SubjectToken,
+        subject_token_type: TokenTypeIdentifier,
+    ) -> TokenExchangeRequest<'b, TE, TT>
+    where
+        'a: 'b,
+    {
+        TokenExchangeRequest {
+            auth_type: &self.auth_type,
+            client_id: &self.client_id,
+            client_secret: self.client_secret.as_ref(),
+            dpop_key: None,
+            extra_params: Vec::new(),
+            subject_token,
+            subject_token_type,
+            actor_token: None,
+            audiences: Vec::new(),
+            requested_token_type: None,
+            resources: Vec::new(),
+            scopes: Vec::new(),
+            token_url: self.token_url.as_ref(),
+            _phantom: PhantomData,
+        }
+    }
+
+    ///
+    /// Perform a device authorization request as per
+    /// https://tools.ietf.org/html/rfc8628#section-3.1
+    ///
+    pub fn exchange_device_code(&self) -> DeviceAuthorizationRequest<TE> {
+        DeviceAuthorizationRequest {
+            auth_type: &self.auth_type,
+            client_id: &self.client_id,
+            client_secret: self.client_secret.as_ref(),
+            extra_params: Vec::new(),
+            resources: Vec::new(),
+            scopes: Vec::new(),
+            device_authorization_url: self.device_authorization_url.as_ref(),
+            _phantom: PhantomData,
+        }
+    }
+
+    ///
+    /// Perform a device access token request as per
+    /// https://tools.ietf.org/html/rfc8628#section-3.4
+    ///
+    pub fn exchange_device_access_token<'a, 'b, 'c, EF>(
+        &'a self,
+        auth_response: &'b DeviceAuthorizationResponse<EF>,
+    ) -> DeviceAccessTokenRequest<'b, 'c, TR, TT, EF>
+    where
+        'a: 'b,
+        EF: ExtraDeviceAuthorizationFields,
+    {
+        DeviceAccessTokenRequest {
+            auth_type: &self.auth_type,
+            client_id: &self.client_id,
+            client_secret: self.client_secret.as_ref(),
+            dpop_key: None,
+            extra_params: Vec::new(),
+            token_url: self.token_url.as_ref(),
+            dev_auth_resp: auth_response,
+            time_fn: Arc::new(Utc::now),
+            _phantom: PhantomData,
+        }
+    }
+
+    ///
+    /// Perform a Client-Initiated Backchannel Authentication (CIBA) request as per
+    /// https://openid.net/specs/openid-client-initiated-backchannel-authentication-core-1_0.html#rfc.section.7.1
+    ///
+    pub fn backchannel_authentication(&self) -> BackchannelAuthenticationRequest<'_> {
+        BackchannelAuthenticationRequest {
+            auth_type: &self.auth_type,
+            client_id: &self.client_id,
+            client_secret: self.client_secret.as_ref(),
+            backchannel_authentication_url: self.backchannel_authentication_url.as_ref(),
+            binding_message: None,
+            client_notification_token: None,
+            extra_params: Vec::new(),
+            id_token_hint: None,
+            login_hint: None,
+            requested_expiry: None,
+            scopes: Vec::new(),
+        }
+    }
+
+    ///
+    /// Perform a CIBA token request for a backchannel authentication response as per
+    /// https://openid.net/specs/openid-client-initiated-backchannel-authentication-core-1_0.html#rfc.section.10.1
+    ///
+    /// In poll mode, use `BackchannelTokenRequest::request` to poll the token endpoint until the
+    /// end-user has authenticated.
+    ///
+    pub fn exchange_backchannel_authentication<'a, 'b, 'c>(
+        &'a self,
+        auth_response: &'b BackchannelAuthenticationResponse,
+    ) -> BackchannelTokenRequest<'b, 'c, TR, TT>
+    where
+        'a: 'b,
+    {
+        BackchannelTokenRequest {
+            expires_in: Some(auth_response.expires_in()),
+            interval: auth_response.interval(),
+            ..self.exchange_auth_req_id(auth_response.auth_req_id())
+        }
+    }
+
+    ///
+    /// Perform a CIBA token request for the given `auth_req_id` as per
+    /// https://openid.net/specs/openid-client-initiated-backchannel-authentication-core-1_0.html#rfc.section.10.1
+    ///
+    /// This is typically called by the client notification endpoint in ping mode, after
+    /// receiving a `ciba::BackchannelPingNotification`, using
+    /// `BackchannelTokenRequest::request_once`.
+    ///
+    pub fn exchange_auth_req_id<'a, 'b, 'c>(
+        &'a self,
+        auth_req_id: &'b AuthReqId,
+    ) -> BackchannelTokenRequest<'b, 'c, TR, TT>
+    where
+        'a: 'b,
+    {
+        BackchannelTokenRequest {
+            auth_type: &self.auth_type,
+            client_id: &self.client_id,
+            client_secret: self.client_secret.as_ref(),
+            dpop_key: None,
+            extra_params: Vec::new(),
+            token_url: self.token_url.as_ref(),
+            auth_req_id,
+            expires_in: None,
+            interval: Duration::from_secs(5),
+            time_fn: Arc::new(Utc::now),
+            _phantom: PhantomData,
+        }
+    }
+
+    ///
+    /// Exchanges a code produced by a successful authorization process with an access token.
+    ///
+    /// Acquires ownership of the `code` because authorization codes may only be used once to
+    /// retrieve an access token from the authorization server.
+    ///
+    /// See https://tools.ietf.org/html/rfc6749#section-4.1.3
+    ///
+    pub fn introspect<'a>(&'a self, token: &'a AccessToken) -> IntrospectRequest<'a, TE, TIR, TT> {
+        IntrospectRequest {
+            auth_type: &self.auth_type,
+            client_id: &self.client_id,
+            client_secret: self.client_secret.as_ref(),
+            extra_params: Vec::new(),
+            introspect_url: self.introspect_url.as_ref(),
+            token,
+            token_type_hint: None,
+            _phantom: PhantomData,
+        }
+    }
+
+    ///
+    /// Revokes an access or refresh token, such as when the end-user logs out.
+    ///
+    /// The `token_type_hint` parameter is derived from the type of the token being revoked.
+    ///
+    /// See https://tools.ietf.org/html/rfc7009#section-2.1
+    ///
+    pub fn revoke_token<'a, RT>(&'a self, token: &'a RT) -> RevocationRequest<'a, RT>
+    where
+        RT: RevocableToken,
+    {
+        RevocationRequest {
+            auth_type: &self.auth_type,
+            client_id: &self.client_id,
+            client_secret: self.client_secret.as_ref(),
+            extra_params: Vec::new(),
+            revocation_url: self.revocation_url.as_ref(),
+            token,
+        }
+    }
+}
+
+///
+/// A request to the authorization endpoint
+///
+#[derive(Debug)]
+pub struct AuthorizationRequest<'a> {
+    auth_url: &'a AuthUrl,
+    client_id: &'a ClientId,
+    authorization_details: rar::AuthorizationDetails,
+    extra_params: Vec<(Cow<'a, str>, Cow<'a, str>)>,
+    resources: Vec<Cow<'a, ResourceUrl>>,
+    pkce_challenge: Option<PkceCodeChallenge>,
+    redirect_url: Option<Cow<'a, RedirectUrl>>,
+    response_type: Cow<'a, str>,
+    scopes: Vec<Cow<'a, Scope>>,
+    state: CsrfToken,
+}
+impl<'a> AuthorizationRequest<'a> {
+    ///
+    /// Appends a new scope to the authorization URL.
+    ///
+    pub fn add_scope(mut self, scope: Scope) -> Self {
+        self.scopes.push(Cow::Owned(scope));
+        self
+    }
+
+    ///
+    /// Appends an extra param to the authorization URL.
+    ///
+    /// This method allows extensions to be used without direct support from
+    /// this crate. If `name` conflicts with a parameter managed by this crate, the
+    /// behavior is undefined. In particular, do not set parameters defined by
+    /// [RFC 6749](https://tools.ietf.org/html/rfc6749) or
+    /// [RFC 7636](https://tools.ietf.org/html/rfc7636).
+    ///
+    /// # Security Warning
+    ///
+    /// Callers should follow the security recommendations for any OAuth2 extensions used with
+    /// this function, which are beyond the scope of
+    /// [RFC 6749](https://tools.ietf.org/html/rfc6749).
+    ///
+    pub fn add_extra_param<N, V>(mut self, name: N, value: V) -> Self
+    where
+        N: Into<Cow<'a, str>>,
+        V: Into<Cow<'a, str>>,
+    {
+        self.extra_params.push((name.into(), value.into()));
+        self
+    }
+
+    ///
+    /// Appends the URI of a target service or resource where the client intends to use the
+    /// requested token, as defined in [RFC 8707](https://tools.ietf.org/html/rfc8707).
+    ///
+    /// This method may be called multiple times to request access to several resources.
+    ///
+    pub fn add_resource(mut self, resource: ResourceUrl) -> Self {
+        self.resources.push(Cow::Owned(resource));
+        self
+    }
+
+    ///
+    /// Appends an authorization details object to the `authorization_details` parameter, as
+    /// defined in [RFC 9396](https://tools.ietf.org/html/rfc9396).
+    ///
+    /// If the authorization details object cannot be serialized to JSON, `try_url` returns an
+    /// error, as do pushed authorization requests and request objects. `url` omits any such
+    /// objects.
+    ///
+    pub fn add_authorization_detail<AD>(mut self, authorization_detail: &AD) -> Self
+    where
+        AD: AuthorizationDetail,
+    {
+        self.authorization_details.push(authorization_detail);
+        self
+    }
+
+    ///
+    /// Enables the [Implicit Grant](https://tools.ietf.org/html/rfc6749#section-4.2) flow.
+    ///
+    pub fn use_implicit_flow(mut self) -> Self {
+        self.response_type = "token".into();
+        self
+    }
+
+    ///
+    /// Enables custom flows other than the `code` and `token` (implicit flow) grant.
+    ///
+    pub fn set_response_type(mut self, response_type: &ResponseType) -> Self {
+        self.response_type = (&**response_type).to_owned().into();
+        self
+    }
+
+    ///
+    /// Enables the use of [Proof Key for Code Exchange](https://tools.ietf.org/html/rfc7636)
+    /// (PKCE).
+    ///
+    /// PKCE is *highly recommended* for all public clients (i.e., those for which there
+    /// is no client secret or for which the client secret is distributed with the client,
+    /// such as in a native, mobile app, or browser app).
+    ///
+    pub fn set_pkce_challenge(mut self, pkce_code_challenge: PkceCodeChallenge) -> Self {
+        self.pkce_challenge = Some(pkce_code_challenge);
+        self
+    }
+
+    ///
+    /// Overrides the `redirect_url` to the one specified.
+    ///
+    pub fn set_redirect_url(mut self, redirect_url: Cow<'a, RedirectUrl>) -> Self {
+        self.redirect_url = Some(redirect_url);
+        self
+    }
+
+    ///
+    /// Passes the parameters of this authorization request in a signed request object, as
+    /// defined in [RFC 9101](https://tools.ietf.org/html/rfc9101).
+    ///
+    /// # Arguments
+    ///
+    /// * `audience` - Value of the `aud` claim, typically the issuer identifier of the
+    ///   authorization server.
+    /// * `signing_key` - Key used to sign the request object, which must be registered with
+    ///   the authorization server.
+    ///
+    pub fn request_object(
+        self,
+        audience: String,
+        signing_key: &'a dyn JwsSigningKey,
+    ) -> RequestObjectBuilder<'a> {
+        RequestObjectBuilder::new(self, audience, signing_key)
+    }
+
+    ///
+    /// Returns the full authorization URL and CSRF state for this authorization
+    /// request.
+    ///
+    pub fn url(self) -> (Url, CsrfToken) {
+        let mut url: Url = self.auth_url.url().to_owned();
+        url.query_pairs_mut().extend_pairs(self.params());
+
+        (url, self.state)
+    }
+
+    ///
+    /// Returns the full authorization URL and CSRF state for this authorization
+    /// request, or an error if any of the authorization details objects added using
+    /// `add_authorization_detail` could not be serialized.
+    ///
+    pub fn try_url(mut self) -> Result<(Url, CsrfToken), serde_json::Error> {
+        match self.authorization_details.take_error() {
+            Some(err) => Err(err),
+            None => Ok(self.url()),
+        }
+    }
+
+    fn params(&self) -> Vec<(&str, Cow<'_, str>)> {
+        let mut pairs: Vec<(&str, Cow<str>)> = vec![
+            ("response_type", Cow::Borrowed(self.response_type.as_ref())),
+            ("client_id", Cow::Borrowed(self.client_id)),
+            ("state", Cow::Borrowed(self.state.secret())),
+        ];
+
+        if let Some(ref pkce_challenge) = self.pkce_challenge {
+            pairs.push(("code_challenge", Cow::Borrowed(pkce_challenge.as_str())));
+            pairs.push((
+                "code_challenge_method",
+                Cow::Borrowed(pkce_challenge.method().as_str()),
+            ));
+        }
+
+        if let Some(ref redirect_url) = self.redirect_url {
+            pairs.push(("redirect_uri", Cow::Borrowed(redirect_url.as_str())));
+        }
+
+        if !self.scopes.is_empty() {
+            let scopes = self
+                .scopes
+                .iter()
+                .map(|s| s.to_string())
+                .collect::<Vec<_>>()
+                .join(" ");
+            pairs.push(("scope", Cow::Owned(scopes)));
+        }
+
+        pairs.extend(
+            self.resources
+                .iter()
+                .map(|resource| ("resource", Cow::Borrowed(resource.as_str()))),
+        );
+
+        if let Some(authorization_details) = self.authorization_details.param() {
+            pairs.push(("authorization_details", Cow::Owned(authorization_details)));
+        }
+
+        pairs.extend(
+            self.extra_params
+                .iter()
+                .map(|(k, v)| (k.as_ref(), Cow::Borrowed(v.as_ref()))),
+        );
+        pairs
+    }
+}
+
+///
+/// A request to push an authorization request to the pushed authorization request (PAR)
+/// endpoint.
+///
+/// See https://tools.ietf.org/html/rfc9126#section-2.1.
+///
+#[derive(Debug)]
+pub struct PushedAuthorizationRequest<'a, TE>
+where
+    TE: ErrorResponse,
+{
+    auth_type: &'a AuthType,
+    client_id: &'a ClientId,
+    client_secret: Option<&'a ClientSecret>,
+    par_url: Option<&'a ParUrl>,
+    authorization_request: AuthorizationRequest<'a>,
+    _phantom: PhantomData<TE>,
+}
+
+impl<'a, TE> PushedAuthorizationRequest<'a, TE>
+where
+    TE: ErrorResponse + 'static,
+{
+    ///
+    /// Synchronously sends the request to the authorization server and awaits a response.
+    ///
+    /// Returns the short authorization URL, the CSRF state of the authorization request, and
+    /// the response of the PAR endpoint.
+    ///
+    pub fn request<F, RE>(
+        self,
+        http_client: F,
+    ) -> Result<(Url, CsrfToken, PushedAuthorizationResponse), RequestTokenError<RE, TE>>
+    where
+        F: SyncHttpClient<Error = RE>,
+        RE: Error + 'static,
+    {
+        let http_response = http_client
+            .call(self.prepare_request()?)
+            .map_err(RequestTokenError::Request)?;
+        self.process_response(http_response)
+    }
+
+    ///
+    /// Asynchronously sends the request to the authorization server and awaits a response.
+    ///
+    /// Returns the short authorization URL, the CSRF state of the authorization request, and
+    /// the response of the PAR endpoint.
+    ///
+    pub async fn request_async<C, RE>(
+        self,
+        http_client: C,
+    ) -> Result<(Url, CsrfToken, PushedAuthorizationResponse), RequestTokenError<RE, TE>>
+    where
+        C: AsyncHttpClient<Error = RE>,
+        RE: Error + 'static,
+    {
+        let http_request = self.prepare_request()?;
+        let http_response = http_client
+            .call(http_request)
+            .await
+            .map_err(RequestTokenError::Request)?;
+        self.process_response(http_response)
+    }
+
+    fn prepare_request<RE>(&self) -> Result<HttpRequest, RequestTokenError<RE, TE>>
+    where
+        RE: Error + 'static,
+    {
+        self.authorization_request.authorization_details.check()?;
+        let params = self.authorization_request.params();
+        // The client_id parameter is added by endpoint_request() for authentication methods
+        // other than HTTP Basic auth.
+        let params = params
+            .iter()
+            .filter(|(name, _)| {
+                *name != "client_id" || matches!(self.auth_type, AuthType::BasicAuth)
+            })
+            .map(|(name, value)| (*name, value.as_ref()))
+            .collect::<Vec<_>>();
+
+        endpoint_request(
+            self.auth_type,
+            self.client_id,
+            self.client_secret,
+            &[],
+            None,
+            None,
+            self.par_url
+                .ok_or_else(|| RequestTokenError::Other("no par_url provided".to_string()))?
+                .url(),
+            params,
+        )
+    }
+
+    fn process_response<RE>(
+        self,
+        http_response: HttpResponse,
+    ) -> Result<(Url, CsrfToken, PushedAuthorizationResponse), RequestTokenError<RE, TE>>
+    where
+        RE: Error + 'static,
+    {
+        // Section 2.2 of RFC 9126 specifies a 201 (Created) status code, but some authorization
+        // servers respond with 200 (OK).
+        if http_response.status_code != StatusCode::CREATED {
+            check_response_status(&http_response)?;
+        }
+        let par_response: PushedAuthorizationResponse = endpoint_response_body(http_response)?;
+
+        let mut url: Url = self.authorization_request.auth_url.url().to_owned();
+        url.query_pairs_mut()
+            .append_pair("client_id", self.client_id)
+            .append_pair("request_uri", par_response.request_uri());
+
+        Ok((url, self.authorization_request.state, par_response))
+    }
+}
+
+///
+/// An HTTP request.
+///
+#[derive(Clone, Debug)]
+pub struct HttpRequest {
+    // These are all owned values so that the request can safely be passed between
+    // threads.
+    /// URL to which the HTTP request is being made.
+    pub url: Url,
+    /// HTTP request method for this request.
+    pub method: http::method::Method,
+    /// HTTP request headers to send.
+    pub headers: HeaderMap,
+    /// HTTP request body (typically for POST requests only).
+    pub body: Vec<u8>,
+}
+
+///
+/// An HTTP response.
+///
+#[derive(Clone, Debug)]
+pub struct HttpResponse {
+    /// HTTP status code returned by the server.
+    pub status_code: http::status::StatusCode,
+    /// HTTP response headers returned by the server.
+    pub headers: HeaderMap,
+    /// HTTP response body returned by the server.
+    pub body: Vec<u8>,
+}
+
+///
+/// A synchronous (blocking) HTTP client.
+///
+/// This trait is implemented for any `FnOnce(HttpRequest) -> Result<HttpResponse, RE>`
+/// (including functions such as `reqwest::http_client` and boxed closures), as well as for the
+/// reusable clients provided by this crate (e.g., `reqwest::ReqwestClient`) and references to
+/// them. Stateful clients or middleware may implement it directly.
+///
+/// Requests that may need to be sent more than once (e.g., token requests, which are retried
+/// when the authorization server requires a DPoP nonce) require the HTTP client to be callable
+/// through a shared reference. This is the case for any `Fn` closure, since `&F` implements
+/// `FnOnce`. Custom clients should also implement this trait for references to themselves.
+///
+pub trait SyncHttpClient {
+    ///
+    /// Error type returned by failed HTTP requests.
+    ///
+    type Error: Error + 'static;
+
+    ///
+    /// Sends the given HTTP request and returns its response.
+    ///
+    fn call(self, request: HttpRequest) -> Result<HttpResponse, Self::Error>;
+}
+impl<F, RE> SyncHttpClient for F
+where
+    F: FnOnce(HttpRequest) -> Result<HttpResponse, RE>,
+    RE: Error + 'static,
+{
+    type Error = RE;
+
+    fn call(self, request: HttpRequest) -> Result<HttpResponse, RE> {
+        self(request)
+    }
+}
+
+///
+/// An asynchronous HTTP client.
+///
+/// This trait is implemented for any `FnOnce(HttpRequest) -> F` returning a `Send` future `F`
+/// that resolves to a `Result<HttpResponse, RE>` (including functions such as
+/// `reqwest::async_http_client` and boxed closures), as well as for the reusable clients provided
+/// by this crate (e.g., `reqwest::AsyncReqwestClient`) and references to them. Requiring `Send`
+/// futures allows the futures returned by the `request_async` methods of this crate to be
+/// spawned on multi-threaded runtimes.
+///
+/// As with `SyncHttpClient`, requests that may need to be sent more than once require the HTTP
+/// client to be callable through a shared reference.
+///
+pub trait AsyncHttpClient {
+    ///
+    /// Error type returned by failed HTTP requests.
+    ///
+    type Error: Error + 'static;
+
+    ///
+    /// Future returned by `call`.
+    ///
+    type Future: Future<Output = Result<HttpResponse, Self::Error>> + Send;
+
+    ///
+    /// Sends the given HTTP request and returns a future resolving to its response.
+    ///
+    fn call(self, request: HttpRequest) -> Self::Future;
+}
+impl<C, F, RE> AsyncHttpClient for C
+where
+    C: FnOnce(HttpRequest) -> F,
+    F: Future<Output = Result<HttpResponse, RE>> + Send,
+    RE: Error + 'static,
+{
+    type Error = RE;
+    type Future = F;
+
+    fn call(self, request: HttpRequest) -> F {
+        self(request)
+    }
+}
+
+///
+/// A request to exchange an authorization code for an access token.
+///
+/// See https://tools.ietf.org/html/rfc6749#section-4.1.3.
+///
+#[derive(Debug)]
+pub struct CodeTokenRequest<'a, TE, TR, TT>
+where
+    TE: ErrorResponse,
+    TR: TokenResponse<TT>,
+    TT: TokenType,
+{
+    auth_type: &'a AuthType,
+    client_id: &'a ClientId,
+    client_secret: Option<&'a ClientSecret>,
+    code: AuthorizationCode,
+    dpop_key: Option<&'a dyn DpopKey>,
+    authorization_details: rar::AuthorizationDetails,
+    extra_params: Vec<(Cow<'a, str>, Cow<'a, str>)>,
+    resources: Vec<Cow<'a, ResourceUrl>>,
+    pkce_verifier: Option<PkceCodeVerifier>,
+    token_url: Option<&'a TokenUrl>,
+    redirect_url: Option<&'a RedirectUrl>,
+    _phantom: PhantomData<(TE, TR, TT)>,
+}
+impl<'a, TE, TR, TT> CodeTokenRequest<'a, TE, TR, TT>
+where
+    TE: ErrorResponse + 'static,
+    TR: TokenResponse<TT>,
+    TT: TokenType,
+{
+    ///
+    /// Appends an extra param to the token request.
+    ///
+    /// This method allows extensions to be used without direct support from
+    /// this crate. If `name` conflicts with a parameter managed by this crate, the
+    /// behavior is undefined. In particular, do not set parameters defined by
+    /// [RFC 6749](https://tools.ietf.org/html/rfc6749) or
+    /// [RFC 7636](https://tools.ietf.org/html/rfc7636).
+    ///
+    /// # Security Warning
+    ///
+    /// Callers should follow the security recommendations for any OAuth2 extensions used with
+    /// this function, which are beyond the scope of
+    /// [RFC 6749](https://tools.ietf.org/html/rfc6749).
+    ///
+    pub fn add_extra_param<N, V>(mut self, name: N, value: V) -> Self
+    where
+        N: Into<Cow<'a, str>>,
+        V: Into<Cow<'a, str>>,
+    {
+        self.extra_params.push((name.into(), value.into()));
+        self
+    }
+
+    ///
+    /// Appends the URI of a target service or resource where the client intends to use the
+    /// requested token, as defined in [RFC 8707](https://tools.ietf.org/html/rfc8707).
+    ///
+    /// This method may be called multiple times to request access to several resources.
+    ///
+    pub fn add_resource(mut self, resource: ResourceUrl) -> Self {
+        self.resources.push(Cow::Owned(resource));
+        self
+    }
+
+    ///
+    /// Appends an authorization details object to the `authorization_details` parameter, as
+    /// defined in [RFC 9396](https://tools.ietf.org/html/rfc9396).
+    ///
+    /// If the authorization details object cannot be serialized to JSON, sending the request
+    /// returns `RequestTokenError::Other`.
+    ///
+    pub fn add_authorization_detail<AD>(mut self, authorization_detail: &AD) -> Self
+    where
+        AD: AuthorizationDetail,
+    {
+        self.authorization_details.push(authorization_detail);
+        self
+    }
+
+    ///
+    /// Sets the key used to sign a DPoP proof for this request, which binds the issued access
+    /// token to the key ([RFC 9449](https://tools.ietf.org/html/rfc9449)).
+    ///
+    /// If the authorization server requires a nonce, the request is automatically retried with
+    /// the nonce provided in its `DPoP-Nonce` response header.
+    ///
+    pub fn set_dpop_key(mut self, dpop_key: &'a dyn DpopKey) -> Self {
+        self.dpop_key = Some(dpop_key);
+        self
+    }
+
+    ///
+    /// Completes the [Proof Key for Code Exchange](https://tools.ietf.org/html/rfc7636)
+    /// (PKCE) protocol flow.
+    ///
+    /// This method must be called if `set_pkce_challenge` was used during the authorization
+    /// request.
+    ///
+    pub fn set_pkce_verifier(mut self, pkce_verifier: PkceCodeVerifier) -> Self {
+        self.pkce_verifier = Some(pkce_verifier);
+        self
+    }
+
+    fn prepare_request<RE>(self) -> Result<HttpRequest, RequestTokenError<RE, TE>>
+    where
+        RE: Error + 'static,
+    {
+        let mut params = vec![
+            ("grant_type", "authorization_code"),
+            ("code", self.code.secret()),
+        ];
+        if let Some(ref pkce_verifier) = self.pkce_verifier {
+            params.push(("code_verifier", pkce_verifier.secret()));
+        }
+        params.extend(
+            self.resources
+                .iter()
+                .map(|resource| ("resource", resource.as_str())),
+        );
+        self.authorization_details.check()?;
+        let authorization_details = self.authorization_details.param();
+        if let Some(ref authorization_details) = authorization_details {
+            params.push(("authorization_details", authorization_details));
+        }
+
+        endpoint_request(
+            self.auth_type,
+            self.client_id,
+            self.client_secret,
+            &self.extra_params,
+            self.redirect_url,
+            None,
+            self.token_url
+                .ok_or_else(|| RequestTokenError::Other("no token_url provided".to_string()))?
+                .url(),
+            params,
+        )
+    }
+
+    ///
+    /// Synchronously sends the request to the authorization server and awaits a response.
+    ///
+    pub fn request<F, RE>(self, http_client: F) -> Result<TR, RequestTokenError<RE, TE>>
+    where
+        for<'c> &'c F: SyncHttpClient<Error = RE>,
+        RE: Error + 'static,
+    {
+        let dpop_key = self.dpop_key;
+        send_token_request(&http_client, self.prepare_request()?, dpop_key)
+            .and_then(endpoint_response)
+    }
+
+    ///
+    /// Asynchronously sends the request to the authorization server and awaits a response.
+    ///
+    pub async fn request_async<C, RE>(self, http_client: C) -> Result<TR, RequestTokenError<RE, TE>>
+    where
+        for<'c> &'c C: AsyncHttpClient<Error = RE>,
+        RE: Error + 'static,
+    {
+        let dpop_key = self.dpop_key;
+        let http_request = self.prepare_request()?;
+        let http_response = send_token_request_async(&http_client, http_request, dpop_key).await?;
+        endpoint_response(http_response)
+    }
+}
+
+///
+/// A request to exchange a refresh token for an access token.
+///
+/// See https://tools.ietf.org/html/rfc6749#section-6.
+///
+#[derive(Debug)]
+pub struct RefreshTokenRequest<'a, TE, TR, TT>
+where
+    TE: ErrorResponse,
+    TR: TokenResponse<TT>,
+    TT: TokenType,
+{
+    auth_type: &'a AuthType,
+    client_id: &'a ClientId,
+    client_secret: Option<&'a ClientSecret>,
+    dpop_key: Option<&'a dyn DpopKey>,
+    authorization_details: rar::AuthorizationDetails,
+    extra_params: Vec<(Cow<'a, str>, Cow<'a, str>)>,
+    resources: Vec<Cow<'a, ResourceUrl>>,
+    refresh_token: &'a RefreshToken,
+    scopes: Vec<Cow<'a, Scope>>,
+    token_url: Option<&'a TokenUrl>,
+    _phantom: PhantomData<(TE, TR, TT)>,
+}
+impl<'a, TE, TR, TT> RefreshTokenRequest<'a, TE, TR, TT>
+where
+    TE: ErrorResponse + 'static,
+    TR: TokenResponse<TT>,
+    TT: TokenType,
 {
     ///
-    /// Initializes an OAuth2 client with the fields common to most OAuth2 flows.
+    /// Appends an extra param to the token request.
     ///
-    /// # Arguments
+    /// This method allows extensions to be used without direct support from
+    /// this crate. If `name` conflicts with a parameter managed by this crate, the
+    /// behavior is undefined. In particular, do not set parameters defined by
+    /// [RFC 6749](https://tools.ietf.org/html/rfc6749) or
+    /// [RFC 7636](https://tools.ietf.org/html/rfc7636).
     ///
-    /// * `client_id` -  Client ID
-    /// * `client_secret` -  Optional client secret. A client secret is generally used for private
-    ///   (server-side) OAuth2 clients and omitted from public (client-side or native app) OAuth2
-    ///   clients (see [RFC 8252](https://tools.ietf.org/html/rfc8252)).
-    /// * `auth_url` -  Authorization endpoint: used by the client to obtain authorization from
-    ///   the resource owner via user-agent redirection. This URL is used in all standard OAuth2
-    ///   flows except the [Resource Owner Password Credentials
-    ///   Grant](https://tools.ietf.org/html/rfc6749#section-4.3) and the
-    ///   [Client Credentials Grant](https://tools.ietf.org/html/rfc6749#section-4.4).
-    /// * `token_url` - Token endpoint: used by the client to exchange an authorization grant
-    ///   (code) for an access token, typically with client authentication. This URL is used in
-    ///   all standard OAuth2 flows except the
-    ///   [Implicit Grant](https://tools.ietf.org/html/rfc6749#section-4.2). If this value is set
-    ///   to `None`, the `exchange_*` methods will return `Err(RequestTokenError::Other(_))`.
+    /// # Security Warning
     ///
-    pub fn new(
-        client_id: ClientId,
-        client_secret: Option<ClientSecret>,
-        auth_url: AuthUrl,
-        token_url: Option<TokenUrl>,
-    ) -> Self {
-        Client {
-            client_id,
-            client_secret,
-            auth_url,
-            auth_type: AuthType::BasicAuth,
-            token_url,
-            redirect_url: None,
-            introspect_url: None,
-            device_authorization_url: None,
-            phantom_te: PhantomData,
-            phantom_tr: PhantomData,
-            phantom_tt: PhantomData,
-            phantom_tir: PhantomData,
-        }
+    /// Callers should follow the security recommendations for any OAuth2 extensions used with
+    /// this function, which are beyond the scope of
+    /// [RFC 6749](https://tools.ietf.org/html/rfc6749).
+    ///
+    pub fn add_extra_param<N, V>(mut self, name: N, value: V) -> Self
+    where
+        N: Into<Cow<'a, str>>,
+        V: Into<Cow<'a, str>>,
+    {
+        self.extra_params.push((name.into(), value.into()));
+        self
     }
 
     ///
-    /// Configures the type of client authentication used for communicating with the authorization
-    /// server.
+    /// Appends the URI of a target service or resource where the client intends to use the
+    /// requested token, as defined in [RFC 8707](https://tools.ietf.org/html/rfc8707).
     ///
-    /// The default is to use HTTP Basic authentication, as recommended in
-    /// [Section 2.3.1 of RFC 6749](https://tools.ietf.org/html/rfc6749#section-2.3.1).
+    /// This method may be called multiple times to request access to several resources.
     ///
-    pub fn set_auth_type(mut self, auth_type: AuthType) -> Self {
-        self.auth_type = auth_type;
-
+    pub fn add_resource(mut self, resource: ResourceUrl) -> Self {
+        self.resources.push(Cow::Owned(resource));
         self
     }
 
     ///
-    /// Sets the the redirect URL used by the authorization endpoint.
+    /// Appends an authorization details object to the `authorization_details` parameter, as
+    /// defined in [RFC 9396](https://tools.ietf.org/html/rfc9396).
     ///
-    pub fn set_redirect_url(mut self, redirect_url: RedirectUrl) -> Self {
-        self.redirect_url = Some(redirect_url);
-
+    /// If the authorization details object cannot be serialized to JSON, sending the request
+    /// returns `RequestTokenError::Other`.
+    ///
+    pub fn add_authorization_detail<AD>(mut self, authorization_detail: &AD) -> Self
+    where
+        AD: AuthorizationDetail,
+    {
+        self.authorization_details.push(authorization_detail);
         self
     }
 
     ///
-    /// Sets the introspect URL used by the introspect endpoint.
+    /// Sets the key used to sign a DPoP proof for this request, which binds the issued access
+    /// token to the key ([RFC 9449](https://tools.ietf.org/html/rfc9449)).
     ///
-    pub fn set_introspection_url(mut self, introspect_url: IntrospectUrl) -> Self {
-        self.introspect_url = Some(introspect_url);
-
+    /// If the authorization server requires a nonce, the request is automatically retried with
+    /// the nonce provided in its `DPoP-Nonce` response header.
+    ///
+    pub fn set_dpop_key(mut self, dpop_key: &'a dyn DpopKey) -> Self {
+        self.dpop_key = Some(dpop_key);
         self
     }
 
     ///
-    /// Sets the the device authorization URL used by the device authorization endpoint.
-    /// Used for Device Code Flow, as per [RFC 8628](https://tools.ietf.org/html/rfc8628).
+    /// Appends a new scope to the token request.
     ///
-    pub fn set_device_authorization_url(
-        mut self,
-        device_authorization_url: DeviceAuthorizationUrl,
-    ) -> Self {
-        self.device_authorization_url = Some(device_authorization_url);
-
+    pub fn add_scope(mut self, scope: Scope) -> Self {
+        self.scopes.push(Cow::Owned(scope));
         self
     }
 
     ///
-    /// Generates an authorization URL for a new authorization request.
-    ///
-    /// # Arguments
-    ///
-    /// * `state_fn` - A function that returns an opaque value used by the client to maintain state
-    ///   between the request and callback. The authorization server includes this value when
-    ///   redirecting the user-agent back to the client.
-    ///
-    /// # Security Warning
-    ///
-    /// Callers should use a fresh, unpredictable `state` for each authorization request and verify
-    /// that this value matches the `state` parameter passed by the authorization server to the
-    /// redirect URI. Doing so mitigates
-    /// [Cross-Site Request Forgery](https://tools.ietf.org/html/rfc6749#section-10.12)
-    ///  attacks. To disable CSRF protections (NOT recommended), use `insecure::authorize_url`
-    ///  instead.
+    /// Synchronously sends the request to the authorization server and awaits a response.
     ///
-    pub fn authorize_url<S>(&self, state_fn: S) -> AuthorizationRequest
+    pub fn request<F, RE>(self, http_client: F) -> Result<TR, RequestTokenError<RE, TE>>
     where
-        S: FnOnce() -> CsrfToken,
+        for<'c> &'c F: SyncHttpClient<Error = RE>,
+        RE: Error + 'static,
     {
-        AuthorizationRequest {
-            auth_url: &self.auth_url,
-            client_id: &self.client_id,
-            extra_params: Vec::new(),
-            pkce_challenge: None,
-            redirect_url: self.redirect_url.as_ref().map(Cow::Borrowed),
-            response_type: "code".into(),
-            scopes: Vec::new(),
-            state: state_fn(),
-        }
+        let dpop_key = self.dpop_key;
+        send_token_request(&http_client, self.prepare_request()?, dpop_key)
+            .and_then(endpoint_response)
     }
 
     ///
-    /// Exchanges a code produced by a successful authorization process with an access token.
-    ///
-    /// Acquires ownership of the `code` because authorization codes may only be used once to
-    /// retrieve an access token from the authorization server.
-    ///
-    /// See https://tools.ietf.org/html/rfc6749#section-4.1.3
+    /// Asynchronously sends the request to the authorization server and awaits a response.
     ///
-    pub fn exchange_code(&self, code: AuthorizationCode) -> CodeTokenRequest<TE, TR, TT> {
-        CodeTokenRequest {
-            auth_type: &self.auth_type,
-            client_id: &self.client_id,
-            client_secret: self.client_secret.as_ref(),
-            code,
-            extra_params: Vec::new(),
-            pkce_verifier: None,
-            token_url: self.token_url.as_ref(),
-            redirect_url: self.redirect_url.as_ref(),
-            _phantom: PhantomData,
+    pub async fn request_async<C, RE>(self, http_client: C) -> Result<TR, RequestTokenError<RE, TE>>
+    where
+        for<'c> &'c C: AsyncHttpClient<Error = RE>,
+        RE: Error + 'static,
+    {
+        let dpop_key = self.dpop_key;
+        let http_request = self.prepare_request()?;
+        let http_response = send_token_request_async(&http_client, http_request, dpop_key).await?;
+        endpoint_response(http_response)
+    }
+
+    fn prepare_request<RE>(&self) -> Result<HttpRequest, RequestTokenError<RE, TE>>
+    where
+        RE: Error + 'static,
+    {
+        let mut params = vec![
+            ("grant_type", "refresh_token"),
+            ("refresh_token", self.refresh_token.secret()),
+        ];
+        params.extend(
+            self.resources
+                .iter()
+                .map(|resource| ("resource", resource.as_str())),
+        );
+        self.authorization_details.check()?;
+        let authorization_details = self.authorization_details.param();
+        if let Some(ref authorization_details) = authorization_details {
+            params.push(("authorization_details", authorization_details));
         }
+
+        endpoint_request(
+            self.auth_type,
+            self.client_id,
+            self.client_secret,
+            &self.extra_params,
+            None,
+            Some(&self.scopes),
+            self.token_url
+                .ok_or_else(|| RequestTokenError::Other("no token_url provided".to_string()))?
+                .url(),
+            params,
+        )
     }
+}
 
+///
+/// A request to exchange resource owner credentials for an access token.
+///
+/// See https://tools.ietf.org/html/rfc6749#section-4.3.
+///
+#[derive(Debug)]
+pub struct PasswordTokenRequest<'a, TE, TR, TT>
+where
+    TE: ErrorResponse,
+    TR: TokenResponse<TT>,
+    TT: TokenType,
+{
+    auth_type: &'a AuthType,
+    client_id: &'a ClientId,
+    client_secret: Option<&'a ClientSecret>,
+    dpop_key: Option<&'a dyn DpopKey>,
+    extra_params: Vec<(Cow<'a, str>, Cow<'a, str>)>,
+    resources: Vec<Cow<'a, ResourceUrl>>,
+    username: &'a ResourceOwnerUsername,
+    password: &'a ResourceOwnerPassword,
+    scopes: Vec<Cow<'a, Scope>>,
+    token_url: Option<&'a TokenUrl>,
+    _phantom: PhantomData<(TE, TR, TT)>,
+}
+impl<'a, TE, TR, TT> PasswordTokenRequest<'a, TE, TR, TT>
+where
+    TE: ErrorResponse + 'static,
+    TR: TokenResponse<TT>,
+    TT: TokenType,
+{
     ///
-    /// Requests an access token for the *password* grant type.
+    /// Appends an extra param to the token request.
     ///
-    /// See https://tools.ietf.org/html/rfc6749#section-4.3.2
+    /// This method allows extensions to be used without direct support from
+    /// this crate. If `name` conflicts with a parameter managed by this crate, the
+    /// behavior is undefined. In particular, do not set parameters defined by
+    /// [RFC 6749](https://tools.ietf.org/html/rfc6749) or
+    /// [RFC 7636](https://tools.ietf.org/html/rfc7636).
     ///
-    pub fn exchange_password<'a, 'b>(
-        &'a self,
-        username: &'b ResourceOwnerUsername,
-        password: &'b ResourceOwnerPassword,
-    ) -> PasswordTokenRequest<'b, TE, TR, TT>
+    /// # Security Warning
+    ///
+    /// Callers should follow the security recommendations for any OAuth2 extensions used with
+    /// this function, which are beyond the scope of
+    /// [RFC 6749](https://tools.ietf.org/html/rfc6749).
+    ///
+    pub fn add_extra_param<N, V>(mut self, name: N, value: V) -> Self
     where
-        'a: 'b,
+        N: Into<Cow<'a, str>>,
+        V: Into<Cow<'a, str>>,
     {
-        PasswordTokenRequest::<'b> {
-            auth_type: &self.auth_type,
-            client_id: &self.client_id,
-            client_secret: self.client_secret.as_ref(),
-            username,
-            password,
-            extra_params: Vec::new(),
-            scopes: Vec::new(),
-            token_url: self.token_url.as_ref(),
-            _phantom: PhantomData,
-        }
+        self.extra_params.push((name.into(), value.into()));
+        self
     }
 
     ///
-    /// Requests an access token for the *client credentials* grant type.
+    /// Appends the URI of a target service or resource where the client intends to use the
+    /// requested token, as defined in [RFC 8707](https://tools.ietf.org/html/rfc8707).
     ///
-    /// See https://tools.ietf.org/html/rfc6749#section-4.4.2
+    /// This method may be called multiple times to request access to several resources.
     ///
-    pub fn exchange_client_credentials(&self) -> ClientCredentialsTokenRequest<TE, TR, TT> {
-        ClientCredentialsTokenRequest {
-            auth_type: &self.auth_type,
-            client_id: &self.client_id,
-            client_secret: self.client_secret.as_ref(),
-            extra_params: Vec::new(),
-            scopes: Vec::new(),
-            token_url: self.token_url.as_ref(),
-            _phantom: PhantomData,
-        }
+    pub fn add_resource(mut self, resource: ResourceUrl) -> Self {
+        self.resources.push(Cow::Owned(resource));
+        self
     }
 
     ///
-    /// Exchanges a refresh token for an access token
+    /// Sets the key used to sign a DPoP proof for this request, which binds the issued access
+    /// token to the key ([RFC 9449](https://tools.ietf.org/html/rfc9449)).
     ///
-    /// See https://tools.ietf.org/html/rfc6749#section-6
+    /// If the authorization server requires a nonce, the request is automatically retried with
+    /// the nonce provided in its `DPoP-Nonce` response header.
     ///
-    pub fn exchange_refresh_token<'a, 'b>(
-        &'a self,
-        refresh_token: &'b RefreshToken,
-    ) -> RefreshTokenRequest<'b, TE, TR, TT>
-    where
-        'a: 'b,
-    {
-        RefreshTokenRequest {
-            auth_type: &self.auth_type,
-            client_id: &self.client_id,
-            client_secret: self.client_secret.as_ref(),
-            extra_params: Vec::new(),
-            refresh_token,
-            scopes: Vec::new(),
-            token_url: self.token_url.as_ref(),
-            _phantom: PhantomData,
-        }
+    pub fn set_dpop_key(mut self, dpop_key: &'a dyn DpopKey) -> Self {
+        self.dpop_key = Some(dpop_key);
+        self
     }
 
     ///
-    /// Perform a device authorization request as per
-    /// https://tools.ietf.org/html/rfc8628#section-3.1
+    /// Appends a new scope to the token request.
     ///
-    pub fn exchange_device_code(&self) -> DeviceAuthorizationRequest<TE> {
-        DeviceAuthorizationRequest {
-            auth_type: &self.auth_type,
-            client_id: &self.client_id,
-            client_secret: self.client_secret.as_ref(),
-            extra_params: Vec::new(),
-            scopes: Vec::new(),
-            device_authorization_url: self.device_authorization_url.as_ref(),
-            _phantom: PhantomData,
-        }
+    pub fn add_scope(mut self, scope: Scope) -> Self {
+        self.scopes.push(Cow::Owned(scope));
+        self
     }
 
     ///
-    /// Perform a device access token request as per
-    /// https://tools.ietf.org/html/rfc8628#section-3.4
+    /// Synchronously sends the request to the authorization server and awaits a response.
     ///
-    pub fn exchange_device_access_token<'a, 'b, 'c, EF>(
-        &'a self,
-        auth_response: &'b DeviceAuthorizationResponse<EF>,
-    ) -> DeviceAccessTokenRequest<'b, 'c, TR, TT, EF>
+    pub fn request<F, RE>(self, http_client: F) -> Result<TR, RequestTokenError<RE, TE>>
     where
-        'a: 'b,
-        EF: ExtraDeviceAuthorizationFields,
+        for<'c> &'c F: SyncHttpClient<Error = RE>,
+        RE: Error + 'static,
     {
-        DeviceAccessTokenRequest {
-            auth_type: &self.auth_type,
-            client_id: &self.client_id,
-            client_secret: self.client_secret.as_ref(),
-            extra_params: Vec::new(),
-            token_url: self.token_url.as_ref(),
-            dev_auth_resp: auth_response,
-            time_fn: Arc::new(Utc::now),
-            _phantom: PhantomData,
-        }
+        let dpop_key = self.dpop_key;
+        send_token_request(&http_client, self.prepare_request()?, dpop_key)
+            .and_then(endpoint_response)
     }
 
     ///
-    /// Exchanges a code produced by a successful authorization process with an access token.
-    ///
-    /// Acquires ownership of the `code` because authorization codes may only be used once to
-    /// retrieve an access token from the authorization server.
-    ///
-    /// See https://tools.ietf.org/html/rfc6749#section-4.1.3
+    /// Asynchronously sends the request to the authorization server and awaits a response.
     ///
-    pub fn introspect<'a>(&'a self, token: &'a AccessToken) -> IntrospectRequest<'a, TE, TIR, TT> {
-        IntrospectRequest {
-            auth_type: &self.auth_type,
-            client_id: &self.client_id,
-            client_secret: self.client_secret.as_ref(),
-            extra_params: Vec::new(),
-            introspect_url: self.introspect_url.as_ref(),
-            token,
-            token_type_hint: None,
-            _phantom: PhantomData,
-        }
+    pub async fn request_async<C, RE>(self, http_client: C) -> Result<TR, RequestTokenError<RE, TE>>
+    where
+        for<'c> &'c C: AsyncHttpClient<Error = RE>,
+        RE: Error + 'static,
+    {
+        let dpop_key = self.dpop_key;
+        let http_request = self.prepare_request()?;
+        let http_response = send_token_request_async(&http_client, http_request, dpop_key).await?;
+        endpoint_response(http_response)
+    }
+
+    fn prepare_request<RE>(&self) -> Result<HttpRequest, RequestTokenError<RE, TE>>
+    where
+        RE: Error + 'static,
+    {
+        let mut params = vec![
+            ("grant_type", "password"),
+            ("username", self.username),
+            ("password", self.password.secret()),
+        ];
+        params.extend(
+            self.resources
+                .iter()
+                .map(|resource| ("resource", resource.as_str())),
+        );
+
+        endpoint_request(
+            self.auth_type,
+            self.client_id,
+            self.client_secret,
+            &self.extra_params,
+            None,
+            Some(&self.scopes),
+            self.token_url
+                .ok_or_else(|| RequestTokenError::Other("no token_url provided".to_string()))?
+                .url(),
+            params,
+        )
     }
 }
 
 ///
-/// A request to the authorization endpoint
+/// A request to exchange client credentials for an access token.
+///
+/// See https://tools.ietf.org/html/rfc6749#section-4.4.
 ///
 #[derive(Debug)]
-pub struct AuthorizationRequest<'a> {
-    auth_url: &'a AuthUrl,
+pub struct ClientCredentialsTokenRequest<'a, TE, TR, TT>
+where
+    TE: ErrorResponse,
+    TR: TokenResponse<TT>,
+    TT: TokenType,
+{
+    auth_type: &'a AuthType,
     client_id: &'a ClientId,
+    client_secret: Option<&'a ClientSecret>,
+    dpop_key: Option<&'a dyn DpopKey>,
+    authorization_details: rar::AuthorizationDetails,
     extra_params: Vec<(Cow<'a, str>, Cow<'a, str>)>,
-    pkce_challenge: Option<PkceCodeChallenge>,
-    redirect_url: Option<Cow<'a, RedirectUrl>>,
-    response_type: Cow<'a, str>,
+    resources: Vec<Cow<'a, ResourceUrl>>,
     scopes: Vec<Cow<'a, Scope>>,
-    state: CsrfToken,
+    token_url: Option<&'a TokenUrl>,
+    _phantom: PhantomData<(TE, TR, TT)>,
 }
-impl<'a> AuthorizationRequest<'a> {
-    ///
-    /// Appends a new scope to the authorization URL.
-    ///
-    pub fn add_scope(mut self, scope: Scope) -> Self {
-        self.scopes.push(Cow::Owned(scope));
-        self
-    }
-
+impl<'a, TE, TR, TT> ClientCredentialsTokenRequest<'a, TE, TR, TT>
+where
+    TE: ErrorResponse + 'static,
+    TR: TokenResponse<TT>,
+    TT: TokenType,
+{
     ///
-    /// Appends an extra param to the authorization URL.
+    /// Appends an extra param to the token request.
     ///
     /// This method allows extensions to be used without direct support from
     /// this crate. If `name` conflicts with a parameter managed by this crate, the
@@ -854,125 +3069,116 @@ impl<'a> AuthorizationRequest<'a> {
     }
 
     ///
-    /// Enables the [Implicit Grant](https://tools.ietf.org/html/rfc6749#section-4.2) flow.
+    /// Appends the URI of a target service or resource where the client intends to use the
+    /// requested token, as defined in [RFC 8707](https://tools.ietf.org/html/rfc8707).
     ///
-    pub fn use_implicit_flow(mut self) -> Self {
-        self.response_type = "token".into();
+    /// This method may be called multiple times to request access to several resources.
+    ///
+    pub fn add_resource(mut self, resource: ResourceUrl) -> Self {
+        self.resources.push(Cow::Owned(resource));
         self
     }
 
     ///
-    /// Enables custom flows other than the `code` and `token` (implicit flow) grant.
+    /// Appends an authorization details object to the `authorization_details` parameter, as
+    /// defined in [RFC 9396](https://tools.ietf.org/html/rfc9396).
     ///
-    pub fn set_response_type(mut self, response_type: &ResponseType) -> Self {
-        self.response_type = (&**response_type).to_owned().into();
+    /// If the authorization details object cannot be serialized to JSON, sending the request
+    /// returns `RequestTokenError::Other`.
+    ///
+    pub fn add_authorization_detail<AD>(mut self, authorization_detail: &AD) -> Self
+    where
+        AD: AuthorizationDetail,
+    {
+        self.authorization_details.push(authorization_detail);
         self
     }
 
     ///
-    /// Enables the use of [Proof Key for Code Exchange](https://tools.ietf.org/html/rfc7636)
-    /// (PKCE).
+    /// Sets the key used to sign a DPoP proof for this request, which binds the issued access
+    /// token to the key ([RFC 9449](https://tools.ietf.org/html/rfc9449)).
     ///
-    /// PKCE is *highly recommended* for all public clients (i.e., those for which there
-    /// is no client secret or for which the client secret is distributed with the client,
-    /// such as in a native, mobile app, or browser app).
+    /// If the authorization server requires a nonce, the request is automatically retried with
+    /// the nonce provided in its `DPoP-Nonce` response header.
     ///
-    pub fn set_pkce_challenge(mut self, pkce_code_challenge: PkceCodeChallenge) -> Self {
-        self.pkce_challenge = Some(pkce_code_challenge);
+    pub fn set_dpop_key(mut self, dpop_key: &'a dyn DpopKey) -> Self {
+        self.dpop_key = Some(dpop_key);
         self
     }
 
     ///
-    /// Overrides the `redirect_url` to the one specified.
+    /// Appends a new scope to the token request.
     ///
-    pub fn set_redirect_url(mut self, redirect_url: Cow<'a, RedirectUrl>) -> Self {
-        self.redirect_url = Some(redirect_url);
+    pub fn add_scope(mut self, scope: Scope) -> Self {
+        self.scopes.push(Cow::Owned(scope));
         self
     }
 
     ///
-    /// Returns the full authorization URL and CSRF state for this authorization
-    /// request.
+    /// Synchronously sends the request to the authorization server and awaits a response.
     ///
-    pub fn url(self) -> (Url, CsrfToken) {
-        let scopes = self
-            .scopes
-            .iter()
-            .map(|s| s.to_string())
-            .collect::<Vec<_>>()
-            .join(" ");
-
-        let url = {
-            let mut pairs: Vec<(&str, &str)> = vec![
-                ("response_type", self.response_type.as_ref()),
-                ("client_id", &self.client_id),
-                ("state", self.state.secret()),
-            ];
-
-            if let Some(ref pkce_challenge) = self.pkce_challenge {
-                pairs.push(("code_challenge", &pkce_challenge.as_str()));
-                pairs.push(("code_challenge_method", &pkce_challenge.method().as_str()));
-            }
-
-            if let Some(ref redirect_url) = self.redirect_url {
-                pairs.push(("redirect_uri", redirect_url.as_str()));
-            }
-
-            if !scopes.is_empty() {
-                pairs.push(("scope", &scopes));
-            }
-
-            let mut url: Url = self.auth_url.url().to_owned();
-
-            url.query_pairs_mut()
-                .extend_pairs(pairs.iter().map(|&(k, v)| (k, &v[..])));
-
-            url.query_pairs_mut()
-                .extend_pairs(self.extra_params.iter().cloned());
-            url
-        };
+    pub fn request<F, RE>(self, http_client: F) -> Result<TR, RequestTokenError<RE, TE>>
+    where
+        for<'c> &'c F: SyncHttpClient<Error = RE>,
+        RE: Error + 'static,
+    {
+        let dpop_key = self.dpop_key;
+        send_token_request(&http_client, self.prepare_request()?, dpop_key)
+            .and_then(endpoint_response)
+    }
 
-        (url, self.state)
+    ///
+    /// Asynchronously sends the request to the authorization server and awaits a response.
+    ///
+    pub async fn request_async<C, RE>(self, http_client: C) -> Result<TR, RequestTokenError<RE, TE>>
+    where
+        for<'c> &'c C: AsyncHttpClient<Error = RE>,
+        RE: Error + 'static,
+    {
+        let dpop_key = self.dpop_key;
+        let http_request = self.prepare_request()?;
+        let http_response = send_token_request_async(&http_client, http_request, dpop_key).await?;
+        endpoint_response(http_response)
     }
-}
 
-///
-/// An HTTP request.
-///
-#[derive(Clone, Debug)]
-pub struct HttpRequest {
-    // These are all owned values so that the request can safely be passed between
-    // threads.
-    /// URL to which the HTTP request is being made.
-    pub url: Url,
-    /// HTTP request method for this request.
-    pub method: http::method::Method,
-    /// HTTP request headers to send.
-    pub headers: HeaderMap,
-    /// HTTP request body (typically for POST requests only).
-    pub body: Vec<u8>,
-}
+    fn prepare_request<RE>(&self) -> Result<HttpRequest, RequestTokenError<RE, TE>>
+    where
+        RE: Error + 'static,
+    {
+        let mut params = vec![("grant_type", "client_credentials")];
+        params.extend(
+            self.resources
+                .iter()
+                .map(|resource| ("resource", resource.as_str())),
+        );
+        self.authorization_details.check()?;
+        let authorization_details = self.authorization_details.param();
+        if let Some(ref authorization_details) = authorization_details {
+            params.push(("authorization_details", authorization_details));
+        }
 
-///
-/// An HTTP response.
-///
-#[derive(Clone, Debug)]
-pub struct HttpResponse {
-    /// HTTP status code returned by the server.
-    pub status_code: http::status::StatusCode,
-    /// HTTP response headers returned by the server.
-    pub headers: HeaderMap,
-    /// HTTP response body returned by the server.
-    pub body: Vec<u8>,
+        endpoint_request(
+            self.auth_type,
+            self.client_id,
+            self.client_secret,
+            &self.extra_params,
+            None,
+            Some(&self.scopes),
+            self.token_url
+                .ok_or_else(|| RequestTokenError::Other("no token_url provided".to_string()))?
+                .url(),
+            params,
+        )
+    }
 }
 
 ///
-/// A request to exchange an authorization code for an access token.
+/// A request to exchange a signed JWT assertion for an access token.
 ///
-/// See https://tools.ietf.org/html/rfc6749#section-4.1.3.
+/// See https://tools.ietf.org/html/rfc7523#section-2.1.
 ///
 #[derive(Debug)]
-pub struct CodeTokenRequest<'a, TE, TR, TT>
+pub struct JwtBearerTokenRequest<'a, TE, TR, TT>
 where
     TE: ErrorResponse,
     TR: TokenResponse<TT>,
@@ -981,14 +3187,14 @@ where
     auth_type: &'a AuthType,
     client_id: &'a ClientId,
     client_secret: Option<&'a ClientSecret>,
-    code: AuthorizationCode,
+    assertion: &'a JwtBearerAssertion,
+    dpop_key: Option<&'a dyn DpopKey>,
     extra_params: Vec<(Cow<'a, str>, Cow<'a, str>)>,
-    pkce_verifier: Option<PkceCodeVerifier>,
+    scopes: Vec<Cow<'a, Scope>>,
     token_url: Option<&'a TokenUrl>,
-    redirect_url: Option<&'a RedirectUrl>,
     _phantom: PhantomData<(TE, TR, TT)>,
 }
-impl<'a, TE, TR, TT> CodeTokenRequest<'a, TE, TR, TT>
+impl<'a, TE, TR, TT> JwtBearerTokenRequest<'a, TE, TR, TT>
 where
     TE: ErrorResponse + 'static,
     TR: TokenResponse<TT>,
@@ -1001,7 +3207,7 @@ where
     /// this crate. If `name` conflicts with a parameter managed by this crate, the
     /// behavior is undefined. In particular, do not set parameters defined by
     /// [RFC 6749](https://tools.ietf.org/html/rfc6749) or
-    /// [RFC 7636](https://tools.ietf.org/html/rfc7636).
+    /// [RFC 7523](https://tools.ietf.org/html/rfc7523).
     ///
     /// # Security Warning
     ///
@@ -1019,41 +3225,23 @@ where
     }
 
     ///
-    /// Completes the [Proof Key for Code Exchange](https://tools.ietf.org/html/rfc7636)
-    /// (PKCE) protocol flow.
+    /// Sets the key used to sign a DPoP proof for this request, which binds the issued access
+    /// token to the key ([RFC 9449](https://tools.ietf.org/html/rfc9449)).
     ///
-    /// This method must be called if `set_pkce_challenge` was used during the authorization
-    /// request.
+    /// If the authorization server requires a nonce, the request is automatically retried with
+    /// the nonce provided in its `DPoP-Nonce` response header.
     ///
-    pub fn set_pkce_verifier(mut self, pkce_verifier: PkceCodeVerifier) -> Self {
-        self.pkce_verifier = Some(pkce_verifier);
+    pub fn set_dpop_key(mut self, dpop_key: &'a dyn DpopKey) -> Self {
+        self.dpop_key = Some(dpop_key);
         self
     }
 
-    fn prepare_request<RE>(self) -> Result<HttpRequest, RequestTokenError<RE, TE>>
-    where
-        RE: Error + 'static,
-    {
-        let mut params = vec![
-            ("grant_type", "authorization_code"),
-            ("code", self.code.secret()),
-        ];
-        if let Some(ref pkce_verifier) = self.pkce_verifier {
-            params.push(("code_verifier", pkce_verifier.secret()));
-        }
-
-        Ok(endpoint_request(
-            self.auth_type,
-            self.client_id,
-            self.client_secret,
-            &self.extra_params,
-            self.redirect_url,
-            None,
-            self.token_url
-                .ok_or_else(|| RequestTokenError::Other("no token_url provided".to_string()))?
-                .url(),
-            params,
-        ))
+    ///
+    /// Appends a new scope to the token request.
+    ///
+    pub fn add_scope(mut self, scope: Scope) -> Self {
+        self.scopes.push(Cow::Owned(scope));
+        self
     }
 
     ///
@@ -1061,59 +3249,79 @@ where
     ///
     pub fn request<F, RE>(self, http_client: F) -> Result<TR, RequestTokenError<RE, TE>>
     where
-        F: FnOnce(HttpRequest) -> Result<HttpResponse, RE>,
+        for<'c> &'c F: SyncHttpClient<Error = RE>,
         RE: Error + 'static,
     {
-        http_client(self.prepare_request()?)
-            .map_err(RequestTokenError::Request)
+        let dpop_key = self.dpop_key;
+        send_token_request(&http_client, self.prepare_request()?, dpop_key)
             .and_then(endpoint_response)
     }
 
     ///
-    /// Asynchronously sends the request to the authorization server and returns a Future.
+    /// Asynchronously sends the request to the authorization server and awaits a response.
     ///
-    pub async fn request_async<C, F, RE>(
-        self,
-        http_client: C,
-    ) -> Result<TR, RequestTokenError<RE, TE>>
+    pub async fn request_async<C, RE>(self, http_client: C) -> Result<TR, RequestTokenError<RE, TE>>
     where
-        C: FnOnce(HttpRequest) -> F,
-        F: Future<Output = Result<HttpResponse, RE>>,
+        for<'c> &'c C: AsyncHttpClient<Error = RE>,
         RE: Error + 'static,
     {
+        let dpop_key = self.dpop_key;
         let http_request = self.prepare_request()?;
-        let http_response = http_client(http_request)
-            .await
-            .map_err(RequestTokenError::Request)?;
+        let http_response = send_token_request_async(&http_client, http_request, dpop_key).await?;
         endpoint_response(http_response)
     }
+
+    fn prepare_request<RE>(&self) -> Result<HttpRequest, RequestTokenError<RE, TE>>
+    where
+        RE: Error + 'static,
+    {
+        endpoint_request(
+            self.auth_type,
+            self.client_id,
+            self.client_secret,
+            &self.extra_params,
+            None,
+            Some(&self.scopes),
+            self.token_url
+                .ok_or_else(|| RequestTokenError::Other("no token_url provided".to_string()))?
+                .url(),
+            vec![
+                ("grant_type", jwt::JWT_BEARER_GRANT_TYPE),
+                ("assertion", self.assertion.secret()),
+            ],
+        )
+    }
 }
 
 ///
-/// A request to exchange a refresh token for an access token.
+/// A request to exchange a security token for another token.
 ///
-/// See https://tools.ietf.org/html/rfc6749#section-6.
+/// See https://tools.ietf.org/html/rfc8693#section-2.1.
 ///
 #[derive(Debug)]
-pub struct RefreshTokenRequest<'a, TE, TR, TT>
+pub struct TokenExchangeRequest<'a, TE, TT>
 where
     TE: ErrorResponse,
-    TR: TokenResponse<TT>,
     TT: TokenType,
 {
     auth_type: &'a AuthType,
     client_id: &'a ClientId,
     client_secret: Option<&'a ClientSecret>,
+    dpop_key: Option<&'a dyn DpopKey>,
     extra_params: Vec<(Cow<'a, str>, Cow<'a, str>)>,
-    refresh_token: &'a RefreshToken,
+    subject_token: &'a SubjectToken,
+    subject_token_type: TokenTypeIdentifier,
+    actor_token: Option<(&'a ActorToken, TokenTypeIdentifier)>,
+    audiences: Vec<Cow<'a, Audience>>,
+    requested_token_type: Option<TokenTypeIdentifier>,
+    resources: Vec<Cow<'a, ResourceUrl>>,
     scopes: Vec<Cow<'a, Scope>>,
     token_url: Option<&'a TokenUrl>,
-    _phantom: PhantomData<(TE, TR, TT)>,
+    _phantom: PhantomData<(TE, TT)>,
 }
-impl<'a, TE, TR, TT> RefreshTokenRequest<'a, TE, TR, TT>
+impl<'a, TE, TT> TokenExchangeRequest<'a, TE, TT>
 where
     TE: ErrorResponse + 'static,
-    TR: TokenResponse<TT>,
     TT: TokenType,
 {
     ///
@@ -1123,7 +3331,7 @@ where
     /// this crate. If `name` conflicts with a parameter managed by this crate, the
     /// behavior is undefined. In particular, do not set parameters defined by
     /// [RFC 6749](https://tools.ietf.org/html/rfc6749) or
-    /// [RFC 7636](https://tools.ietf.org/html/rfc7636).
+    /// [RFC 8693](https://tools.ietf.org/html/rfc8693).
     ///
     /// # Security Warning
     ///
@@ -1140,6 +3348,58 @@ where
         self
     }
 
+    ///
+    /// Sets the key used to sign a DPoP proof for this request, which binds the issued access
+    /// token to the key ([RFC 9449](https://tools.ietf.org/html/rfc9449)).
+    ///
+    /// If the authorization server requires a nonce, the request is automatically retried with
+    /// the nonce provided in its `DPoP-Nonce` response header.
+    ///
+    pub fn set_dpop_key(mut self, dpop_key: &'a dyn DpopKey) -> Self {
+        self.dpop_key = Some(dpop_key);
+        self
+    }
+
+    ///
+    /// Sets the security token representing the identity of the acting party (e.g., the
+    /// service performing the exchange on behalf of the subject), along with its type.
+    ///
+    pub fn set_actor_token(
+        mut self,
+        actor_token: &'a ActorToken,
+        actor_token_type: TokenTypeIdentifier,
+    ) -> Self {
+        self.actor_token = Some((actor_token, actor_token_type));
+        self
+    }
+
+    ///
+    /// Appends the logical name of a target service where the client intends to use the
+    /// requested token.
+    ///
+    pub fn add_audience(mut self, audience: Audience) -> Self {
+        self.audiences.push(Cow::Owned(audience));
+        self
+    }
+
+    ///
+    /// Appends the URI of a target service or resource where the client intends to use the
+    /// requested token.
+    ///
+    pub fn add_resource(mut self, resource: ResourceUrl) -> Self {
+        self.resources.push(Cow::Owned(resource));
+        self
+    }
+
+    ///
+    /// Sets the type of the requested security token. If not set, the authorization server
+    /// chooses the issued token type.
+    ///
+    pub fn set_requested_token_type(mut self, requested_token_type: TokenTypeIdentifier) -> Self {
+        self.requested_token_type = Some(requested_token_type);
+        self
+    }
+
     ///
     /// Appends a new scope to the token request.
     ///
@@ -1151,31 +3411,35 @@ where
     ///
     /// Synchronously sends the request to the authorization server and awaits a response.
     ///
-    pub fn request<F, RE>(self, http_client: F) -> Result<TR, RequestTokenError<RE, TE>>
+    pub fn request<F, RE, EF>(
+        self,
+        http_client: F,
+    ) -> Result<TokenExchangeTokenResponse<EF, TT>, RequestTokenError<RE, TE>>
     where
-        F: FnOnce(HttpRequest) -> Result<HttpResponse, RE>,
+        for<'c> &'c F: SyncHttpClient<Error = RE>,
         RE: Error + 'static,
+        EF: ExtraTokenFields,
     {
-        http_client(self.prepare_request()?)
-            .map_err(RequestTokenError::Request)
+        let dpop_key = self.dpop_key;
+        send_token_request(&http_client, self.prepare_request()?, dpop_key)
             .and_then(endpoint_response)
     }
+
     ///
     /// Asynchronously sends the request to the authorization server and awaits a response.
     ///
-    pub async fn request_async<C, F, RE>(
+    pub async fn request_async<C, RE, EF>(
         self,
         http_client: C,
-    ) -> Result<TR, RequestTokenError<RE, TE>>
+    ) -> Result<TokenExchangeTokenResponse<EF, TT>, RequestTokenError<RE, TE>>
     where
-        C: FnOnce(HttpRequest) -> F,
-        F: Future<Output = Result<HttpResponse, RE>>,
+        for<'c> &'c C: AsyncHttpClient<Error = RE>,
         RE: Error + 'static,
+        EF: ExtraTokenFields,
     {
+        let dpop_key = self.dpop_key;
         let http_request = self.prepare_request()?;
-        let http_response = http_client(http_request)
-            .await
-            .map_err(RequestTokenError::Request)?;
+        let http_response = send_token_request_async(&http_client, http_request, dpop_key).await?;
         endpoint_response(http_response)
     }
 
@@ -1183,7 +3447,30 @@ where
     where
         RE: Error + 'static,
     {
-        Ok(endpoint_request(
+        let mut params = vec![
+            ("grant_type", tokenexchange::TOKEN_EXCHANGE_GRANT_TYPE),
+            ("subject_token", self.subject_token.secret()),
+            ("subject_token_type", self.subject_token_type.as_ref()),
+        ];
+        if let Some((actor_token, ref actor_token_type)) = self.actor_token {
+            params.push(("actor_token", actor_token.secret()));
+            params.push(("actor_token_type", actor_token_type.as_ref()));
+        }
+        if let Some(ref requested_token_type) = self.requested_token_type {
+            params.push(("requested_token_type", requested_token_type.as_ref()));
+        }
+        params.extend(
+            self.resources
+                .iter()
+                .map(|resource| ("resource", resource.as_str())),
+        );
+        params.extend(
+            self.audiences
+                .iter()
+                .map(|audience| ("audience", audience.as_str())),
+        );
+
+        endpoint_request(
             self.auth_type,
             self.client_id,
             self.client_secret,
@@ -1193,50 +3480,177 @@ where
             self.token_url
                 .ok_or_else(|| RequestTokenError::Other("no token_url provided".to_string()))?
                 .url(),
-            vec![
-                ("grant_type", "refresh_token"),
-                ("refresh_token", self.refresh_token.secret()),
-            ],
-        ))
+            params,
+        )
     }
 }
 
 ///
-/// A request to exchange resource owner credentials for an access token.
+/// A request to introspect an access token.
 ///
-/// See https://tools.ietf.org/html/rfc6749#section-4.3.
+/// See https://tools.ietf.org/html/rfc7662#section-2.1
 ///
 #[derive(Debug)]
-pub struct PasswordTokenRequest<'a, TE, TR, TT>
+pub struct IntrospectRequest<'a, TE, TIR, TT>
 where
     TE: ErrorResponse,
-    TR: TokenResponse<TT>,
+    TIR: TokenInspectionResponse<TT>,
+    TT: TokenType,
+{
+    token: &'a AccessToken,
+    token_type_hint: Option<Cow<'a, str>>,
+
+    auth_type: &'a AuthType,
+    client_id: &'a ClientId,
+    client_secret: Option<&'a ClientSecret>,
+    extra_params: Vec<(Cow<'a, str>, Cow<'a, str>)>,
+    introspect_url: Option<&'a IntrospectUrl>,
+
+    _phantom: PhantomData<(TE, TIR, TT)>,
+}
+
+impl<'a, TE, TIR, TT> IntrospectRequest<'a, TE, TIR, TT>
+where
+    TE: ErrorResponse + 'static,
+    TIR: TokenInspectionResponse<TT>,
     TT: TokenType,
 {
+    ///
+    /// Sets the optional token_type_hint parameter
+    ///
+    /// See: https://tools.ietf.org/html/rfc7662#section-2.1
+    ///
+    /// OPTIONAL.  A hint about the type of the token submitted for
+    ///       introspection.  The protected resource MAY pass this parameter to
+    ///       help the authorization server optimize the token lookup.  If the
+    ///       server is unable to locate the token using the given hint, it MUST
+    ///      extend its search across all of its supported token types.  An
+    ///      authorization server MAY ignore this parameter, particularly if it
+    ///      is able to detect the token type automatically.  Values for this
+    ///      field are defined in the "OAuth Token Type Hints" registry defined
+    ///      in OAuth Token Revocation [RFC7009](https://tools.ietf.org/html/rfc7009).
+    ///
+    pub fn set_token_type_hint<V>(mut self, value: V) -> Self
+    where
+        V: Into<Cow<'a, str>>,
+    {
+        self.token_type_hint = Some(value.into());
+
+        self
+    }
+
+    ///
+    /// Appends an extra param to the token introspect.
+    ///
+    /// This method allows extensions to be used without direct support from
+    /// this crate. If `name` conflicts with a parameter managed by this crate, the
+    /// behavior is undefined. In particular, do not set parameters defined by
+    /// [RFC 6749](https://tools.ietf.org/html/rfc6749) or
+    /// [RFC 7662](https://tools.ietf.org/html/rfc7662).
+    ///
+    /// # Security Warning
+    ///
+    /// Callers should follow the security recommendations for any OAuth2 extensions used with
+    /// this function, which are beyond the scope of
+    /// [RFC 6749](https://tools.ietf.org/html/rfc6749).
+    ///
+    pub fn add_extra_param<N, V>(mut self, name: N, value: V) -> Self
+    where
+        N: Into<Cow<'a, str>>,
+        V: Into<Cow<'a, str>>,
+    {
+        self.extra_params.push((name.into(), value.into()));
+        self
+    }
+
+    fn prepare_request<RE>(self) -> Result<HttpRequest, RequestTokenError<RE, TE>>
+    where
+        RE: Error + 'static,
+    {
+        let mut params: Vec<(&str, &str)> = vec![("token", self.token.secret())];
+        if let Some(ref token_type_hint) = self.token_type_hint {
+            params.push(("token_type_hint", token_type_hint));
+        }
+
+        endpoint_request(
+            self.auth_type,
+            self.client_id,
+            self.client_secret,
+            &self.extra_params,
+            None,
+            None,
+            self.introspect_url
+                .ok_or_else(|| RequestTokenError::Other("no introspect_url provided".to_string()))?
+                .url(),
+            params,
+        )
+    }
+
+    ///
+    /// Synchronously sends the request to the authorization server and awaits a response.
+    ///
+    pub fn request<F, RE>(self, http_client: F) -> Result<TIR, RequestTokenError<RE, TE>>
+    where
+        F: SyncHttpClient<Error = RE>,
+        RE: Error + 'static,
+    {
+        http_client
+            .call(self.prepare_request()?)
+            .map_err(RequestTokenError::Request)
+            .and_then(endpoint_response)
+    }
+
+    ///
+    /// Asynchronously sends the request to the authorization server and returns a Future.
+    ///
+    pub async fn request_async<C, RE>(
+        self,
+        http_client: C,
+    ) -> Result<TIR, RequestTokenError<RE, TE>>
+    where
+        C: AsyncHttpClient<Error = RE>,
+        RE: Error + 'static,
+    {
+        let http_request = self.prepare_request()?;
+        let http_response = http_client
+            .call(http_request)
+            .await
+            .map_err(RequestTokenError::Request)?;
+        endpoint_response(http_response)
+    }
+}
+
+///
+/// A request to revoke a token.
+///
+/// See https://tools.ietf.org/html/rfc7009#section-2.1
+///
+#[derive(Debug)]
+pub struct RevocationRequest<'a, RT>
+where
+    RT: RevocableToken,
+{
+    token: &'a RT,
+
     auth_type: &'a AuthType,
     client_id: &'a ClientId,
     client_secret: Option<&'a ClientSecret>,
     extra_params: Vec<(Cow<'a, str>, Cow<'a, str>)>,
-    username: &'a ResourceOwnerUsername,
-    password: &'a ResourceOwnerPassword,
-    scopes: Vec<Cow<'a, Scope>>,
-    token_url: Option<&'a TokenUrl>,
-    _phantom: PhantomData<(TE, TR, TT)>,
+    revocation_url: Option<&'a RevocationUrl>,
 }
-impl<'a, TE, TR, TT> PasswordTokenRequest<'a, TE, TR, TT>
+
+impl<'a, RT> RevocationRequest<'a, RT>
 where
-    TE: ErrorResponse + 'static,
-    TR: TokenResponse<TT>,
-    TT: TokenType,
+    RT: RevocableToken,
 {
     ///
-    /// Appends an extra param to the token request.
+    /// Appends an extra param to the token revocation request.
     ///
     /// This method allows extensions to be used without direct support from
     /// this crate. If `name` conflicts with a parameter managed by this crate, the
     /// behavior is undefined. In particular, do not set parameters defined by
     /// [RFC 6749](https://tools.ietf.org/html/rfc6749) or
-    /// [RFC 7636](https://tools.ietf.org/html/rfc7636).
+    /// [RFC 7009](https://tools.ietf.org/html/rfc7009).
     ///
     /// # Security Warning
     ///
@@ -1253,94 +3667,411 @@ where
         self
     }
 
-    ///
-    /// Appends a new scope to the token request.
-    ///
-    pub fn add_scope(mut self, scope: Scope) -> Self {
-        self.scopes.push(Cow::Owned(scope));
-        self
+    fn prepare_request<RE>(
+        self,
+    ) -> Result<HttpRequest, RequestTokenError<RE, RevocationErrorResponse>>
+    where
+        RE: Error + 'static,
+    {
+        let mut params: Vec<(&str, &str)> = vec![("token", self.token.secret())];
+        if let Some(type_hint) = self.token.type_hint() {
+            params.push(("token_type_hint", type_hint));
+        }
+
+        endpoint_request(
+            self.auth_type,
+            self.client_id,
+            self.client_secret,
+            &self.extra_params,
+            None,
+            None,
+            self.revocation_url
+                .ok_or_else(|| RequestTokenError::Other("no revocation_url provided".to_string()))?
+                .url(),
+            params,
+        )
     }
 
     ///
     /// Synchronously sends the request to the authorization server and awaits a response.
     ///
-    pub fn request<F, RE>(self, http_client: F) -> Result<TR, RequestTokenError<RE, TE>>
+    /// A successful response indicates that the token has been revoked or that the client
+    /// submitted an invalid token (see
+    /// [Section 2.2 of RFC 7009](https://tools.ietf.org/html/rfc7009#section-2.2)).
+    ///
+    pub fn request<F, RE>(
+        self,
+        http_client: F,
+    ) -> Result<(), RequestTokenError<RE, RevocationErrorResponse>>
     where
-        F: FnOnce(HttpRequest) -> Result<HttpResponse, RE>,
+        F: SyncHttpClient<Error = RE>,
         RE: Error + 'static,
     {
-        http_client(self.prepare_request()?)
+        http_client
+            .call(self.prepare_request()?)
             .map_err(RequestTokenError::Request)
-            .and_then(endpoint_response)
+            .and_then(endpoint_response_status_only)
     }
 
     ///
-    /// Asynchronously sends the request to the authorization server and awaits a response.
+    /// Asynchronously sends the request to the authorization server and returns a Future.
     ///
-    pub async fn request_async<C, F, RE>(
+    pub async fn request_async<C, RE>(
         self,
         http_client: C,
-    ) -> Result<TR, RequestTokenError<RE, TE>>
+    ) -> Result<(), RequestTokenError<RE, RevocationErrorResponse>>
     where
-        C: FnOnce(HttpRequest) -> F,
-        F: Future<Output = Result<HttpResponse, RE>>,
+        C: AsyncHttpClient<Error = RE>,
         RE: Error + 'static,
     {
         let http_request = self.prepare_request()?;
-        let http_response = http_client(http_request)
+        let http_response = http_client
+            .call(http_request)
             .await
             .map_err(RequestTokenError::Request)?;
-        endpoint_response(http_response)
+        endpoint_response_status_only(http_response)
     }
+}
 
-    fn prepare_request<RE>(&self) -> Result<HttpRequest, RequestTokenError<RE, TE>>
-    where
-        RE: Error + 'static,
-    {
-        Ok(endpoint_request(
-            self.auth_type,
-            self.client_id,
-            self.client_secret,
-            &self.extra_params,
-            None,
-            Some(&self.scopes),
-            self.token_url
-                .ok_or_else(|| RequestTokenError::Other("no token_url provided".to_string()))?
-                .url(),
-            vec![
-                ("grant_type", "password"),
-                ("username", self.username),
-                ("password", self.password.secret()),
-            ],
+///
+/// Sends a request to the token endpoint, signing a DPoP proof if a key is provided and retrying
+/// once if the authorization server requires a DPoP nonce.
+///
+fn send_token_request<F, RE, TE>(
+    http_client: F,
+    mut http_request: HttpRequest,
+    dpop_key: Option<&dyn DpopKey>,
+) -> Result<HttpResponse, RequestTokenError<RE, TE>>
+where
+    F: SyncHttpClient<Error = RE> + Copy,
+    RE: Error + 'static,
+    TE: ErrorResponse + 'static,
+{
+    let dpop_key = match dpop_key {
+        Some(dpop_key) => dpop_key,
+        None => {
+            return http_client
+                .call(http_request)
+                .map_err(RequestTokenError::Request)
+        }
+    };
+
+    set_dpop_proof(&mut http_request, dpop_key, None)?;
+    let http_response = http_client
+        .call(http_request.clone())
+        .map_err(RequestTokenError::Request)?;
+    match dpop::nonce_challenge(&http_response) {
+        Some(nonce) => {
+            set_dpop_proof(&mut http_request, dpop_key, Some(&nonce))?;
+            http_client
+                .call(http_request)
+                .map_err(RequestTokenError::Request)
+        }
+        None => Ok(http_response),
+    }
+}
+
+///
+/// Asynchronous version of `send_token_request`.
+///
+async fn send_token_request_async<C, RE, TE>(
+    http_client: C,
+    mut http_request: HttpRequest,
+    dpop_key: Option<&dyn DpopKey>,
+) -> Result<HttpResponse, RequestTokenError<RE, TE>>
+where
+    C: AsyncHttpClient<Error = RE> + Copy,
+    RE: Error + 'static,
+    TE: ErrorResponse + 'static,
+{
+    let dpop_key = match dpop_key {
+        Some(dpop_key) => dpop_key,
+        None => {
+            return http_client
+                .call(http_request)
+                .await
+                .map_err(RequestTokenError::Request)
+        }
+    };
+
+    set_dpop_proof(&mut http_request, dpop_key, None)?;
+    let http_response = http_client
+        .call(http_request.clone())
+        .await
+        .map_err(RequestTokenError::Request)?;
+    match dpop::nonce_challenge(&http_response) {
+        Some(nonce) => {
+            set_dpop_proof(&mut http_request, dpop_key, Some(&nonce))?;
+            http_client
+                .call(http_request)
+                .await
+                .map_err(RequestTokenError::Request)
+        }
+        None => Ok(http_response),
+    }
+}
+
+fn set_dpop_proof<RE, TE>(
+    http_request: &mut HttpRequest,
+    dpop_key: &dyn DpopKey,
+    nonce: Option<&str>,
+) -> Result<(), RequestTokenError<RE, TE>>
+where
+    RE: Error + 'static,
+    TE: ErrorResponse + 'static,
+{
+    dpop::set_proof_header(http_request, dpop_key, None, nonce)
+        .map_err(|err| RequestTokenError::Other(format!("Failed to sign DPoP proof: {}", err)))
+}
+
+#[allow(clippy::too_many_arguments)]
+fn endpoint_request<'a, RE, TE>(
+    auth_type: &'a AuthType,
+    client_id: &'a ClientId,
+    client_secret: Option<&'a ClientSecret>,
+    extra_params: &'a [(Cow<'a, str>, Cow<'a, str>)],
+    redirect_url: Option<&'a RedirectUrl>,
+    scopes: Option<&'a Vec<Cow<'a, Scope>>>,
+    url: &'a Url,
+    params: Vec<(&'a str, &'a str)>,
+) -> Result<HttpRequest, RequestTokenError<RE, TE>>
+where
+    RE: Error + 'static,
+    TE: ErrorResponse + 'static,
+{
+    let mut headers = HeaderMap::new();
+    headers.append(ACCEPT, HeaderValue::from_static(CONTENT_TYPE_JSON));
+    headers.append(
+        CONTENT_TYPE,
+        HeaderValue::from_static(CONTENT_TYPE_FORMENCODED),
+    );
+
+    let scopes_opt = scopes.and_then(|scopes| {
+        if !scopes.is_empty() {
+            Some(
+                scopes
+                    .iter()
+                    .map(|s| s.to_string())
+                    .collect::<Vec<_>>()
+                    .join(" "),
+            )
+        } else {
+            None
+        }
+    });
+
+    let mut params: Vec<(&str, &str)> = params;
+    if let Some(ref scopes) = scopes_opt {
+        params.push(("scope", scopes));
+    }
+
+    let client_assertion = match auth_type {
+        AuthType::ClientSecretJwt => {
+            let client_secret = client_secret.ok_or_else(|| {
+                RequestTokenError::Other(
+                    "client_secret_jwt authentication requires a client secret".to_string(),
+                )
+            })?;
+            Some(jwt::client_assertion(
+                client_id,
+                url.as_str(),
+                &jwt::HmacSha256Key::from_client_secret(client_secret),
+            ))
+        }
+        AuthType::PrivateKeyJwt(ref key) => {
+            Some(jwt::client_assertion(client_id, url.as_str(), key.as_ref()))
+        }
+        AuthType::RequestBody
+        | AuthType::BasicAuth
+        | AuthType::TlsClientAuth
+        | AuthType::SelfSignedTlsClientAuth => None,
+    }
+    .transpose()
+    .map_err(|err| RequestTokenError::Other(format!("Failed to sign client assertion: {}", err)))?;
+
+    match auth_type {
+        AuthType::RequestBody => {
+            params.push(("client_id", client_id));
+            if let Some(ref client_secret) = client_secret {
+                params.push(("client_secret", client_secret.secret()));
+            }
+        }
+        AuthType::BasicAuth => {
+            // Section 2.3.1 of RFC 6749 requires separately url-encoding the id and secret
+            // before using them as HTTP Basic auth username and password. Note that this is
+            // not standard for ordinary Basic auth, so curl won't do it for us.
+            let urlencoded_id: String =
+                form_urlencoded::byte_serialize(&client_id.as_bytes()).collect();
+
+            let urlencoded_secret = client_secret.map(|secret| {
+                form_urlencoded::byte_serialize(secret.secret().as_bytes()).collect::<String>()
+            });
+            let b64_credential = base64::encode(&format!(
+                "{}:{}",
+                &urlencoded_id,
+                urlencoded_secret.as_deref().unwrap_or("")
+            ));
+            headers.append(
+                AUTHORIZATION,
+                HeaderValue::from_str(&format!("Basic {}", &b64_credential)).unwrap(),
+            );
+        }
+        AuthType::ClientSecretJwt | AuthType::PrivateKeyJwt(_) => {
+            params.push(("client_id", client_id));
+            params.push((
+                "client_assertion_type",
+                jwt::CLIENT_ASSERTION_TYPE_JWT_BEARER,
+            ));
+            if let Some(ref client_assertion) = client_assertion {
+                params.push(("client_assertion", client_assertion));
+            }
+        }
+        AuthType::TlsClientAuth | AuthType::SelfSignedTlsClientAuth => {
+            // The client is authenticated by the certificate presented during the TLS handshake.
+            params.push(("client_id", client_id));
+        }
+    }
+
+    if let Some(ref redirect_url) = redirect_url {
+        params.push(("redirect_uri", redirect_url.as_ref()));
+    }
+
+    params.extend_from_slice(
+        extra_params
+            .iter()
+            .map(|&(ref k, ref v)| (k.as_ref(), v.as_ref()))
+            .collect::<Vec<_>>()
+            .as_slice(),
+    );
+
+    let body = url::form_urlencoded::Serializer::new(String::new())
+        .extend_pairs(params)
+        .finish()
+        .into_bytes();
+
+    Ok(HttpRequest {
+        url: url.to_owned(),
+        method: http::method::Method::POST,
+        headers,
+        body,
+    })
+}
+
+fn endpoint_response<RE, TE, DO>(
+    http_response: HttpResponse,
+) -> Result<DO, RequestTokenError<RE, TE>>
+where
+    RE: Error + 'static,
+    TE: ErrorResponse,
+    DO: DeserializeOwned,
+{
+    check_response_status(&http_response)?;
+    endpoint_response_body(http_response)
+}
+
+fn endpoint_response_body<RE, TE, DO>(
+    http_response: HttpResponse,
+) -> Result<DO, RequestTokenError<RE, TE>>
+where
+    RE: Error + 'static,
+    TE: ErrorResponse,
+    DO: DeserializeOwned,
+{
+    // Validate that the response Content-Type is JSON.
+    check_content_type(&http_response.headers).map_err(RequestTokenError::Other)?;
+
+    if http_response.body.is_empty() {
+        Err(RequestTokenError::Other(
+            "Server returned empty response body".to_string(),
         ))
+    } else {
+        let response_body = http_response.body.as_slice();
+        serde_json::from_slice(response_body)
+            .map_err(|e| RequestTokenError::Parse(e, response_body.to_vec()))
+    }
+}
+
+fn endpoint_response_status_only<RE, TE>(
+    http_response: HttpResponse,
+) -> Result<(), RequestTokenError<RE, TE>>
+where
+    RE: Error + 'static,
+    TE: ErrorResponse,
+{
+    // The response body of a successful request is ignored, and is typically empty (see
+    // https://tools.ietf.org/html/rfc7009#section-2.2).
+    check_response_status(&http_response)
+}
+
+fn check_content_type(headers: &HeaderMap) -> Result<(), String> {
+    headers.get(CONTENT_TYPE).map_or(Ok(()), |content_type| {
+        // Section 3.1.1.1 of RFC 7231 indicates that media types are case insensitive and
+        // may be followed by optional whitespace and/or a parameter (e.g., charset).
+        // See https://tools.ietf.org/html/rfc7231#section-3.1.1.1.
+        if content_type
+            .to_str()
+            .ok()
+            .filter(|ct| ct.to_lowercase().starts_with(CONTENT_TYPE_JSON))
+            .is_none()
+        {
+            Err(format!(
+                "Unexpected response Content-Type: {:?}, should be `{}`",
+                content_type, CONTENT_TYPE_JSON
+            ))
+        } else {
+            Ok(())
+        }
+    })
+}
+
+fn check_response_status<RE, TE>(
+    http_response: &HttpResponse,
+) -> Result<(), RequestTokenError<RE, TE>>
+where
+    RE: Error + 'static,
+    TE: ErrorResponse,
+{
+    if http_response.status_code != StatusCode::OK {
+        let reason = http_response.body.as_slice();
+        if reason.is_empty() {
+            return Err(RequestTokenError::Other(
+                "Server returned empty error response".to_string(),
+            ));
+        } else {
+            let error = match serde_json::from_slice::<TE>(reason) {
+                Ok(error) => RequestTokenError::ServerResponse(error),
+                Err(error) => RequestTokenError::Parse(error, reason.to_vec()),
+            };
+            return Err(error);
+        }
     }
+
+    Ok(())
 }
 
 ///
-/// A request to exchange client credentials for an access token.
+/// The request for a set of verification codes from the authorization server.
 ///
-/// See https://tools.ietf.org/html/rfc6749#section-4.4.
+/// See https://tools.ietf.org/html/rfc8628#section-3.1.
 ///
 #[derive(Debug)]
-pub struct ClientCredentialsTokenRequest<'a, TE, TR, TT>
+pub struct DeviceAuthorizationRequest<'a, TE>
 where
-    TE: ErrorResponse,
-    TR: TokenResponse<TT>,
-    TT: TokenType,
+    TE: ErrorResponse,
 {
     auth_type: &'a AuthType,
     client_id: &'a ClientId,
     client_secret: Option<&'a ClientSecret>,
     extra_params: Vec<(Cow<'a, str>, Cow<'a, str>)>,
+    resources: Vec<Cow<'a, ResourceUrl>>,
     scopes: Vec<Cow<'a, Scope>>,
-    token_url: Option<&'a TokenUrl>,
-    _phantom: PhantomData<(TE, TR, TT)>,
+    device_authorization_url: Option<&'a DeviceAuthorizationUrl>,
+    _phantom: PhantomData<TE>,
 }
-impl<'a, TE, TR, TT> ClientCredentialsTokenRequest<'a, TE, TR, TT>
+
+impl<'a, TE> DeviceAuthorizationRequest<'a, TE>
 where
     TE: ErrorResponse + 'static,
-    TR: TokenResponse<TT>,
-    TT: TokenType,
 {
     ///
     /// Appends an extra param to the token request.
@@ -1366,6 +4097,17 @@ where
         self
     }
 
+    ///
+    /// Appends the URI of a target service or resource where the client intends to use the
+    /// requested token, as defined in [RFC 8707](https://tools.ietf.org/html/rfc8707).
+    ///
+    /// This method may be called multiple times to request access to several resources.
+    ///
+    pub fn add_resource(mut self, resource: ResourceUrl) -> Self {
+        self.resources.push(Cow::Owned(resource));
+        self
+    }
+
     ///
     /// Appends a new scope to the token request.
     ///
@@ -1374,119 +4116,105 @@ where
         self
     }
 
+    fn prepare_request<RE>(self) -> Result<HttpRequest, RequestTokenError<RE, TE>>
+    where
+        RE: Error + 'static,
+    {
+        endpoint_request(
+            self.auth_type,
+            self.client_id,
+            self.client_secret,
+            &self.extra_params,
+            None,
+            Some(&self.scopes),
+            self.device_authorization_url
+                .ok_or_else(|| {
+                    RequestTokenError::Other("no device authorization_url provided".to_string())
+                })?
+                .url(),
+            self.resources
+                .iter()
+                .map(|resource| ("resource", resource.as_str()))
+                .collect(),
+        )
+    }
+
     ///
     /// Synchronously sends the request to the authorization server and awaits a response.
     ///
-    pub fn request<F, RE>(self, http_client: F) -> Result<TR, RequestTokenError<RE, TE>>
+    pub fn request<F, RE, EF>(
+        self,
+        http_client: F,
+    ) -> Result<DeviceAuthorizationResponse<EF>, RequestTokenError<RE, TE>>
     where
-        F: FnOnce(HttpRequest) -> Result<HttpResponse, RE>,
+        F: SyncHttpClient<Error = RE>,
         RE: Error + 'static,
+        EF: ExtraDeviceAuthorizationFields,
     {
-        http_client(self.prepare_request()?)
+        http_client
+            .call(self.prepare_request()?)
             .map_err(RequestTokenError::Request)
             .and_then(endpoint_response)
     }
 
     ///
-    /// Asynchronously sends the request to the authorization server and awaits a response.
+    /// Asynchronously sends the request to the authorization server and returns a Future.
     ///
-    pub async fn request_async<C, F, RE>(
+    pub async fn request_async<C, RE, EF>(
         self,
         http_client: C,
-    ) -> Result<TR, RequestTokenError<RE, TE>>
+    ) -> Result<DeviceAuthorizationResponse<EF>, RequestTokenError<RE, TE>>
     where
-        C: FnOnce(HttpRequest) -> F,
-        F: Future<Output = Result<HttpResponse, RE>>,
+        C: AsyncHttpClient<Error = RE>,
         RE: Error + 'static,
+        EF: ExtraDeviceAuthorizationFields,
     {
         let http_request = self.prepare_request()?;
-        let http_response = http_client(http_request)
+        let http_response = http_client
+            .call(http_request)
             .await
             .map_err(RequestTokenError::Request)?;
         endpoint_response(http_response)
     }
-
-    fn prepare_request<RE>(&self) -> Result<HttpRequest, RequestTokenError<RE, TE>>
-    where
-        RE: Error + 'static,
-    {
-        Ok(endpoint_request(
-            self.auth_type,
-            self.client_id,
-            self.client_secret,
-            &self.extra_params,
-            None,
-            Some(&self.scopes),
-            self.token_url
-                .ok_or_else(|| RequestTokenError::Other("no token_url provided".to_string()))?
-                .url(),
-            vec![("grant_type", "client_credentials")],
-        ))
-    }
 }
 
 ///
-/// A request to introspect an access token.
+/// The request for an device access token from the authorization server.
 ///
-/// See https://tools.ietf.org/html/rfc7662#section-2.1
+/// See https://tools.ietf.org/html/rfc8628#section-3.4.
 ///
-#[derive(Debug)]
-pub struct IntrospectRequest<'a, TE, TIR, TT>
+#[derive(Clone)]
+pub struct DeviceAccessTokenRequest<'a, 'b, TR, TT, EF>
 where
-    TE: ErrorResponse,
-    TIR: TokenInspectionResponse<TT>,
+    TR: TokenResponse<TT>,
     TT: TokenType,
+    EF: ExtraDeviceAuthorizationFields,
 {
-    token: &'a AccessToken,
-    token_type_hint: Option<Cow<'a, str>>,
-
     auth_type: &'a AuthType,
     client_id: &'a ClientId,
     client_secret: Option<&'a ClientSecret>,
+    dpop_key: Option<&'a dyn DpopKey>,
     extra_params: Vec<(Cow<'a, str>, Cow<'a, str>)>,
-    introspect_url: Option<&'a IntrospectUrl>,
-
-    _phantom: PhantomData<(TE, TIR, TT)>,
+    token_url: Option<&'a TokenUrl>,
+    dev_auth_resp: &'a DeviceAuthorizationResponse<EF>,
+    time_fn: Arc<dyn Fn() -> DateTime<Utc> + 'b + Send + Sync>,
+    _phantom: PhantomData<(TR, TT, EF)>,
 }
 
-impl<'a, TE, TIR, TT> IntrospectRequest<'a, TE, TIR, TT>
+impl<'a, 'b, TR, TT, EF> DeviceAccessTokenRequest<'a, 'b, TR, TT, EF>
 where
-    TE: ErrorResponse + 'static,
-    TIR: TokenInspectionResponse<TT>,
+    TR: TokenResponse<TT>,
     TT: TokenType,
+    EF: ExtraDeviceAuthorizationFields,
 {
     ///
-    /// Sets the optional token_type_hint parameter
-    ///
-    /// See: https://tools.ietf.org/html/rfc7662#section-2.1
-    ///
-    /// OPTIONAL.  A hint about the type of the token submitted for
-    ///       introspection.  The protected resource MAY pass this parameter to
-    ///       help the authorization server optimize the token lookup.  If the
-    ///       server is unable to locate the token using the given hint, it MUST
-    ///      extend its search across all of its supported token types.  An
-    ///      authorization server MAY ignore this parameter, particularly if it
-    ///      is able to detect the token type automatically.  Values for this
-    ///      field are defined in the "OAuth Token Type Hints" registry defined
-    ///      in OAuth Token Revocation [RFC7009](https://tools.ietf.org/html/rfc7009).
-    ///
-    pub fn set_token_type_hint<V>(mut self, value: V) -> Self
-    where
-        V: Into<Cow<'a, str>>,
-    {
-        self.token_type_hint = Some(value.into());
-
-        self
-    }
-
-    ///
-    /// Appends an extra param to the token introspect.
+    /// Appends an extra param to the token request.
     ///
     /// This method allows extensions to be used without direct support from
     /// this crate. If `name` conflicts with a parameter managed by this crate, the
     /// behavior is undefined. In particular, do not set parameters defined by
     /// [RFC 6749](https://tools.ietf.org/html/rfc6749) or
-    /// [RFC 7662](https://tools.ietf.org/html/rfc7662).
+    /// [RFC 7636](https://tools.ietf.org/html/rfc7636).
     ///
     /// # Security Warning
     ///
@@ -1503,242 +4231,183 @@ where
         self
     }
 
-    fn prepare_request<RE>(self) -> Result<HttpRequest, RequestTokenError<RE, TE>>
-    where
-        RE: Error + 'static,
-    {
-        let mut params: Vec<(&str, &str)> = vec![("token", self.token.secret())];
-        if let Some(ref token_type_hint) = self.token_type_hint {
-            params.push(("token_type_hint", token_type_hint));
-        }
-
-        Ok(endpoint_request(
-            self.auth_type,
-            self.client_id,
-            self.client_secret,
-            &self.extra_params,
-            None,
-            None,
-            self.introspect_url
-                .ok_or_else(|| RequestTokenError::Other("no introspect_url provided".to_string()))?
-                .url(),
-            params,
-        ))
+    ///
+    /// Sets the key used to sign a DPoP proof for this request, which binds the issued access
+    /// token to the key ([RFC 9449](https://tools.ietf.org/html/rfc9449)).
+    ///
+    /// If the authorization server requires a nonce, the request is automatically retried with
+    /// the nonce provided in its `DPoP-Nonce` response header.
+    ///
+    pub fn set_dpop_key(mut self, dpop_key: &'a dyn DpopKey) -> Self {
+        self.dpop_key = Some(dpop_key);
+        self
     }
 
     ///
-    /// Synchronously sends the request to the authorization server and awaits a response.
+    /// Specifies a function for returning the current time.
     ///
-    pub fn request<F, RE>(self, http_client: F) -> Result<TIR, RequestTokenError<RE, TE>>
+    /// This function is used while polling the authorization server.
+    ///
+    pub fn set_time_fn<T>(mut self, time_fn: T) -> Self
     where
-        F: FnOnce(HttpRequest) -> Result<HttpResponse, RE>,
-        RE: Error + 'static,
+        T: Fn() -> DateTime<Utc> + 'b + Send + Sync,
     {
-        http_client(self.prepare_request()?)
-            .map_err(RequestTokenError::Request)
-            .and_then(endpoint_response)
+        self.time_fn = Arc::new(time_fn);
+        self
     }
 
     ///
-    /// Asynchronously sends the request to the authorization server and returns a Future.
+    /// Synchronously polls the authorization server for a response, waiting
+    /// using a user defined sleep function.
     ///
-    pub async fn request_async<C, F, RE>(
+    pub fn request<F, S, RE>(
         self,
-        http_client: C,
-    ) -> Result<TIR, RequestTokenError<RE, TE>>
+        http_client: F,
+        sleep_fn: S,
+        timeout: Option<Duration>,
+    ) -> Result<TR, RequestTokenError<RE, DeviceCodeErrorResponse>>
     where
-        C: FnOnce(HttpRequest) -> F,
-        F: Future<Output = Result<HttpResponse, RE>>,
+        for<'c> &'c F: SyncHttpClient<Error = RE>,
+        S: Fn(Duration),
         RE: Error + 'static,
     {
-        let http_request = self.prepare_request()?;
-        let http_response = http_client(http_request)
-            .await
-            .map_err(RequestTokenError::Request)?;
-        endpoint_response(http_response)
-    }
-}
-
-#[allow(clippy::too_many_arguments)]
-fn endpoint_request<'a>(
-    auth_type: &'a AuthType,
-    client_id: &'a ClientId,
-    client_secret: Option<&'a ClientSecret>,
-    extra_params: &'a [(Cow<'a, str>, Cow<'a, str>)],
-    redirect_url: Option<&'a RedirectUrl>,
-    scopes: Option<&'a Vec<Cow<'a, Scope>>>,
-    url: &'a Url,
-    params: Vec<(&'a str, &'a str)>,
-) -> HttpRequest {
-    let mut headers = HeaderMap::new();
-    headers.append(ACCEPT, HeaderValue::from_static(CONTENT_TYPE_JSON));
-    headers.append(
-        CONTENT_TYPE,
-        HeaderValue::from_static(CONTENT_TYPE_FORMENCODED),
-    );
-
-    let scopes_opt = scopes.and_then(|scopes| {
-        if !scopes.is_empty() {
-            Some(
-                scopes
-                    .iter()
-                    .map(|s| s.to_string())
-                    .collect::<Vec<_>>()
-                    .join(" "),
-            )
-        } else {
-            None
-        }
-    });
-
-    let mut params: Vec<(&str, &str)> = params;
-    if let Some(ref scopes) = scopes_opt {
-        params.push(("scope", scopes));
-    }
+        // Get the request timeout and starting interval
+        let timeout_dt = poll_timeout(
+            (*self.time_fn)(),
+            timeout.unwrap_or_else(|| self.dev_auth_resp.expires_in()),
+        )?;
+        let mut interval = self.dev_auth_resp.interval();
 
-    // FIXME: add support for auth extensions? e.g., client_secret_jwt and private_key_jwt
-    match auth_type {
-        AuthType::RequestBody => {
-            params.push(("client_id", client_id));
-            if let Some(ref client_secret) = client_secret {
-                params.push(("client_secret", client_secret.secret()));
+        // Loop while requesting a token.
+        loop {
+            let now = (*self.time_fn)();
+            if now > timeout_dt {
+                break Err(RequestTokenError::Other("Device code expired".to_string()));
             }
-        }
-        AuthType::BasicAuth => {
-            // Section 2.3.1 of RFC 6749 requires separately url-encoding the id and secret
-            // before using them as HTTP Basic auth username and password. Note that this is
-            // not standard for ordinary Basic auth, so curl won't do it for us.
-            let urlencoded_id: String =
-                form_urlencoded::byte_serialize(&client_id.as_bytes()).collect();
-
-            let urlencoded_secret = client_secret.map(|secret| {
-                form_urlencoded::byte_serialize(secret.secret().as_bytes()).collect::<String>()
-            });
-            let b64_credential = base64::encode(&format!(
-                "{}:{}",
-                &urlencoded_id,
-                urlencoded_secret.as_deref().unwrap_or("")
-            ));
-            headers.append(
-                AUTHORIZATION,
-                HeaderValue::from_str(&format!("Basic {}", &b64_credential)).unwrap(),
-            );
-        }
-    }
-
-    if let Some(ref redirect_url) = redirect_url {
-        params.push(("redirect_uri", redirect_url.as_ref()));
-    }
-
-    params.extend_from_slice(
-        extra_params
-            .iter()
-            .map(|&(ref k, ref v)| (k.as_ref(), v.as_ref()))
-            .collect::<Vec<_>>()
-            .as_slice(),
-    );
 
-    let body = url::form_urlencoded::Serializer::new(String::new())
-        .extend_pairs(params)
-        .finish()
-        .into_bytes();
+            let http_response =
+                match send_token_request(&http_client, self.prepare_request()?, self.dpop_key) {
+                    Ok(http_response) => Ok(http_response),
+                    Err(RequestTokenError::Request(err)) => Err(err),
+                    Err(err) => break Err(err),
+                };
+            match process_poll_response(http_response, interval) {
+                DeviceAccessTokenPollResult::ContinueWithNewPollInterval(new_interval) => {
+                    interval = new_interval
+                }
+                DeviceAccessTokenPollResult::Done(res, _) => break res,
+            }
 
-    HttpRequest {
-        url: url.to_owned(),
-        method: http::method::Method::POST,
-        headers,
-        body,
+            // Sleep here using the provided sleep function.
+            sleep_fn(interval);
+        }
     }
-}
 
-fn endpoint_response<RE, TE, DO>(
-    http_response: HttpResponse,
-) -> Result<DO, RequestTokenError<RE, TE>>
-where
-    RE: Error + 'static,
-    TE: ErrorResponse,
-    DO: DeserializeOwned,
-{
-    if http_response.status_code != StatusCode::OK {
-        let reason = http_response.body.as_slice();
-        if reason.is_empty() {
-            return Err(RequestTokenError::Other(
-                "Server returned empty error response".to_string(),
-            ));
-        } else {
-            let error = match serde_json::from_slice::<TE>(reason) {
-                Ok(error) => RequestTokenError::ServerResponse(error),
-                Err(error) => RequestTokenError::Parse(error, reason.to_vec()),
+    ///
+    /// Asynchronously sends the request to the authorization server and awaits a response.
+    ///
+    pub async fn request_async<C, S, SF, RE>(
+        self,
+        http_client: C,
+        sleep_fn: S,
+        timeout: Option<Duration>,
+    ) -> Result<TR, RequestTokenError<RE, DeviceCodeErrorResponse>>
+    where
+        for<'c> &'c C: AsyncHttpClient<Error = RE>,
+        S: Fn(Duration) -> SF,
+        SF: Future<Output = ()>,
+        RE: Error + 'static,
+    {
+        // Get the request timeout and starting interval
+        let timeout_dt = poll_timeout(
+            (*self.time_fn)(),
+            timeout.unwrap_or_else(|| self.dev_auth_resp.expires_in()),
+        )?;
+        let mut interval = self.dev_auth_resp.interval();
+
+        // Loop while requesting a token.
+        loop {
+            let now = (*self.time_fn)();
+            if now > timeout_dt {
+                break Err(RequestTokenError::Other("Device code expired".to_string()));
+            }
+
+            let http_response = match send_token_request_async(
+                &http_client,
+                self.prepare_request()?,
+                self.dpop_key,
+            )
+            .await
+            {
+                Ok(http_response) => Ok(http_response),
+                Err(RequestTokenError::Request(err)) => Err(err),
+                Err(err) => break Err(err),
             };
-            return Err(error);
+            match process_poll_response(http_response, interval) {
+                DeviceAccessTokenPollResult::ContinueWithNewPollInterval(new_interval) => {
+                    interval = new_interval
+                }
+                DeviceAccessTokenPollResult::Done(res, _) => break res,
+            }
+
+            // Sleep here using the provided sleep function.
+            sleep_fn(interval).await;
         }
     }
 
-    // Validate that the response Content-Type is JSON.
-    http_response
-        .headers
-        .get(CONTENT_TYPE)
-        .map_or(Ok(()), |content_type|
-            // Section 3.1.1.1 of RFC 7231 indicates that media types are case insensitive and
-            // may be followed by optional whitespace and/or a parameter (e.g., charset).
-            // See https://tools.ietf.org/html/rfc7231#section-3.1.1.1.
-            if content_type.to_str().ok().filter(|ct| ct.to_lowercase().starts_with(CONTENT_TYPE_JSON)).is_none() {
-                Err(
-                    RequestTokenError::Other(
-                        format!(
-                            "Unexpected response Content-Type: {:?}, should be `{}`",
-                            content_type,
-                            CONTENT_TYPE_JSON
-                        )
-                    )
-                )
-            } else {
-                Ok(())
-            }
-        )?;
-
-    if http_response.body.is_empty() {
-        Err(RequestTokenError::Other(
-            "Server returned empty response body".to_string(),
-        ))
-    } else {
-        let response_body = http_response.body.as_slice();
-        serde_json::from_slice(response_body)
-            .map_err(|e| RequestTokenError::Parse(e, response_body.to_vec()))
+    fn prepare_request<RE>(
+        &self,
+    ) -> Result<HttpRequest, RequestTokenError<RE, DeviceCodeErrorResponse>>
+    where
+        RE: Error + 'static,
+    {
+        endpoint_request(
+            self.auth_type,
+            self.client_id,
+            self.client_secret,
+            &self.extra_params,
+            None,
+            None,
+            self.token_url
+                .ok_or_else(|| RequestTokenError::Other("no token_url provided".to_string()))?
+                .url(),
+            vec![
+                ("grant_type", "urn:ietf:params:oauth:grant-type:device_code"),
+                ("device_code", self.dev_auth_resp.device_code().secret()),
+            ],
+        )
     }
 }
 
 ///
-/// The request for a set of verification codes from the authorization server.
+/// A Client-Initiated Backchannel Authentication (CIBA) request to the authorization server.
 ///
-/// See https://tools.ietf.org/html/rfc8628#section-3.1.
+/// See https://openid.net/specs/openid-client-initiated-backchannel-authentication-core-1_0.html#rfc.section.7.1.
 ///
 #[derive(Debug)]
-pub struct DeviceAuthorizationRequest<'a, TE>
-where
-    TE: ErrorResponse,
-{
+pub struct BackchannelAuthenticationRequest<'a> {
     auth_type: &'a AuthType,
     client_id: &'a ClientId,
     client_secret: Option<&'a ClientSecret>,
+    backchannel_authentication_url: Option<&'a BackchannelAuthenticationUrl>,
+    binding_message: Option<Cow<'a, str>>,
+    client_notification_token: Option<&'a ClientNotificationToken>,
     extra_params: Vec<(Cow<'a, str>, Cow<'a, str>)>,
+    id_token_hint: Option<Cow<'a, str>>,
+    login_hint: Option<Cow<'a, str>>,
+    requested_expiry: Option<Duration>,
     scopes: Vec<Cow<'a, Scope>>,
-    device_authorization_url: Option<&'a DeviceAuthorizationUrl>,
-    _phantom: PhantomData<TE>,
 }
 
-impl<'a, TE> DeviceAuthorizationRequest<'a, TE>
-where
-    TE: ErrorResponse + 'static,
-{
+impl<'a> BackchannelAuthenticationRequest<'a> {
     ///
-    /// Appends an extra param to the token request.
+    /// Appends an extra param to the authentication request.
     ///
     /// This method allows extensions to be used without direct support from
     /// this crate. If `name` conflicts with a parameter managed by this crate, the
     /// behavior is undefined. In particular, do not set parameters defined by
     /// [RFC 6749](https://tools.ietf.org/html/rfc6749) or
-    /// [RFC 7636](https://tools.ietf.org/html/rfc7636).
+    /// [CIBA](https://openid.net/specs/openid-client-initiated-backchannel-authentication-core-1_0.html).
     ///
     /// # Security Warning
     ///
@@ -1756,46 +4425,146 @@ where
     }
 
     ///
-    /// Appends a new scope to the token request.
+    /// Appends a new scope to the authentication request.
+    ///
+    /// OpenID Connect providers require the `openid` scope.
     ///
     pub fn add_scope(mut self, scope: Scope) -> Self {
         self.scopes.push(Cow::Owned(scope));
         self
     }
 
-    fn prepare_request<RE>(self) -> Result<HttpRequest, RequestTokenError<RE, TE>>
+    ///
+    /// Sets a hint identifying the end-user to authenticate, such as an email address, phone
+    /// number or account number.
+    ///
+    /// Exactly one of `login_hint` and `id_token_hint` must be provided.
+    ///
+    pub fn set_login_hint<H>(mut self, login_hint: H) -> Self
+    where
+        H: Into<Cow<'a, str>>,
+    {
+        self.login_hint = Some(login_hint.into());
+        self
+    }
+
+    ///
+    /// Sets an ID Token previously issued to the client, identifying the end-user to
+    /// authenticate.
+    ///
+    /// Exactly one of `login_hint` and `id_token_hint` must be provided.
+    ///
+    pub fn set_id_token_hint<H>(mut self, id_token_hint: H) -> Self
+    where
+        H: Into<Cow<'a, str>>,
+    {
+        self.id_token_hint = Some(id_token_hint.into());
+        self
+    }
+
+    ///
+    /// Sets a short human-readable message displayed on both the consumption device (e.g., a
+    /// call-center agent's screen) and the end-user's authentication device, allowing the
+    /// end-user to verify that both belong to the same transaction.
+    ///
+    pub fn set_binding_message<M>(mut self, binding_message: M) -> Self
+    where
+        M: Into<Cow<'a, str>>,
+    {
+        self.binding_message = Some(binding_message.into());
+        self
+    }
+
+    ///
+    /// Requests a lifetime for the returned `auth_req_id`. The authorization server may ignore
+    /// this value.
+    ///
+    pub fn set_requested_expiry(mut self, requested_expiry: Duration) -> Self {
+        self.requested_expiry = Some(requested_expiry);
+        self
+    }
+
+    ///
+    /// Sets the bearer token the authorization server uses to authenticate its callback to the
+    /// client notification endpoint in ping mode.
+    ///
+    /// The token should be unpredictable (see `ClientNotificationToken::new_random`) and kept
+    /// until the notification is received, in order to authenticate it with
+    /// `ciba::BackchannelPingNotification::from_request`.
+    ///
+    pub fn set_client_notification_token(
+        mut self,
+        client_notification_token: &'a ClientNotificationToken,
+    ) -> Self {
+        self.client_notification_token = Some(client_notification_token);
+        self
+    }
+
+    fn prepare_request<RE>(
+        self,
+    ) -> Result<HttpRequest, RequestTokenError<RE, BackchannelAuthenticationErrorResponse>>
     where
         RE: Error + 'static,
     {
-        Ok(endpoint_request(
+        let mut params = match (&self.login_hint, &self.id_token_hint) {
+            (Some(login_hint), None) => vec![("login_hint", login_hint.as_ref())],
+            (None, Some(id_token_hint)) => vec![("id_token_hint", id_token_hint.as_ref())],
+            _ => {
+                return Err(RequestTokenError::Other(
+                    "exactly one of login_hint and id_token_hint must be provided".to_string(),
+                ))
+            }
+        };
+        if let Some(ref binding_message) = self.binding_message {
+            params.push(("binding_message", binding_message.as_ref()));
+        }
+        let requested_expiry = self
+            .requested_expiry
+            .map(|requested_expiry| requested_expiry.as_secs().to_string());
+        if let Some(ref requested_expiry) = requested_expiry {
+            params.push(("requested_expiry", requested_expiry));
+        }
+        if let Some(client_notification_token) = self.client_notification_token {
+            params.push((
+                "client_notification_token",
+                client_notification_token.secret(),
+            ));
+        }
+
+        endpoint_request(
             self.auth_type,
             self.client_id,
             self.client_secret,
             &self.extra_params,
             None,
             Some(&self.scopes),
-            self.device_authorization_url
+            self.backchannel_authentication_url
                 .ok_or_else(|| {
-                    RequestTokenError::Other("no device authorization_url provided".to_string())
+                    RequestTokenError::Other(
+                        "no backchannel authentication_url provided".to_string(),
+                    )
                 })?
                 .url(),
-            vec![],
-        ))
+            params,
+        )
     }
 
     ///
     /// Synchronously sends the request to the authorization server and awaits a response.
     ///
-    pub fn request<F, RE, EF>(
+    pub fn request<F, RE>(
         self,
         http_client: F,
-    ) -> Result<DeviceAuthorizationResponse<EF>, RequestTokenError<RE, TE>>
+    ) -> Result<
+        BackchannelAuthenticationResponse,
+        RequestTokenError<RE, BackchannelAuthenticationErrorResponse>,
+    >
     where
-        F: FnOnce(HttpRequest) -> Result<HttpResponse, RE>,
+        F: SyncHttpClient<Error = RE>,
         RE: Error + 'static,
-        EF: ExtraDeviceAuthorizationFields,
     {
-        http_client(self.prepare_request()?)
+        http_client
+            .call(self.prepare_request()?)
             .map_err(RequestTokenError::Request)
             .and_then(endpoint_response)
     }
@@ -1803,18 +4572,20 @@ where
     ///
     /// Asynchronously sends the request to the authorization server and returns a Future.
     ///
-    pub async fn request_async<C, F, RE, EF>(
+    pub async fn request_async<C, RE>(
         self,
         http_client: C,
-    ) -> Result<DeviceAuthorizationResponse<EF>, RequestTokenError<RE, TE>>
+    ) -> Result<
+        BackchannelAuthenticationResponse,
+        RequestTokenError<RE, BackchannelAuthenticationErrorResponse>,
+    >
     where
-        C: FnOnce(HttpRequest) -> F,
-        F: Future<Output = Result<HttpResponse, RE>>,
+        C: AsyncHttpClient<Error = RE>,
         RE: Error + 'static,
-        EF: ExtraDeviceAuthorizationFields,
     {
         let http_request = self.prepare_request()?;
-        let http_response = http_client(http_request)
+        let http_response = http_client
+            .call(http_request)
             .await
             .map_err(RequestTokenError::Request)?;
         endpoint_response(http_response)
@@ -1822,32 +4593,34 @@ where
 }
 
 ///
-/// The request for an device access token from the authorization server.
+/// A CIBA token request, which exchanges an `auth_req_id` for an access token once the end-user
+/// has authenticated.
 ///
-/// See https://tools.ietf.org/html/rfc8628#section-3.4.
+/// See https://openid.net/specs/openid-client-initiated-backchannel-authentication-core-1_0.html#rfc.section.10.1.
 ///
 #[derive(Clone)]
-pub struct DeviceAccessTokenRequest<'a, 'b, TR, TT, EF>
+pub struct BackchannelTokenRequest<'a, 'b, TR, TT>
 where
     TR: TokenResponse<TT>,
     TT: TokenType,
-    EF: ExtraDeviceAuthorizationFields,
 {
     auth_type: &'a AuthType,
     client_id: &'a ClientId,
     client_secret: Option<&'a ClientSecret>,
+    dpop_key: Option<&'a dyn DpopKey>,
     extra_params: Vec<(Cow<'a, str>, Cow<'a, str>)>,
     token_url: Option<&'a TokenUrl>,
-    dev_auth_resp: &'a DeviceAuthorizationResponse<EF>,
+    auth_req_id: &'a AuthReqId,
+    expires_in: Option<Duration>,
+    interval: Duration,
     time_fn: Arc<dyn Fn() -> DateTime<Utc> + 'b + Send + Sync>,
-    _phantom: PhantomData<(TR, TT, EF)>,
+    _phantom: PhantomData<(TR, TT)>,
 }
 
-impl<'a, 'b, TR, TT, EF> DeviceAccessTokenRequest<'a, 'b, TR, TT, EF>
+impl<'a, 'b, TR, TT> BackchannelTokenRequest<'a, 'b, TR, TT>
 where
     TR: TokenResponse<TT>,
     TT: TokenType,
-    EF: ExtraDeviceAuthorizationFields,
 {
     ///
     /// Appends an extra param to the token request.
@@ -1873,6 +4646,18 @@ where
         self
     }
 
+    ///
+    /// Sets the key used to sign a DPoP proof for this request, which binds the issued access
+    /// token to the key ([RFC 9449](https://tools.ietf.org/html/rfc9449)).
+    ///
+    /// If the authorization server requires a nonce, the request is automatically retried with
+    /// the nonce provided in its `DPoP-Nonce` response header.
+    ///
+    pub fn set_dpop_key(mut self, dpop_key: &'a dyn DpopKey) -> Self {
+        self.dpop_key = Some(dpop_key);
+        self
+    }
+
     ///
     /// Specifies a function for returning the current time.
     ///
@@ -1887,9 +4672,45 @@ where
     }
 
     ///
-    /// Synchronously polls the authorization server for a response, waiting
+    /// Synchronously sends a single token request (e.g., after receiving a ping mode
+    /// notification) and awaits a response.
+    ///
+    pub fn request_once<F, RE>(
+        self,
+        http_client: F,
+    ) -> Result<TR, RequestTokenError<RE, DeviceCodeErrorResponse>>
+    where
+        for<'c> &'c F: SyncHttpClient<Error = RE>,
+        RE: Error + 'static,
+    {
+        send_token_request(&http_client, self.prepare_request()?, self.dpop_key)
+            .and_then(endpoint_response)
+    }
+
+    ///
+    /// Asynchronously sends a single token request (e.g., after receiving a ping mode
+    /// notification) and returns a Future.
+    ///
+    pub async fn request_once_async<C, RE>(
+        self,
+        http_client: C,
+    ) -> Result<TR, RequestTokenError<RE, DeviceCodeErrorResponse>>
+    where
+        for<'c> &'c C: AsyncHttpClient<Error = RE>,
+        RE: Error + 'static,
+    {
+        let http_response =
+            send_token_request_async(&http_client, self.prepare_request()?, self.dpop_key).await?;
+        endpoint_response(http_response)
+    }
+
+    ///
+    /// Synchronously polls the authorization server for a response (poll mode), waiting
     /// using a user defined sleep function.
     ///
+    /// If `timeout` is `None`, polling stops once the `auth_req_id` expires. A timeout must be
+    /// provided if the request was created using `Client::exchange_auth_req_id`.
+    ///
     pub fn request<F, S, RE>(
         self,
         http_client: F,
@@ -1897,22 +4718,28 @@ where
         timeout: Option<Duration>,
     ) -> Result<TR, RequestTokenError<RE, DeviceCodeErrorResponse>>
     where
-        F: Fn(HttpRequest) -> Result<HttpResponse, RE>,
+        for<'c> &'c F: SyncHttpClient<Error = RE>,
         S: Fn(Duration),
         RE: Error + 'static,
     {
         // Get the request timeout and starting interval
         let timeout_dt = self.compute_timeout(timeout)?;
-        let mut interval = self.dev_auth_resp.interval();
+        let mut interval = self.interval;
 
         // Loop while requesting a token.
         loop {
             let now = (*self.time_fn)();
             if now > timeout_dt {
-                break Err(RequestTokenError::Other("Device code expired".to_string()));
+                break Err(RequestTokenError::Other("auth_req_id expired".to_string()));
             }
 
-            match self.process_response(http_client(self.prepare_request()?), interval) {
+            let http_response =
+                match send_token_request(&http_client, self.prepare_request()?, self.dpop_key) {
+                    Ok(http_response) => Ok(http_response),
+                    Err(RequestTokenError::Request(err)) => Err(err),
+                    Err(err) => break Err(err),
+                };
+            match process_poll_response(http_response, interval) {
                 DeviceAccessTokenPollResult::ContinueWithNewPollInterval(new_interval) => {
                     interval = new_interval
                 }
@@ -1925,33 +4752,44 @@ where
     }
 
     ///
-    /// Asynchronously sends the request to the authorization server and awaits a response.
+    /// Asynchronously polls the authorization server for a response (poll mode) and returns a
+    /// Future.
     ///
-    pub async fn request_async<C, F, S, SF, RE>(
+    pub async fn request_async<C, S, SF, RE>(
         self,
         http_client: C,
         sleep_fn: S,
         timeout: Option<Duration>,
     ) -> Result<TR, RequestTokenError<RE, DeviceCodeErrorResponse>>
     where
-        C: Fn(HttpRequest) -> F,
-        F: Future<Output = Result<HttpResponse, RE>>,
+        for<'c> &'c C: AsyncHttpClient<Error = RE>,
         S: Fn(Duration) -> SF,
         SF: Future<Output = ()>,
         RE: Error + 'static,
     {
         // Get the request timeout and starting interval
         let timeout_dt = self.compute_timeout(timeout)?;
-        let mut interval = self.dev_auth_resp.interval();
+        let mut interval = self.interval;
 
         // Loop while requesting a token.
         loop {
             let now = (*self.time_fn)();
             if now > timeout_dt {
-                break Err(RequestTokenError::Other("Device code expired".to_string()));
+                break Err(RequestTokenError::Other("auth_req_id expired".to_string()));
             }
 
-            match self.process_response(http_client(self.prepare_request()?).await, interval) {
+            let http_response = match send_token_request_async(
+                &http_client,
+                self.prepare_request()?,
+                self.dpop_key,
+            )
+            .await
+            {
+                Ok(http_response) => Ok(http_response),
+                Err(RequestTokenError::Request(err)) => Err(err),
+                Err(err) => break Err(err),
+            };
+            match process_poll_response(http_response, interval) {
                 DeviceAccessTokenPollResult::ContinueWithNewPollInterval(new_interval) => {
                     interval = new_interval
                 }
@@ -1959,7 +4797,7 @@ where
             }
 
             // Sleep here using the provided sleep function.
-            sleep_fn(interval);
+            sleep_fn(interval).await;
         }
     }
 
@@ -1969,7 +4807,7 @@ where
     where
         RE: Error + 'static,
     {
-        Ok(endpoint_request(
+        endpoint_request(
             self.auth_type,
             self.client_id,
             self.client_secret,
@@ -1980,81 +4818,97 @@ where
                 .ok_or_else(|| RequestTokenError::Other("no token_url provided".to_string()))?
                 .url(),
             vec![
-                ("grant_type", "urn:ietf:params:oauth:grant-type:device_code"),
-                ("device_code", self.dev_auth_resp.device_code().secret()),
+                ("grant_type", CIBA_GRANT_TYPE),
+                ("auth_req_id", self.auth_req_id.secret()),
             ],
-        ))
+        )
     }
 
-    fn process_response<RE>(
+    fn compute_timeout<RE>(
         &self,
-        res: Result<HttpResponse, RE>,
-        current_interval: Duration,
-    ) -> DeviceAccessTokenPollResult<TR, RE, DeviceCodeErrorResponse, TT>
+        timeout: Option<Duration>,
+    ) -> Result<DateTime<Utc>, RequestTokenError<RE, DeviceCodeErrorResponse>>
     where
         RE: Error + 'static,
     {
-        let http_response = match res {
-            Ok(inner) => inner,
-            Err(_) => {
-                // Try and double the current interval. If that fails, just use the current one.
-                let new_interval = current_interval.checked_mul(2).unwrap_or(current_interval);
-                return DeviceAccessTokenPollResult::ContinueWithNewPollInterval(new_interval);
-            }
-        };
+        // If the user specified a timeout, use that, otherwise use the lifetime of the
+        // auth_req_id given by the backchannel authentication response.
+        let timeout = timeout
+            .or(self.expires_in)
+            .ok_or_else(|| RequestTokenError::Other("no polling timeout provided".to_string()))?;
+        poll_timeout((*self.time_fn)(), timeout)
+    }
+}
 
-        // Explicitly process the response with a DeviceCodeErrorResponse
-        let res = endpoint_response::<RE, DeviceCodeErrorResponse, TR>(http_response);
-        match res {
-            // On a ServerResponse error, the error needs inspecting as a DeviceCodeErrorResponse
-            // to work out whether a retry needs to happen.
-            Err(RequestTokenError::ServerResponse(dcer)) => {
-                match dcer.error() {
-                    // On AuthorizationPending, a retry needs to happen with the same poll interval.
-                    DeviceCodeErrorResponseType::AuthorizationPending => {
-                        DeviceAccessTokenPollResult::ContinueWithNewPollInterval(current_interval)
-                    }
-                    // On SlowDown, a retry needs to happen with a larger poll interval.
-                    DeviceCodeErrorResponseType::SlowDown => {
-                        DeviceAccessTokenPollResult::ContinueWithNewPollInterval(
-                            current_interval + Duration::from_secs(5),
-                        )
-                    }
-
-                    // On any other error, just return the error.
-                    _ => DeviceAccessTokenPollResult::Done(
-                        Err(RequestTokenError::ServerResponse(dcer)),
-                        PhantomData,
-                    ),
+///
+/// Processes a single response while polling the token endpoint, as in the device authorization
+/// grant ([RFC 8628](https://tools.ietf.org/html/rfc8628#section-3.5)) and CIBA poll mode.
+///
+fn process_poll_response<TR, TT, RE>(
+    res: Result<HttpResponse, RE>,
+    current_interval: Duration,
+) -> DeviceAccessTokenPollResult<TR, RE, DeviceCodeErrorResponse, TT>
+where
+    TR: TokenResponse<TT>,
+    TT: TokenType,
+    RE: Error + 'static,
+{
+    let http_response = match res {
+        Ok(inner) => inner,
+        Err(_) => {
+            // Try and double the current interval. If that fails, just use the current one.
+            let new_interval = current_interval.checked_mul(2).unwrap_or(current_interval);
+            return DeviceAccessTokenPollResult::ContinueWithNewPollInterval(new_interval);
+        }
+    };
+
+    // Explicitly process the response with a DeviceCodeErrorResponse
+    let res = endpoint_response::<RE, DeviceCodeErrorResponse, TR>(http_response);
+    match res {
+        // On a ServerResponse error, the error needs inspecting as a DeviceCodeErrorResponse
+        // to work out whether a retry needs to happen.
+        Err(RequestTokenError::ServerResponse(dcer)) => {
+            match dcer.error() {
+                // On AuthorizationPending, a retry needs to happen with the same poll interval.
+                DeviceCodeErrorResponseType::AuthorizationPending => {
+                    DeviceAccessTokenPollResult::ContinueWithNewPollInterval(current_interval)
+                }
+                // On SlowDown, a retry needs to happen with a larger poll interval.
+                DeviceCodeErrorResponseType::SlowDown => {
+                    DeviceAccessTokenPollResult::ContinueWithNewPollInterval(
+                        current_interval + Duration::from_secs(5),
+                    )
                 }
-            }
 
-            // On any other success or failure, return the failure.
-            res => DeviceAccessTokenPollResult::Done(res, PhantomData),
+                // On any other error, just return the error.
+                _ => DeviceAccessTokenPollResult::Done(
+                    Err(RequestTokenError::ServerResponse(dcer)),
+                    PhantomData,
+                ),
+            }
         }
-    }
 
-    fn compute_timeout<RE>(
-        &self,
-        timeout: Option<Duration>,
-    ) -> Result<DateTime<Utc>, RequestTokenError<RE, DeviceCodeErrorResponse>>
-    where
-        RE: Error + 'static,
-    {
-        // Calculate the request timeout - if the user specified a timeout,
-        // use that, otherwise use the value given by the device authorization
-        // response.
-        let timeout_dur = timeout.unwrap_or_else(|| self.dev_auth_resp.expires_in());
-        let chrono_timeout = chrono::Duration::from_std(timeout_dur)
-            .map_err(|_| RequestTokenError::Other("Failed to convert duration".to_string()))?;
+        // On any other success or failure, return the failure.
+        res => DeviceAccessTokenPollResult::Done(res, PhantomData),
+    }
+}
 
-        // Calculate the DateTime at which the request times out.
-        let timeout_dt = (*self.time_fn)()
-            .checked_add_signed(chrono_timeout)
-            .ok_or_else(|| RequestTokenError::Other("Failed to calculate timeout".to_string()))?;
+///
+/// Calculates the time at which polling the token endpoint should stop.
+///
+fn poll_timeout<RE>(
+    now: DateTime<Utc>,
+    timeout: Duration,
+) -> Result<DateTime<Utc>, RequestTokenError<RE, DeviceCodeErrorResponse>>
+where
+    RE: Error + 'static,
+{
+    let chrono_timeout = chrono::Duration::from_std(timeout)
+        .map_err(|_| RequestTokenError::Other("Failed to convert duration".to_string()))?;
 
-        Ok(timeout_dt)
-    }
+    // Calculate the DateTime at which the request times out.
+    now.checked_add_signed(chrono_timeout)
+        .ok_or_else(|| RequestTokenError::Other("Failed to calculate timeout".to_string()))
 }
 
 ///
@@ -0,0 +1,43 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use super::RequestUri;
+
+///
+/// Response from the pushed authorization request (PAR) endpoint.
+///
+/// See [Section 2.2 of RFC 9126](https://tools.ietf.org/html/rfc9126#section-2.2).
+///
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PushedAuthorizationResponse {
+    /// The request URI corresponding to the authorization request posted.
+    request_uri: RequestUri,
+
+    /// The lifetime in seconds of the request URI.
+    expires_in: u64,
+}
+
+impl PushedAuthorizationResponse {
+    ///
+    /// Instantiate a new pushed authorization response.
+    ///
+    pub fn new(request_uri: RequestUri, expires_in: Duration) -> Self {
+        Self {
+            request_uri,
+            expires_in: expires_in.as_secs(),
+        }
+    }
+
+    /// The request URI corresponding to the authorization request posted. This URI is a
+    /// single-use reference to the respective request data in the subsequent authorization
+    /// request.
+    pub fn request_uri(&self) -> &RequestUri {
+        &self.request_uri
+    }
+
+    /// The lifetime of the request URI.
+    pub fn expires_in(&self) -> Duration {
+        Duration::from_secs(self.expires_in)
+    }
+}
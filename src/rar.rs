@@ -0,0 +1,253 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::Debug;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use super::{ErrorResponse, ExtraTokenFields, RequestTokenError, StandardTokenResponse};
+
+///
+/// Trait for authorization details objects, as defined in
+/// [Section 2 of RFC 9396](https://tools.ietf.org/html/rfc9396#section-2).
+///
+/// Implementations must serialize to a JSON object containing a `type` field that determines
+/// the other fields of the object (e.g., a `payment_initiation` type for payment APIs).
+/// `StandardAuthorizationDetail` may be used for types that do not require a dedicated
+/// struct.
+///
+pub trait AuthorizationDetail: Clone + Debug + DeserializeOwned + Serialize {
+    ///
+    /// The type of authorization details (the `type` field).
+    ///
+    fn detail_type(&self) -> &str;
+}
+
+///
+/// Authorization details object containing the common data fields defined in
+/// [Section 2.2 of RFC 9396](https://tools.ietf.org/html/rfc9396#section-2.2), along with any
+/// type-specific fields.
+///
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct StandardAuthorizationDetail {
+    #[serde(rename = "type")]
+    detail_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    locations: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    actions: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    datatypes: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    identifier: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    privileges: Option<Vec<String>>,
+
+    #[serde(flatten)]
+    extra_fields: HashMap<String, serde_json::Value>,
+}
+
+impl StandardAuthorizationDetail {
+    ///
+    /// Instantiate a new authorization details object of the given type.
+    ///
+    pub fn new(detail_type: String) -> Self {
+        Self {
+            detail_type,
+            locations: None,
+            actions: None,
+            datatypes: None,
+            identifier: None,
+            privileges: None,
+            extra_fields: HashMap::new(),
+        }
+    }
+
+    /// Locations of the resource servers at which the authorization applies.
+    pub fn locations(&self) -> Option<&Vec<String>> {
+        self.locations.as_ref()
+    }
+
+    /// Sets the locations of the resource servers at which the authorization applies.
+    pub fn set_locations(mut self, locations: Option<Vec<String>>) -> Self {
+        self.locations = locations;
+        self
+    }
+
+    /// Kinds of actions to be taken at the resource.
+    pub fn actions(&self) -> Option<&Vec<String>> {
+        self.actions.as_ref()
+    }
+
+    /// Sets the kinds of actions to be taken at the resource.
+    pub fn set_actions(mut self, actions: Option<Vec<String>>) -> Self {
+        self.actions = actions;
+        self
+    }
+
+    /// Kinds of data being requested from the resource.
+    pub fn datatypes(&self) -> Option<&Vec<String>> {
+        self.datatypes.as_ref()
+    }
+
+    /// Sets the kinds of data being requested from the resource.
+    pub fn set_datatypes(mut self, datatypes: Option<Vec<String>>) -> Self {
+        self.datatypes = datatypes;
+        self
+    }
+
+    /// Identifier of a specific resource available at the API.
+    pub fn identifier(&self) -> Option<&String> {
+        self.identifier.as_ref()
+    }
+
+    /// Sets the identifier of a specific resource available at the API.
+    pub fn set_identifier(mut self, identifier: Option<String>) -> Self {
+        self.identifier = identifier;
+        self
+    }
+
+    /// Types or levels of privilege being requested at the resource.
+    pub fn privileges(&self) -> Option<&Vec<String>> {
+        self.privileges.as_ref()
+    }
+
+    /// Sets the types or levels of privilege being requested at the resource.
+    pub fn set_privileges(mut self, privileges: Option<Vec<String>>) -> Self {
+        self.privileges = privileges;
+        self
+    }
+
+    /// Type-specific fields of the authorization details object.
+    pub fn extra_fields(&self) -> &HashMap<String, serde_json::Value> {
+        &self.extra_fields
+    }
+
+    /// Sets a type-specific field of the authorization details object.
+    pub fn set_extra_field(mut self, name: String, value: serde_json::Value) -> Self {
+        self.extra_fields.insert(name, value);
+        self
+    }
+}
+
+impl AuthorizationDetail for StandardAuthorizationDetail {
+    fn detail_type(&self) -> &str {
+        &self.detail_type
+    }
+}
+
+///
+/// Extra token response fields containing the authorization details granted by the
+/// authorization server, as defined in
+/// [Section 7 of RFC 9396](https://tools.ietf.org/html/rfc9396#section-7).
+///
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct AuthorizationDetailsTokenFields<AD>
+where
+    AD: AuthorizationDetail,
+{
+    #[serde(bound = "AD: AuthorizationDetail")]
+    #[serde(default = "Option::default", skip_serializing_if = "Option::is_none")]
+    authorization_details: Option<Vec<AD>>,
+}
+
+impl<AD> AuthorizationDetailsTokenFields<AD>
+where
+    AD: AuthorizationDetail,
+{
+    ///
+    /// Instantiate new authorization details token fields.
+    ///
+    pub fn new(authorization_details: Option<Vec<AD>>) -> Self {
+        Self {
+            authorization_details,
+        }
+    }
+
+    /// The authorization details granted by the authorization server, which may differ from
+    /// those requested by the client.
+    pub fn authorization_details(&self) -> Option<&Vec<AD>> {
+        self.authorization_details.as_ref()
+    }
+}
+
+impl<AD> ExtraTokenFields for AuthorizationDetailsTokenFields<AD> where AD: AuthorizationDetail {}
+
+///
+/// Token response containing the granted authorization details.
+///
+pub type AuthorizationDetailsTokenResponse<AD, TT> =
+    StandardTokenResponse<AuthorizationDetailsTokenFields<AD>, TT>;
+
+///
+/// Authorization details objects to include in the `authorization_details` parameter of a
+/// request.
+///
+/// Serialization errors are recorded rather than returned immediately so that the builder
+/// methods adding authorization details remain chainable. The first such error is returned when
+/// the request is sent.
+///
+#[derive(Debug, Default)]
+pub(crate) struct AuthorizationDetails {
+    values: Vec<serde_json::Value>,
+    error: Option<serde_json::Error>,
+}
+impl AuthorizationDetails {
+    pub(crate) fn push<AD>(&mut self, authorization_detail: &AD)
+    where
+        AD: AuthorizationDetail,
+    {
+        match serde_json::to_value(authorization_detail) {
+            Ok(value) => self.values.push(value),
+            Err(err) => {
+                if self.error.is_none() {
+                    self.error = Some(err);
+                }
+            }
+        }
+    }
+
+    pub(crate) fn values(&self) -> &[serde_json::Value] {
+        &self.values
+    }
+
+    ///
+    /// Returns the first error encountered while serializing the authorization details objects.
+    ///
+    pub(crate) fn error(&self) -> Option<&serde_json::Error> {
+        self.error.as_ref()
+    }
+
+    ///
+    /// Returns an error if any of the authorization details objects could not be serialized.
+    ///
+    pub(crate) fn check<RE, TE>(&self) -> Result<(), RequestTokenError<RE, TE>>
+    where
+        RE: Error + 'static,
+        TE: ErrorResponse + 'static,
+    {
+        match self.error() {
+            Some(err) => Err(RequestTokenError::Other(format!(
+                "failed to serialize authorization details: {}",
+                err
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    pub(crate) fn take_error(&mut self) -> Option<serde_json::Error> {
+        self.error.take()
+    }
+
+    ///
+    /// Returns the value of the `authorization_details` parameter, or `None` if no
+    /// authorization details were requested.
+    ///
+    pub(crate) fn param(&self) -> Option<String> {
+        if self.values.is_empty() {
+            None
+        } else {
+            Some(serde_json::Value::Array(self.values.clone()).to_string())
+        }
+    }
+}
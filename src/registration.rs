@@ -0,0 +1,912 @@
+use std::error::Error;
+use std::fmt::Error as FormatterError;
+use std::fmt::{Debug, Display, Formatter};
+
+use chrono::{DateTime, TimeZone, Utc};
+use http::header::{HeaderMap, HeaderValue, ACCEPT, AUTHORIZATION, CONTENT_TYPE};
+use http::method::Method;
+use http::status::StatusCode;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    check_content_type, helpers, AccessToken, AsyncHttpClient, ClientAuthMethod,
+    ClientConfigurationUrl, ClientId, ClientSecret, ErrorResponseType, GrantType, HttpRequest,
+    HttpResponse, JsonWebKeySetUrl, RedirectUrl, RegistrationAccessToken, RegistrationUrl,
+    RequestTokenError, ResponseType, Scope, StandardErrorResponse, SyncHttpClient,
+    CONTENT_TYPE_JSON,
+};
+use crate::jwt::JsonWebKeySet;
+
+///
+/// Trait for adding extra fields to the `ClientMetadata`.
+///
+pub trait ExtraClientMetadata: Clone + Debug + DeserializeOwned + Serialize {}
+
+///
+/// Empty (default) extra client metadata fields.
+///
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct EmptyExtraClientMetadata {}
+impl ExtraClientMetadata for EmptyExtraClientMetadata {}
+
+///
+/// Client metadata, as defined in
+/// [Section 2 of RFC 7591](https://tools.ietf.org/html/rfc7591#section-2).
+///
+/// The same metadata is used for registering a client, for updating its registration, and is
+/// returned by the authorization server in the `ClientRegistrationResponse`.
+///
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct ClientMetadata<EF>
+where
+    EF: ExtraClientMetadata,
+{
+    #[serde(skip_serializing_if = "Option::is_none")]
+    redirect_uris: Option<Vec<RedirectUrl>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    token_endpoint_auth_method: Option<ClientAuthMethod>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    grant_types: Option<Vec<GrantType>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    response_types: Option<Vec<ResponseType>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    client_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    client_uri: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    logo_uri: Option<String>,
+    #[serde(rename = "scope")]
+    #[serde(deserialize_with = "helpers::deserialize_space_delimited_vec")]
+    #[serde(serialize_with = "helpers::serialize_space_delimited_vec")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    scopes: Option<Vec<Scope>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    contacts: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tos_uri: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    policy_uri: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    jwks_uri: Option<JsonWebKeySetUrl>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    jwks: Option<JsonWebKeySet>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    software_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    software_version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    software_statement: Option<String>,
+
+    #[serde(bound = "EF: ExtraClientMetadata", flatten)]
+    extra_fields: EF,
+}
+
+impl<EF> ClientMetadata<EF>
+where
+    EF: ExtraClientMetadata,
+{
+    ///
+    /// Instantiate new client metadata with the given extra fields and no standard fields set.
+    ///
+    pub fn new(extra_fields: EF) -> Self {
+        Self {
+            redirect_uris: None,
+            token_endpoint_auth_method: None,
+            grant_types: None,
+            response_types: None,
+            client_name: None,
+            client_uri: None,
+            logo_uri: None,
+            scopes: None,
+            contacts: None,
+            tos_uri: None,
+            policy_uri: None,
+            jwks_uri: None,
+            jwks: None,
+            software_id: None,
+            software_version: None,
+            software_statement: None,
+            extra_fields,
+        }
+    }
+
+    /// Redirection URIs used by the client in redirect-based flows.
+    pub fn redirect_uris(&self) -> Option<&Vec<RedirectUrl>> {
+        self.redirect_uris.as_ref()
+    }
+
+    /// Sets the redirection URIs used by the client in redirect-based flows.
+    pub fn set_redirect_uris(mut self, redirect_uris: Vec<RedirectUrl>) -> Self {
+        self.redirect_uris = Some(redirect_uris);
+        self
+    }
+
+    /// Method used by the client to authenticate to the token endpoint.
+    pub fn token_endpoint_auth_method(&self) -> Option<&ClientAuthMethod> {
+        self.token_endpoint_auth_method.as_ref()
+    }
+
+    /// Sets the method used by the client to authenticate to the token endpoint.
+    pub fn set_token_endpoint_auth_method(mut self, auth_method: ClientAuthMethod) -> Self {
+        self.token_endpoint_auth_method = Some(auth_method);
+        self
+    }
+
+    /// Grant types the client may use at the token endpoint.
+    pub fn grant_types(&self) -> Option<&Vec<GrantType>> {
+        self.grant_types.as_ref()
+    }
+
+    /// Sets the grant types the client may use at the token endpoint.
+    pub fn set_grant_types(mut self, grant_types: Vec<GrantType>) -> Self {
+        self.grant_types = Some(grant_types);
+        self
+    }
+
+    /// Response types the client may use at the authorization endpoint.
+    pub fn response_types(&self) -> Option<&Vec<ResponseType>> {
+        self.response_types.as_ref()
+    }
+
+    /// Sets the response types the client may use at the authorization endpoint.
+    pub fn set_response_types(mut self, response_types: Vec<ResponseType>) -> Self {
+        self.response_types = Some(response_types);
+        self
+    }
+
+    /// Human-readable name of the client.
+    pub fn client_name(&self) -> Option<&str> {
+        self.client_name.as_deref()
+    }
+
+    /// Sets the human-readable name of the client.
+    pub fn set_client_name(mut self, client_name: String) -> Self {
+        self.client_name = Some(client_name);
+        self
+    }
+
+    /// URL of the client's home page.
+    pub fn client_uri(&self) -> Option<&str> {
+        self.client_uri.as_deref()
+    }
+
+    /// Sets the URL of the client's home page.
+    pub fn set_client_uri(mut self, client_uri: String) -> Self {
+        self.client_uri = Some(client_uri);
+        self
+    }
+
+    /// URL of the client's logo.
+    pub fn logo_uri(&self) -> Option<&str> {
+        self.logo_uri.as_deref()
+    }
+
+    /// Sets the URL of the client's logo.
+    pub fn set_logo_uri(mut self, logo_uri: String) -> Self {
+        self.logo_uri = Some(logo_uri);
+        self
+    }
+
+    /// Scopes the client may request.
+    pub fn scopes(&self) -> Option<&Vec<Scope>> {
+        self.scopes.as_ref()
+    }
+
+    /// Sets the scopes the client may request.
+    pub fn set_scopes(mut self, scopes: Vec<Scope>) -> Self {
+        self.scopes = Some(scopes);
+        self
+    }
+
+    /// Contact information (typically email addresses) of the people responsible for the client.
+    pub fn contacts(&self) -> Option<&Vec<String>> {
+        self.contacts.as_ref()
+    }
+
+    /// Sets the contact information of the people responsible for the client.
+    pub fn set_contacts(mut self, contacts: Vec<String>) -> Self {
+        self.contacts = Some(contacts);
+        self
+    }
+
+    /// URL of the client's terms of service.
+    pub fn tos_uri(&self) -> Option<&str> {
+        self.tos_uri.as_deref()
+    }
+
+    /// Sets the URL of the client's terms of service.
+    pub fn set_tos_uri(mut self, tos_uri: String) -> Self {
+        self.tos_uri = Some(tos_uri);
+        self
+    }
+
+    /// URL of the client's privacy policy.
+    pub fn policy_uri(&self) -> Option<&str> {
+        self.policy_uri.as_deref()
+    }
+
+    /// Sets the URL of the client's privacy policy.
+    pub fn set_policy_uri(mut self, policy_uri: String) -> Self {
+        self.policy_uri = Some(policy_uri);
+        self
+    }
+
+    /// URL of the client's JSON Web Key Set document.
+    pub fn jwks_uri(&self) -> Option<&JsonWebKeySetUrl> {
+        self.jwks_uri.as_ref()
+    }
+
+    /// Sets the URL of the client's JSON Web Key Set document. This must not be used along with
+    /// `set_jwks`.
+    pub fn set_jwks_uri(mut self, jwks_uri: JsonWebKeySetUrl) -> Self {
+        self.jwks_uri = Some(jwks_uri);
+        self
+    }
+
+    /// The client's JSON Web Key Set, passed by value.
+    pub fn jwks(&self) -> Option<&JsonWebKeySet> {
+        self.jwks.as_ref()
+    }
+
+    /// Sets the client's JSON Web Key Set (e.g., the public keys used for `private_key_jwt`
+    /// client authentication). This must not be used along with `set_jwks_uri`.
+    pub fn set_jwks(mut self, jwks: JsonWebKeySet) -> Self {
+        self.jwks = Some(jwks);
+        self
+    }
+
+    /// Identifier of the client software, shared by all instances of the software.
+    pub fn software_id(&self) -> Option<&str> {
+        self.software_id.as_deref()
+    }
+
+    /// Sets the identifier of the client software.
+    pub fn set_software_id(mut self, software_id: String) -> Self {
+        self.software_id = Some(software_id);
+        self
+    }
+
+    /// Version of the client software.
+    pub fn software_version(&self) -> Option<&str> {
+        self.software_version.as_deref()
+    }
+
+    /// Sets the version of the client software.
+    pub fn set_software_version(mut self, software_version: String) -> Self {
+        self.software_version = Some(software_version);
+        self
+    }
+
+    /// Signed JWT asserting metadata values about the client software, as defined in
+    /// [Section 2.3 of RFC 7591](https://tools.ietf.org/html/rfc7591#section-2.3).
+    pub fn software_statement(&self) -> Option<&str> {
+        self.software_statement.as_deref()
+    }
+
+    /// Sets the software statement.
+    pub fn set_software_statement(mut self, software_statement: String) -> Self {
+        self.software_statement = Some(software_statement);
+        self
+    }
+
+    /// Any extra metadata fields.
+    pub fn extra_fields(&self) -> &EF {
+        &self.extra_fields
+    }
+}
+
+///
+/// Standard implementation of `ClientMetadata` which throws away extra metadata fields.
+///
+pub type StandardClientMetadata = ClientMetadata<EmptyExtraClientMetadata>;
+
+///
+/// Client information returned by the authorization server after successfully registering a
+/// client, as defined in
+/// [Section 3.2.1 of RFC 7591](https://tools.ietf.org/html/rfc7591#section-3.2.1) and
+/// [Section 3 of RFC 7592](https://tools.ietf.org/html/rfc7592#section-3).
+///
+/// This response is also returned when reading or updating the client's registration.
+///
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ClientRegistrationResponse<EF>
+where
+    EF: ExtraClientMetadata,
+{
+    client_id: ClientId,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    client_secret: Option<ClientSecret>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    registration_access_token: Option<RegistrationAccessToken>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    registration_client_uri: Option<ClientConfigurationUrl>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    client_id_issued_at: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    client_secret_expires_at: Option<i64>,
+
+    #[serde(bound = "EF: ExtraClientMetadata", flatten)]
+    metadata: ClientMetadata<EF>,
+}
+
+impl<EF> ClientRegistrationResponse<EF>
+where
+    EF: ExtraClientMetadata,
+{
+    /// The client identifier issued by the authorization server.
+    pub fn client_id(&self) -> &ClientId {
+        &self.client_id
+    }
+
+    /// The client secret issued by the authorization server, if any.
+    pub fn client_secret(&self) -> Option<&ClientSecret> {
+        self.client_secret.as_ref()
+    }
+
+    /// The access token used to read, update or delete the client's registration.
+    pub fn registration_access_token(&self) -> Option<&RegistrationAccessToken> {
+        self.registration_access_token.as_ref()
+    }
+
+    /// The URL of the client configuration endpoint for this client.
+    pub fn registration_client_uri(&self) -> Option<&ClientConfigurationUrl> {
+        self.registration_client_uri.as_ref()
+    }
+
+    /// The time at which the client identifier was issued.
+    pub fn client_id_issued_at(&self) -> Option<DateTime<Utc>> {
+        self.client_id_issued_at
+            .and_then(|timestamp| Utc.timestamp_opt(timestamp, 0).single())
+    }
+
+    /// The time at which the client secret expires, or `None` if it does not expire.
+    pub fn client_secret_expires_at(&self) -> Option<DateTime<Utc>> {
+        // A value of 0 indicates that the client secret does not expire.
+        self.client_secret_expires_at
+            .filter(|timestamp| *timestamp != 0)
+            .and_then(|timestamp| Utc.timestamp_opt(timestamp, 0).single())
+    }
+
+    /// The metadata registered for the client, which may differ from the metadata requested.
+    pub fn metadata(&self) -> &ClientMetadata<EF> {
+        &self.metadata
+    }
+
+    ///
+    /// Returns the client configuration endpoint for reading, updating or deleting this client's
+    /// registration, if the authorization server supports
+    /// [RFC 7592](https://tools.ietf.org/html/rfc7592).
+    ///
+    pub fn configuration_endpoint(&self) -> Option<ClientConfigurationEndpoint<'_>> {
+        match (
+            self.registration_client_uri.as_ref(),
+            self.registration_access_token.as_ref(),
+        ) {
+            (Some(url), Some(registration_access_token)) => Some(ClientConfigurationEndpoint::new(
+                url,
+                registration_access_token,
+            )),
+            _ => None,
+        }
+    }
+}
+
+///
+/// Standard implementation of `ClientRegistrationResponse` which throws away extra metadata
+/// fields.
+///
+pub type StandardClientRegistrationResponse = ClientRegistrationResponse<EmptyExtraClientMetadata>;
+
+///
+/// A request to register a new client with the authorization server, as defined in
+/// [Section 3.1 of RFC 7591](https://tools.ietf.org/html/rfc7591#section-3.1).
+///
+#[derive(Debug)]
+pub struct ClientRegistrationRequest<'a, EF>
+where
+    EF: ExtraClientMetadata,
+{
+    registration_url: &'a RegistrationUrl,
+    metadata: &'a ClientMetadata<EF>,
+    initial_access_token: Option<&'a AccessToken>,
+}
+
+impl<'a, EF> ClientRegistrationRequest<'a, EF>
+where
+    EF: ExtraClientMetadata,
+{
+    ///
+    /// Instantiate a new request to register a client with the given metadata at the
+    /// authorization server's registration endpoint.
+    ///
+    pub fn new(registration_url: &'a RegistrationUrl, metadata: &'a ClientMetadata<EF>) -> Self {
+        Self {
+            registration_url,
+            metadata,
+            initial_access_token: None,
+        }
+    }
+
+    ///
+    /// Sets the initial access token issued by the authorization server to authorize the
+    /// registration, for servers that do not allow open registration.
+    ///
+    pub fn set_initial_access_token(mut self, initial_access_token: &'a AccessToken) -> Self {
+        self.initial_access_token = Some(initial_access_token);
+        self
+    }
+
+    ///
+    /// Synchronously sends the request to the authorization server.
+    ///
+    pub fn request<F, RE>(
+        self,
+        http_client: F,
+    ) -> Result<
+        ClientRegistrationResponse<EF>,
+        RequestTokenError<RE, ClientRegistrationErrorResponse>,
+    >
+    where
+        F: SyncHttpClient<Error = RE>,
+        RE: Error + 'static,
+    {
+        let http_request = self.prepare_request()?;
+        let http_response = http_client
+            .call(http_request)
+            .map_err(RequestTokenError::Request)?;
+        registration_response_body(http_response, &[StatusCode::CREATED, StatusCode::OK])
+    }
+
+    ///
+    /// Asynchronously sends the request to the authorization server.
+    ///
+    pub async fn request_async<C, RE>(
+        self,
+        http_client: C,
+    ) -> Result<
+        ClientRegistrationResponse<EF>,
+        RequestTokenError<RE, ClientRegistrationErrorResponse>,
+    >
+    where
+        C: AsyncHttpClient<Error = RE>,
+        RE: Error + 'static,
+    {
+        let http_request = self.prepare_request()?;
+        let http_response = http_client
+            .call(http_request)
+            .await
+            .map_err(RequestTokenError::Request)?;
+        registration_response_body(http_response, &[StatusCode::CREATED, StatusCode::OK])
+    }
+
+    fn prepare_request<RE>(
+        &self,
+    ) -> Result<HttpRequest, RequestTokenError<RE, ClientRegistrationErrorResponse>>
+    where
+        RE: Error + 'static,
+    {
+        let body = serde_json::to_vec(self.metadata).map_err(|err| {
+            RequestTokenError::Other(format!("Failed to serialize metadata: {}", err))
+        })?;
+        registration_request(
+            Method::POST,
+            self.registration_url.url(),
+            self.initial_access_token.map(AccessToken::secret),
+            body,
+        )
+    }
+}
+
+///
+/// Client configuration endpoint of a dynamically registered client, used for reading, updating
+/// and deleting the client's registration as defined in
+/// [RFC 7592](https://tools.ietf.org/html/rfc7592).
+///
+#[derive(Clone, Debug)]
+pub struct ClientConfigurationEndpoint<'a> {
+    url: &'a ClientConfigurationUrl,
+    registration_access_token: &'a RegistrationAccessToken,
+}
+
+impl<'a> ClientConfigurationEndpoint<'a> {
+    ///
+    /// Instantiate a new client configuration endpoint with the `registration_client_uri` and
+    /// `registration_access_token` returned by the authorization server.
+    ///
+    pub fn new(
+        url: &'a ClientConfigurationUrl,
+        registration_access_token: &'a RegistrationAccessToken,
+    ) -> Self {
+        Self {
+            url,
+            registration_access_token,
+        }
+    }
+
+    ///
+    /// Synchronously reads the client's current registration, as described in
+    /// [Section 2.1 of RFC 7592](https://tools.ietf.org/html/rfc7592#section-2.1).
+    ///
+    pub fn read<EF, F, RE>(
+        &self,
+        http_client: F,
+    ) -> Result<
+        ClientRegistrationResponse<EF>,
+        RequestTokenError<RE, ClientRegistrationErrorResponse>,
+    >
+    where
+        EF: ExtraClientMetadata,
+        F: SyncHttpClient<Error = RE>,
+        RE: Error + 'static,
+    {
+        let http_request = self.prepare_request(Method::GET, Vec::new())?;
+        let http_response = http_client
+            .call(http_request)
+            .map_err(RequestTokenError::Request)?;
+        registration_response_body(http_response, &[StatusCode::OK])
+    }
+
+    ///
+    /// Asynchronously reads the client's current registration, as described in
+    /// [Section 2.1 of RFC 7592](https://tools.ietf.org/html/rfc7592#section-2.1).
+    ///
+    pub async fn read_async<EF, C, RE>(
+        &self,
+        http_client: C,
+    ) -> Result<
+        ClientRegistrationResponse<EF>,
+        RequestTokenError<RE, ClientRegistrationErrorResponse>,
+    >
+    where
+        EF: ExtraClientMetadata,
+        C: AsyncHttpClient<Error = RE>,
+        RE: Error + 'static,
+    {
+        let http_request = self.prepare_request(Method::GET, Vec::new())?;
+        let http_response = http_client
+            .call(http_request)
+            .await
+            .map_err(RequestTokenError::Request)?;
+        registration_response_body(http_response, &[StatusCode::OK])
+    }
+
+    ///
+    /// Synchronously replaces the client's registered metadata, as described in
+    /// [Section 2.2 of RFC 7592](https://tools.ietf.org/html/rfc7592#section-2.2).
+    ///
+    /// All metadata values must be included, since omitted fields may be deleted by the
+    /// authorization server. The client secret must be included if one was issued.
+    ///
+    pub fn update<EF, F, RE>(
+        &self,
+        client_id: &ClientId,
+        client_secret: Option<&ClientSecret>,
+        metadata: &ClientMetadata<EF>,
+        http_client: F,
+    ) -> Result<
+        ClientRegistrationResponse<EF>,
+        RequestTokenError<RE, ClientRegistrationErrorResponse>,
+    >
+    where
+        EF: ExtraClientMetadata,
+        F: SyncHttpClient<Error = RE>,
+        RE: Error + 'static,
+    {
+        let http_request = self.prepare_update_request(client_id, client_secret, metadata)?;
+        let http_response = http_client
+            .call(http_request)
+            .map_err(RequestTokenError::Request)?;
+        registration_response_body(http_response, &[StatusCode::OK])
+    }
+
+    ///
+    /// Asynchronously replaces the client's registered metadata, as described in
+    /// [Section 2.2 of RFC 7592](https://tools.ietf.org/html/rfc7592#section-2.2).
+    ///
+    /// All metadata values must be included, since omitted fields may be deleted by the
+    /// authorization server. The client secret must be included if one was issued.
+    ///
+    pub async fn update_async<EF, C, RE>(
+        &self,
+        client_id: &ClientId,
+        client_secret: Option<&ClientSecret>,
+        metadata: &ClientMetadata<EF>,
+        http_client: C,
+    ) -> Result<
+        ClientRegistrationResponse<EF>,
+        RequestTokenError<RE, ClientRegistrationErrorResponse>,
+    >
+    where
+        EF: ExtraClientMetadata,
+        C: AsyncHttpClient<Error = RE>,
+        RE: Error + 'static,
+    {
+        let http_request = self.prepare_update_request(client_id, client_secret, metadata)?;
+        let http_response = http_client
+            .call(http_request)
+            .await
+            .map_err(RequestTokenError::Request)?;
+        registration_response_body(http_response, &[StatusCode::OK])
+    }
+
+    ///
+    /// Synchronously deletes the client's registration, as described in
+    /// [Section 2.3 of RFC 7592](https://tools.ietf.org/html/rfc7592#section-2.3).
+    ///
+    pub fn delete<F, RE>(
+        &self,
+        http_client: F,
+    ) -> Result<(), RequestTokenError<RE, ClientRegistrationErrorResponse>>
+    where
+        F: SyncHttpClient<Error = RE>,
+        RE: Error + 'static,
+    {
+        let http_request = self.prepare_request(Method::DELETE, Vec::new())?;
+        let http_response = http_client
+            .call(http_request)
+            .map_err(RequestTokenError::Request)?;
+        check_status(&http_response, &[StatusCode::NO_CONTENT, StatusCode::OK])
+    }
+
+    ///
+    /// Asynchronously deletes the client's registration, as described in
+    /// [Section 2.3 of RFC 7592](https://tools.ietf.org/html/rfc7592#section-2.3).
+    ///
+    pub async fn delete_async<C, RE>(
+        &self,
+        http_client: C,
+    ) -> Result<(), RequestTokenError<RE, ClientRegistrationErrorResponse>>
+    where
+        C: AsyncHttpClient<Error = RE>,
+        RE: Error + 'static,
+    {
+        let http_request = self.prepare_request(Method::DELETE, Vec::new())?;
+        let http_response = http_client
+            .call(http_request)
+            .await
+            .map_err(RequestTokenError::Request)?;
+        check_status(&http_response, &[StatusCode::NO_CONTENT, StatusCode::OK])
+    }
+
+    fn prepare_request<RE>(
+        &self,
+        method: Method,
+        body: Vec<u8>,
+    ) -> Result<HttpRequest, RequestTokenError<RE, ClientRegistrationErrorResponse>>
+    where
+        RE: Error + 'static,
+    {
+        registration_request(
+            method,
+            self.url.url(),
+            Some(self.registration_access_token.secret()),
+            body,
+        )
+    }
+
+    fn prepare_update_request<EF, RE>(
+        &self,
+        client_id: &ClientId,
+        client_secret: Option<&ClientSecret>,
+        metadata: &ClientMetadata<EF>,
+    ) -> Result<HttpRequest, RequestTokenError<RE, ClientRegistrationErrorResponse>>
+    where
+        EF: ExtraClientMetadata,
+        RE: Error + 'static,
+    {
+        #[derive(Serialize)]
+        struct UpdateRequest<'a, EF>
+        where
+            EF: ExtraClientMetadata,
+        {
+            client_id: &'a ClientId,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            client_secret: Option<&'a str>,
+            #[serde(flatten)]
+            metadata: &'a ClientMetadata<EF>,
+        }
+
+        let body = serde_json::to_vec(&UpdateRequest {
+            client_id,
+            client_secret: client_secret.map(ClientSecret::secret).map(String::as_str),
+            metadata,
+        })
+        .map_err(|err| {
+            RequestTokenError::Other(format!("Failed to serialize metadata: {}", err))
+        })?;
+        self.prepare_request(Method::PUT, body)
+    }
+}
+
+fn registration_request<RE>(
+    method: Method,
+    url: &url::Url,
+    access_token: Option<&String>,
+    body: Vec<u8>,
+) -> Result<HttpRequest, RequestTokenError<RE, ClientRegistrationErrorResponse>>
+where
+    RE: Error + 'static,
+{
+    let mut headers = HeaderMap::new();
+    headers.append(ACCEPT, HeaderValue::from_static(CONTENT_TYPE_JSON));
+    if !body.is_empty() {
+        headers.append(CONTENT_TYPE, HeaderValue::from_static(CONTENT_TYPE_JSON));
+    }
+    if let Some(access_token) = access_token {
+        headers.append(
+            AUTHORIZATION,
+            HeaderValue::from_str(&format!("Bearer {}", access_token)).map_err(|_| {
+                RequestTokenError::Other("access token is not a valid header value".to_string())
+            })?,
+        );
+    }
+
+    Ok(HttpRequest {
+        url: url.clone(),
+        method,
+        headers,
+        body,
+    })
+}
+
+fn check_status<RE>(
+    http_response: &HttpResponse,
+    expected_status_codes: &[StatusCode],
+) -> Result<(), RequestTokenError<RE, ClientRegistrationErrorResponse>>
+where
+    RE: Error + 'static,
+{
+    if expected_status_codes.contains(&http_response.status_code) {
+        return Ok(());
+    }
+
+    // Error responses are only defined for invalid metadata (see
+    // https://tools.ietf.org/html/rfc7591#section-3.2.2). Other failures, such as an invalid
+    // registration access token, are indicated by the status code alone.
+    let reason = http_response.body.as_slice();
+    if http_response.status_code != StatusCode::BAD_REQUEST || reason.is_empty() {
+        return Err(RequestTokenError::Other(format!(
+            "Server returned unexpected HTTP status code {}",
+            http_response.status_code
+        )));
+    }
+    Err(
+        match serde_json::from_slice::<ClientRegistrationErrorResponse>(reason) {
+            Ok(error) => RequestTokenError::ServerResponse(error),
+            Err(error) => RequestTokenError::Parse(error, reason.to_vec()),
+        },
+    )
+}
+
+fn registration_response_body<EF, RE>(
+    http_response: HttpResponse,
+    expected_status_codes: &[StatusCode],
+) -> Result<ClientRegistrationResponse<EF>, RequestTokenError<RE, ClientRegistrationErrorResponse>>
+where
+    EF: ExtraClientMetadata,
+    RE: Error + 'static,
+{
+    check_status(&http_response, expected_status_codes)?;
+    check_content_type(&http_response.headers).map_err(RequestTokenError::Other)?;
+
+    serde_json::from_slice(&http_response.body)
+        .map_err(|err| RequestTokenError::Parse(err, http_response.body.clone()))
+}
+
+///
+/// Error encountered while initializing a `Client` from a `ClientRegistrationResponse` (see
+/// `Client::from_registration`).
+///
+#[derive(Debug, thiserror::Error)]
+pub enum ClientFromRegistrationError {
+    ///
+    /// The client was registered for `private_key_jwt` authentication, which requires a signing
+    /// key. Such clients should be initialized using `Client::new` and `Client::set_auth_type`
+    /// with `AuthType::PrivateKeyJwt`.
+    ///
+    #[error("private_key_jwt client authentication requires a signing key")]
+    SigningKeyRequired,
+    ///
+    /// The registered `token_endpoint_auth_method` is not supported by this crate.
+    ///
+    #[error("Unsupported token endpoint authentication method: {}", _0.as_str())]
+    UnsupportedAuthMethod(ClientAuthMethod),
+}
+
+///
+/// Client registration error types.
+///
+/// These error types are defined in
+/// [Section 3.2.2 of RFC 7591](https://tools.ietf.org/html/rfc7591#section-3.2.2).
+///
+#[derive(Clone, PartialEq)]
+pub enum ClientRegistrationErrorResponseType {
+    ///
+    /// The value of one or more redirection URIs is invalid.
+    ///
+    InvalidRedirectUri,
+    ///
+    /// The value of one of the client metadata fields is invalid and the server has rejected
+    /// this request.
+    ///
+    InvalidClientMetadata,
+    ///
+    /// The software statement presented is invalid.
+    ///
+    InvalidSoftwareStatement,
+    ///
+    /// The software statement presented is not approved for use by this authorization server.
+    ///
+    UnapprovedSoftwareStatement,
+    ///
+    /// An extension not defined by RFC 7591.
+    ///
+    Extension(String),
+}
+impl ClientRegistrationErrorResponseType {
+    fn from_str(s: &str) -> Self {
+        match s {
+            "invalid_redirect_uri" => ClientRegistrationErrorResponseType::InvalidRedirectUri,
+            "invalid_client_metadata" => ClientRegistrationErrorResponseType::InvalidClientMetadata,
+            "invalid_software_statement" => {
+                ClientRegistrationErrorResponseType::InvalidSoftwareStatement
+            }
+            "unapproved_software_statement" => {
+                ClientRegistrationErrorResponseType::UnapprovedSoftwareStatement
+            }
+            ext => ClientRegistrationErrorResponseType::Extension(ext.to_string()),
+        }
+    }
+}
+impl AsRef<str> for ClientRegistrationErrorResponseType {
+    fn as_ref(&self) -> &str {
+        match *self {
+            ClientRegistrationErrorResponseType::InvalidRedirectUri => "invalid_redirect_uri",
+            ClientRegistrationErrorResponseType::InvalidClientMetadata => "invalid_client_metadata",
+            ClientRegistrationErrorResponseType::InvalidSoftwareStatement => {
+                "invalid_software_statement"
+            }
+            ClientRegistrationErrorResponseType::UnapprovedSoftwareStatement => {
+                "unapproved_software_statement"
+            }
+            ClientRegistrationErrorResponseType::Extension(ref ext) => ext.as_str(),
+        }
+    }
+}
+impl<'de> serde::Deserialize<'de> for ClientRegistrationErrorResponseType {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::de::Deserializer<'de>,
+    {
+        let variant_str = String::deserialize(deserializer)?;
+        Ok(Self::from_str(&variant_str))
+    }
+}
+impl serde::ser::Serialize for ClientRegistrationErrorResponseType {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::ser::Serializer,
+    {
+        serializer.serialize_str(self.as_ref())
+    }
+}
+impl ErrorResponseType for ClientRegistrationErrorResponseType {}
+impl Debug for ClientRegistrationErrorResponseType {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FormatterError> {
+        Display::fmt(self, f)
+    }
+}
+
+impl Display for ClientRegistrationErrorResponseType {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FormatterError> {
+        write!(f, "{}", self.as_ref())
+    }
+}
+
+///
+/// Error response specialization for client registration.
+///
+pub type ClientRegistrationErrorResponse =
+    StandardErrorResponse<ClientRegistrationErrorResponseType>;
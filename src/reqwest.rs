@@ -1,5 +1,11 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::time::Duration;
+
 use thiserror::Error;
 
+use super::HttpResponse;
+
 ///
 /// Error type returned by failed reqwest HTTP requests.
 ///
@@ -22,39 +28,267 @@ where
     Other(String),
 }
 
-pub use blocking::http_client;
+pub use reqwest_0_10::{Certificate, Identity, Proxy};
+
+pub use blocking::{http_client, mtls_http_client, ReqwestClient};
 ///
 /// Error type returned by failed reqwest blocking HTTP requests.
 ///
 pub type HttpClientError = Error<blocking::reqwest::Error>;
 
-pub use async_client::async_http_client;
+pub use async_client::{async_http_client, async_mtls_http_client, AsyncReqwestClient};
 
 ///
 /// Error type returned by failed reqwest async HTTP requests.
 ///
 pub type AsyncHttpClientError = Error<reqwest_0_10::Error>;
 
+///
+/// Future returned by the HTTP clients created by `async_mtls_http_client` and
+/// `AsyncReqwestClient::http_client`.
+///
+pub type AsyncHttpClientFuture =
+    Pin<Box<dyn Future<Output = Result<HttpResponse, AsyncHttpClientError>> + Send>>;
+
+// Applies the configuration of a `ReqwestClientBuilder` to a blocking or async reqwest
+// `ClientBuilder`, which expose identically named methods.
+macro_rules! configure_client_builder {
+    ($config:expr, $builder:expr) => {{
+        let config = $config;
+        let mut builder = $builder
+            // Following redirects opens the client up to SSRF vulnerabilities.
+            .redirect(reqwest_0_10::redirect::Policy::none());
+        if let Some(timeout) = config.timeout {
+            builder = builder.timeout(timeout);
+        }
+        if let Some(connect_timeout) = config.connect_timeout {
+            builder = builder.connect_timeout(connect_timeout);
+        }
+        for proxy in config.proxies {
+            builder = builder.proxy(proxy);
+        }
+        for root_certificate in config.root_certificates {
+            builder = builder.add_root_certificate(root_certificate);
+        }
+        if let Some(identity) = config.identity {
+            builder = builder.identity(identity);
+        }
+        if let Some(user_agent) = config.user_agent {
+            builder = builder.user_agent(user_agent);
+        }
+        builder
+    }};
+}
+
+///
+/// Builder for reusable reqwest HTTP clients.
+///
+/// Unlike `http_client` and `async_http_client`, which construct a new reqwest client for every
+/// request, the clients returned by `build` and `build_async` may be used for many requests,
+/// which allows connections and TLS sessions to be reused. Redirects are never followed, since
+/// following them opens the client up to SSRF vulnerabilities.
+///
+#[derive(Debug, Default)]
+pub struct ReqwestClientBuilder {
+    timeout: Option<Duration>,
+    connect_timeout: Option<Duration>,
+    proxies: Vec<Proxy>,
+    root_certificates: Vec<Certificate>,
+    identity: Option<Identity>,
+    user_agent: Option<String>,
+}
+impl ReqwestClientBuilder {
+    ///
+    /// Creates a new builder with reqwest's default configuration (aside from redirects, which
+    /// are disabled).
+    ///
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    ///
+    /// Sets the total timeout of each request, from connecting until the response body has
+    /// been read.
+    ///
+    pub fn set_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    ///
+    /// Sets the timeout for establishing connections.
+    ///
+    pub fn set_connect_timeout(mut self, connect_timeout: Duration) -> Self {
+        self.connect_timeout = Some(connect_timeout);
+        self
+    }
+
+    ///
+    /// Adds a proxy through which matching requests are sent.
+    ///
+    pub fn add_proxy(mut self, proxy: Proxy) -> Self {
+        self.proxies.push(proxy);
+        self
+    }
+
+    ///
+    /// Adds a certificate to trust in addition to the default roots when verifying servers
+    /// (e.g., a private certificate authority).
+    ///
+    pub fn add_root_certificate(mut self, root_certificate: Certificate) -> Self {
+        self.root_certificates.push(root_certificate);
+        self
+    }
+
+    ///
+    /// Sets the TLS client certificate presented to servers, for use with mutual-TLS client
+    /// authentication and certificate-bound access tokens
+    /// ([RFC 8705](https://tools.ietf.org/html/rfc8705)).
+    ///
+    pub fn set_identity(mut self, identity: Identity) -> Self {
+        self.identity = Some(identity);
+        self
+    }
+
+    ///
+    /// Sets the `User-Agent` header sent with each request.
+    ///
+    pub fn set_user_agent(mut self, user_agent: String) -> Self {
+        self.user_agent = Some(user_agent);
+        self
+    }
+
+    ///
+    /// Builds a synchronous HTTP client.
+    ///
+    /// The blocking reqwest client runs its own internal runtime and must not be built from
+    /// within an async context.
+    ///
+    pub fn build(self) -> Result<ReqwestClient, HttpClientError> {
+        let client = configure_client_builder!(self, reqwest_0_10::blocking::Client::builder())
+            .build()
+            .map_err(Error::Reqwest)?;
+        Ok(ReqwestClient::new(client))
+    }
+
+    ///
+    /// Builds an asynchronous HTTP client.
+    ///
+    pub fn build_async(self) -> Result<AsyncReqwestClient, AsyncHttpClientError> {
+        let client = configure_client_builder!(self, reqwest_0_10::Client::builder())
+            .build()
+            .map_err(Error::Reqwest)?;
+        Ok(AsyncReqwestClient::new(client))
+    }
+}
+
 mod blocking {
-    use super::super::{HttpRequest, HttpResponse};
+    use super::super::{HttpRequest, HttpResponse, SyncHttpClient};
     use super::Error;
 
+    use super::ReqwestClientBuilder;
+
     pub use reqwest_0_10 as reqwest;
     use reqwest_0_10::blocking;
-    use reqwest_0_10::redirect::Policy as RedirectPolicy;
 
     use std::io::Read;
 
     ///
     /// Synchronous HTTP client.
     ///
+    /// A new reqwest client is constructed for each request. Applications sending many requests
+    /// should use a `ReqwestClient` instead.
+    ///
     pub fn http_client(request: HttpRequest) -> Result<HttpResponse, Error<reqwest::Error>> {
-        let client = blocking::Client::builder()
-            // Following redirects opens the client up to SSRF vulnerabilities.
-            .redirect(RedirectPolicy::none())
-            .build()
-            .map_err(Error::Reqwest)?;
+        ReqwestClientBuilder::new().build()?.execute(request)
+    }
+
+    ///
+    /// Returns a synchronous HTTP client that presents a TLS client certificate, for use with
+    /// mutual-TLS client authentication and certificate-bound access tokens
+    /// ([RFC 8705](https://tools.ietf.org/html/rfc8705)).
+    ///
+    /// # Arguments
+    ///
+    /// * `identity_pem` - PEM-encoded private key and certificate chain of the client.
+    /// * `root_certificate_pem` - Optional PEM-encoded certificate to trust in addition to the
+    ///   default roots when verifying the server (e.g., a private certificate authority).
+    ///
+    /// PKCS#12 identities are not supported by the rustls backend used by this crate; they
+    /// should be converted to PEM first.
+    ///
+    pub fn mtls_http_client(
+        identity_pem: &[u8],
+        root_certificate_pem: Option<&[u8]>,
+    ) -> Result<
+        impl Fn(HttpRequest) -> Result<HttpResponse, Error<reqwest::Error>>,
+        Error<reqwest::Error>,
+    > {
+        let mut builder = ReqwestClientBuilder::new()
+            .set_identity(reqwest::Identity::from_pem(identity_pem).map_err(Error::Reqwest)?);
+        if let Some(root_certificate_pem) = root_certificate_pem {
+            builder = builder.add_root_certificate(
+                reqwest::Certificate::from_pem(root_certificate_pem).map_err(Error::Reqwest)?,
+            );
+        }
+
+        Ok(builder.build()?.http_client())
+    }
+
+    ///
+    /// Reusable synchronous HTTP client, created using `ReqwestClientBuilder::build`.
+    ///
+    /// This client (and a reference to it) implements `SyncHttpClient`, so it may be passed
+    /// directly to any of the `request` methods of this crate. The underlying reqwest client
+    /// maintains a pool of connections, which is shared between clones of this client.
+    ///
+    #[derive(Clone, Debug)]
+    pub struct ReqwestClient {
+        client: blocking::Client,
+    }
+    impl ReqwestClient {
+        pub(super) fn new(client: blocking::Client) -> Self {
+            Self { client }
+        }
+
+        ///
+        /// Sends the given request.
+        ///
+        pub fn execute(&self, request: HttpRequest) -> Result<HttpResponse, Error<reqwest::Error>> {
+            execute(&self.client, request)
+        }
+
+        ///
+        /// Returns an HTTP client function sharing this client's connection pool, which may be
+        /// passed to any of the `request` methods of this crate.
+        ///
+        pub fn http_client(
+            &self,
+        ) -> impl Fn(HttpRequest) -> Result<HttpResponse, Error<reqwest::Error>> + Clone + Send + Sync
+        {
+            let client = self.client.clone();
+            move |request| execute(&client, request)
+        }
+    }
+    impl SyncHttpClient for ReqwestClient {
+        type Error = Error<reqwest::Error>;
 
+        fn call(self, request: HttpRequest) -> Result<HttpResponse, Self::Error> {
+            self.execute(request)
+        }
+    }
+    impl SyncHttpClient for &ReqwestClient {
+        type Error = Error<reqwest::Error>;
+
+        fn call(self, request: HttpRequest) -> Result<HttpResponse, Self::Error> {
+            self.execute(request)
+        }
+    }
+
+    fn execute(
+        client: &blocking::Client,
+        request: HttpRequest,
+    ) -> Result<HttpResponse, Error<reqwest::Error>> {
         #[cfg(feature = "reqwest-010")]
         let mut request_builder = client
             .request(request.method, request.url.as_str())
@@ -82,24 +316,108 @@ mod blocking {
 }
 
 mod async_client {
-    use super::super::{HttpRequest, HttpResponse};
-    use super::Error;
+    use super::super::{AsyncHttpClient, HttpRequest, HttpResponse};
+    use super::{AsyncHttpClientFuture, Error, ReqwestClientBuilder};
 
     pub use reqwest_0_10 as reqwest;
-    use reqwest_0_10::redirect::Policy as RedirectPolicy;
 
     ///
     /// Asynchronous HTTP client.
     ///
+    /// A new reqwest client is constructed for each request. Applications sending many requests
+    /// should use an `AsyncReqwestClient` instead.
+    ///
     pub async fn async_http_client(
         request: HttpRequest,
     ) -> Result<HttpResponse, Error<reqwest::Error>> {
-        let client = reqwest::Client::builder()
-            // Following redirects opens the client up to SSRF vulnerabilities.
-            .redirect(RedirectPolicy::none())
-            .build()
-            .map_err(Error::Reqwest)?;
+        ReqwestClientBuilder::new()
+            .build_async()?
+            .execute(request)
+            .await
+    }
+
+    ///
+    /// Returns an asynchronous HTTP client that presents a TLS client certificate, for use with
+    /// mutual-TLS client authentication and certificate-bound access tokens
+    /// ([RFC 8705](https://tools.ietf.org/html/rfc8705)).
+    ///
+    /// See `mtls_http_client` for a description of the arguments.
+    ///
+    pub fn async_mtls_http_client(
+        identity_pem: &[u8],
+        root_certificate_pem: Option<&[u8]>,
+    ) -> Result<impl Fn(HttpRequest) -> AsyncHttpClientFuture, Error<reqwest::Error>> {
+        let mut builder = ReqwestClientBuilder::new()
+            .set_identity(reqwest::Identity::from_pem(identity_pem).map_err(Error::Reqwest)?);
+        if let Some(root_certificate_pem) = root_certificate_pem {
+            builder = builder.add_root_certificate(
+                reqwest::Certificate::from_pem(root_certificate_pem).map_err(Error::Reqwest)?,
+            );
+        }
+
+        Ok(builder.build_async()?.http_client())
+    }
+
+    ///
+    /// Reusable asynchronous HTTP client, created using `ReqwestClientBuilder::build_async`.
+    ///
+    /// This client (and a reference to it) implements `AsyncHttpClient`, so it may be passed
+    /// directly to any of the `request_async` methods of this crate. The underlying reqwest client
+    /// maintains a pool of connections, which is shared between clones of this client.
+    ///
+    #[derive(Clone, Debug)]
+    pub struct AsyncReqwestClient {
+        client: reqwest::Client,
+    }
+    impl AsyncReqwestClient {
+        pub(super) fn new(client: reqwest::Client) -> Self {
+            Self { client }
+        }
+
+        ///
+        /// Sends the given request.
+        ///
+        pub async fn execute(
+            &self,
+            request: HttpRequest,
+        ) -> Result<HttpResponse, Error<reqwest::Error>> {
+            execute(&self.client, request).await
+        }
+
+        ///
+        /// Returns an HTTP client function sharing this client's connection pool, which may be
+        /// passed to any of the `request_async` methods of this crate.
+        ///
+        pub fn http_client(
+            &self,
+        ) -> impl Fn(HttpRequest) -> AsyncHttpClientFuture + Clone + Send + Sync {
+            let client = self.clone();
+            move |request| (&client).call(request)
+        }
+    }
+    impl AsyncHttpClient for AsyncReqwestClient {
+        type Error = Error<reqwest::Error>;
+        type Future = AsyncHttpClientFuture;
 
+        fn call(self, request: HttpRequest) -> AsyncHttpClientFuture {
+            (&self).call(request)
+        }
+    }
+    impl AsyncHttpClient for &AsyncReqwestClient {
+        type Error = Error<reqwest::Error>;
+        type Future = AsyncHttpClientFuture;
+
+        fn call(self, request: HttpRequest) -> AsyncHttpClientFuture {
+            // The client is reference-counted internally, so cloning it is cheap.
+            let client = self.client.clone();
+            Box::pin(async move { execute(&client, request).await })
+        }
+    }
+
+    async fn execute(
+        client: &reqwest::Client,
+        request: HttpRequest,
+    ) -> Result<HttpResponse, Error<reqwest::Error>> {
         let mut request_builder = client
             .request(request.method, request.url.as_str())
             .body(request.body);
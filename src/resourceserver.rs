@@ -0,0 +1,518 @@
+use std::error::Error;
+use std::fmt::Error as FormatterError;
+use std::fmt::{Debug, Formatter};
+use std::future::Future;
+use std::marker::PhantomData;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use http::header::{HeaderMap, HeaderValue, AUTHORIZATION, WWW_AUTHENTICATE};
+use http::status::StatusCode;
+
+use super::{
+    AccessToken, AsyncHttpClient, Client, ErrorResponse, RequestTokenError, Scope, SyncHttpClient,
+    TokenInspectionResponse, TokenResponse, TokenType,
+};
+
+///
+/// Default clock skew tolerated when checking the `exp` and `nbf` claims of access tokens.
+///
+pub const DEFAULT_LEEWAY: Duration = Duration::from_secs(60);
+
+///
+/// Validates bearer tokens presented to a resource server, as described in
+/// [RFC 6750](https://tools.ietf.org/html/rfc6750).
+///
+/// The validator extracts the access token from the `Authorization: Bearer` header of an
+/// incoming `http::Request`, passes it to a verifier (typically token introspection via
+/// `Client::introspect`) and checks the resulting `TokenInspectionResponse`:
+///
+/// * the token must be `active`;
+/// * the current time must be before `exp` and not before `nbf` (allowing for `set_leeway`);
+/// * if configured, `aud` must include the expected audience and `iss` must match the expected
+///   issuer;
+/// * the token must have been granted all of the required scopes.
+///
+/// Rejections are returned as `BearerTokenError` values, which may be converted into responses
+/// containing the appropriate `WWW-Authenticate` challenge using `error_response`.
+///
+pub struct BearerTokenValidator<TT, TIR>
+where
+    TT: TokenType,
+    TIR: TokenInspectionResponse<TT>,
+{
+    audience: Option<String>,
+    issuer: Option<String>,
+    leeway: Duration,
+    realm: Option<String>,
+    required_scopes: Vec<Scope>,
+    time_fn: Arc<dyn Fn() -> DateTime<Utc> + Send + Sync>,
+    _phantom: PhantomData<(TT, TIR)>,
+}
+impl<TT, TIR> BearerTokenValidator<TT, TIR>
+where
+    TT: TokenType,
+    TIR: TokenInspectionResponse<TT>,
+{
+    ///
+    /// Creates a new validator that accepts any active token.
+    ///
+    pub fn new() -> Self {
+        Self {
+            audience: None,
+            issuer: None,
+            leeway: DEFAULT_LEEWAY,
+            realm: None,
+            required_scopes: Vec::new(),
+            time_fn: Arc::new(Utc::now),
+            _phantom: PhantomData,
+        }
+    }
+
+    ///
+    /// Requires the `aud` claim of the token to include the given audience (e.g., the
+    /// identifier of this resource server).
+    ///
+    pub fn set_audience(mut self, audience: String) -> Self {
+        self.audience = Some(audience);
+        self
+    }
+
+    ///
+    /// Requires the `iss` claim of the token to match the given issuer.
+    ///
+    pub fn set_issuer(mut self, issuer: String) -> Self {
+        self.issuer = Some(issuer);
+        self
+    }
+
+    ///
+    /// Sets the clock skew tolerated when checking the `exp` and `nbf` claims. Defaults to
+    /// `DEFAULT_LEEWAY`.
+    ///
+    pub fn set_leeway(mut self, leeway: Duration) -> Self {
+        self.leeway = leeway;
+        self
+    }
+
+    ///
+    /// Sets the `realm` attribute of `WWW-Authenticate` challenges.
+    ///
+    pub fn set_realm(mut self, realm: String) -> Self {
+        self.realm = Some(realm);
+        self
+    }
+
+    ///
+    /// Appends a scope that must have been granted to the token.
+    ///
+    pub fn add_required_scope(mut self, scope: Scope) -> Self {
+        self.required_scopes.push(scope);
+        self
+    }
+
+    ///
+    /// Specifies a function for returning the current time.
+    ///
+    /// This function is used while checking the `exp` and `nbf` claims.
+    ///
+    pub fn set_time_fn<T>(mut self, time_fn: T) -> Self
+    where
+        T: Fn() -> DateTime<Utc> + Send + Sync + 'static,
+    {
+        self.time_fn = Arc::new(time_fn);
+        self
+    }
+
+    ///
+    /// Validates the bearer token of the given request using a custom verifier.
+    ///
+    /// The verifier is called with the access token extracted from the request and returns the
+    /// token's metadata. Verifiers should return `BearerTokenError::InvalidToken` for tokens
+    /// they reject (e.g., JWTs with an invalid signature) and `BearerTokenError::Verifier` if
+    /// the token could not be verified (e.g., because the authorization server is unreachable).
+    ///
+    pub fn validate<B, F, E>(
+        &self,
+        request: &http::Request<B>,
+        verifier: F,
+    ) -> Result<TIR, BearerTokenError<E>>
+    where
+        F: FnOnce(AccessToken) -> Result<TIR, BearerTokenError<E>>,
+        E: Error + 'static,
+    {
+        let token = bearer_token(request.headers())?;
+        let token_info = verifier(token)?;
+        self.validate_token_info(&token_info)?;
+        Ok(token_info)
+    }
+
+    ///
+    /// Asynchronously validates the bearer token of the given request using a custom verifier.
+    ///
+    /// See `validate` for details.
+    ///
+    pub async fn validate_async<B, C, F, E>(
+        &self,
+        request: &http::Request<B>,
+        verifier: C,
+    ) -> Result<TIR, BearerTokenError<E>>
+    where
+        C: FnOnce(AccessToken) -> F,
+        F: Future<Output = Result<TIR, BearerTokenError<E>>>,
+        E: Error + 'static,
+    {
+        let token = bearer_token(request.headers())?;
+        let token_info = verifier(token).await?;
+        self.validate_token_info(&token_info)?;
+        Ok(token_info)
+    }
+
+    ///
+    /// Validates the bearer token of the given request by introspecting it using the given
+    /// client ([RFC 7662](https://tools.ietf.org/html/rfc7662)).
+    ///
+    /// The client must have an introspection URL (see `Client::set_introspection_url`) and the
+    /// credentials of this resource server.
+    ///
+    pub fn introspect<B, TE, TR, HC, RE>(
+        &self,
+        request: &http::Request<B>,
+        client: &Client<TE, TR, TT, TIR>,
+        http_client: HC,
+    ) -> Result<TIR, BearerTokenError<RequestTokenError<RE, TE>>>
+    where
+        TE: ErrorResponse + 'static,
+        TR: TokenResponse<TT>,
+        HC: SyncHttpClient<Error = RE>,
+        RE: Error + 'static,
+    {
+        self.validate(request, |token| {
+            client
+                .introspect(&token)
+                .set_token_type_hint("access_token")
+                .request(http_client)
+                .map_err(BearerTokenError::Verifier)
+        })
+    }
+
+    ///
+    /// Asynchronously validates the bearer token of the given request by introspecting it
+    /// using the given client ([RFC 7662](https://tools.ietf.org/html/rfc7662)).
+    ///
+    /// See `introspect` for details.
+    ///
+    pub async fn introspect_async<B, TE, TR, C, RE>(
+        &self,
+        request: &http::Request<B>,
+        client: &Client<TE, TR, TT, TIR>,
+        http_client: C,
+    ) -> Result<TIR, BearerTokenError<RequestTokenError<RE, TE>>>
+    where
+        TE: ErrorResponse + 'static,
+        TR: TokenResponse<TT>,
+        C: AsyncHttpClient<Error = RE>,
+        RE: Error + 'static,
+    {
+        self.validate_async(request, |token| async move {
+            client
+                .introspect(&token)
+                .set_token_type_hint("access_token")
+                .request_async(http_client)
+                .await
+                .map_err(BearerTokenError::Verifier)
+        })
+        .await
+    }
+
+    ///
+    /// Checks the metadata of an access token (e.g., an introspection response) against the
+    /// requirements of this validator.
+    ///
+    pub fn validate_token_info<E>(&self, token_info: &TIR) -> Result<(), BearerTokenError<E>>
+    where
+        E: Error + 'static,
+    {
+        if !token_info.active() {
+            return Err(BearerTokenError::InvalidToken(
+                "The access token is not active".to_string(),
+            ));
+        }
+
+        let now = (*self.time_fn)();
+        let leeway =
+            chrono::Duration::from_std(self.leeway).unwrap_or_else(|_| chrono::Duration::zero());
+        if let Some(exp) = token_info.exp() {
+            if now >= exp + leeway {
+                return Err(BearerTokenError::InvalidToken(
+                    "The access token expired".to_string(),
+                ));
+            }
+        }
+        if let Some(nbf) = token_info.nbf() {
+            if now < nbf - leeway {
+                return Err(BearerTokenError::InvalidToken(
+                    "The access token is not yet valid".to_string(),
+                ));
+            }
+        }
+
+        if let Some(ref audience) = self.audience {
+            if !token_info
+                .aud()
+                .map(|aud| aud.iter().any(|aud| aud == audience))
+                .unwrap_or(false)
+            {
+                return Err(BearerTokenError::InvalidToken(
+                    "The access token is not intended for this resource server".to_string(),
+                ));
+            }
+        }
+        if let Some(ref issuer) = self.issuer {
+            if token_info.iss() != Some(issuer.as_str()) {
+                return Err(BearerTokenError::InvalidToken(
+                    "The access token was issued by an unexpected issuer".to_string(),
+                ));
+            }
+        }
+
+        let granted_scopes = token_info.scopes();
+        if !self.required_scopes.iter().all(|required_scope| {
+            granted_scopes
+                .map(|scopes| scopes.contains(required_scope))
+                .unwrap_or(false)
+        }) {
+            return Err(BearerTokenError::InsufficientScope(
+                self.required_scopes.clone(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    ///
+    /// Returns the `WWW-Authenticate` challenge corresponding to the given rejection, as
+    /// defined in [Section 3 of RFC 6750](https://tools.ietf.org/html/rfc6750#section-3).
+    ///
+    /// Returns `None` for `BearerTokenError::Verifier` errors, which do not indicate a problem
+    /// with the request.
+    ///
+    pub fn www_authenticate<E>(&self, error: &BearerTokenError<E>) -> Option<HeaderValue>
+    where
+        E: Error + 'static,
+    {
+        let mut attributes = Vec::new();
+        if let Some(ref realm) = self.realm {
+            attributes.push(("realm", realm.clone()));
+        }
+        match error {
+            BearerTokenError::MissingToken => {}
+            BearerTokenError::InvalidRequest(description)
+            | BearerTokenError::InvalidToken(description) => {
+                attributes.push(("error", error.error_code()?.to_string()));
+                attributes.push(("error_description", description.clone()));
+            }
+            BearerTokenError::InsufficientScope(scopes) => {
+                attributes.push(("error", error.error_code()?.to_string()));
+                attributes.push((
+                    "error_description",
+                    "The access token does not have the required scopes".to_string(),
+                ));
+                attributes.push((
+                    "scope",
+                    scopes
+                        .iter()
+                        .map(|scope| scope.as_str())
+                        .collect::<Vec<_>>()
+                        .join(" "),
+                ));
+            }
+            BearerTokenError::Verifier(_) => return None,
+        }
+
+        let challenge = attributes
+            .iter()
+            .map(|(name, value)| format!("{}=\"{}\"", name, quote(value)))
+            .collect::<Vec<_>>()
+            .join(", ");
+        HeaderValue::from_str(&if challenge.is_empty() {
+            "Bearer".to_string()
+        } else {
+            format!("Bearer {}", challenge)
+        })
+        .ok()
+    }
+
+    ///
+    /// Returns an empty-bodied response with the status code and `WWW-Authenticate` challenge
+    /// corresponding to the given rejection.
+    ///
+    pub fn error_response<E, B>(&self, error: &BearerTokenError<E>) -> http::Response<B>
+    where
+        E: Error + 'static,
+        B: Default,
+    {
+        let mut response = http::Response::new(B::default());
+        *response.status_mut() = error.status_code();
+        if let Some(challenge) = self.www_authenticate(error) {
+            response.headers_mut().insert(WWW_AUTHENTICATE, challenge);
+        }
+        response
+    }
+}
+impl<TT, TIR> Default for BearerTokenValidator<TT, TIR>
+where
+    TT: TokenType,
+    TIR: TokenInspectionResponse<TT>,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl<TT, TIR> Debug for BearerTokenValidator<TT, TIR>
+where
+    TT: TokenType,
+    TIR: TokenInspectionResponse<TT>,
+{
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FormatterError> {
+        f.debug_struct("BearerTokenValidator")
+            .field("audience", &self.audience)
+            .field("issuer", &self.issuer)
+            .field("leeway", &self.leeway)
+            .field("realm", &self.realm)
+            .field("required_scopes", &self.required_scopes)
+            .finish()
+    }
+}
+
+///
+/// Rejection of a request to a protected resource.
+///
+/// Each variant maps to the HTTP status code and error code defined in
+/// [Section 3.1 of RFC 6750](https://tools.ietf.org/html/rfc6750#section-3.1).
+///
+#[derive(Debug, thiserror::Error)]
+pub enum BearerTokenError<E>
+where
+    E: Error + 'static,
+{
+    ///
+    /// The request did not include a bearer token (`401 Unauthorized` without an error code).
+    ///
+    #[error("Missing bearer token")]
+    MissingToken,
+    ///
+    /// The request included a malformed `Authorization` header (`invalid_request`).
+    ///
+    #[error("Invalid request: {}", _0)]
+    InvalidRequest(String),
+    ///
+    /// The access token is expired, revoked, malformed, or invalid for other reasons
+    /// (`invalid_token`).
+    ///
+    #[error("Invalid token: {}", _0)]
+    InvalidToken(String),
+    ///
+    /// The access token was not granted the given required scopes (`insufficient_scope`).
+    ///
+    #[error("Insufficient scope")]
+    InsufficientScope(Vec<Scope>),
+    ///
+    /// The verifier failed to verify the token (e.g., due to an introspection request failure).
+    ///
+    #[error("Failed to verify token")]
+    Verifier(#[source] E),
+}
+impl<E> BearerTokenError<E>
+where
+    E: Error + 'static,
+{
+    ///
+    /// The error code included in the `WWW-Authenticate` challenge, if any.
+    ///
+    pub fn error_code(&self) -> Option<&'static str> {
+        match self {
+            BearerTokenError::InvalidRequest(_) => Some("invalid_request"),
+            BearerTokenError::InvalidToken(_) => Some("invalid_token"),
+            BearerTokenError::InsufficientScope(_) => Some("insufficient_scope"),
+            BearerTokenError::MissingToken | BearerTokenError::Verifier(_) => None,
+        }
+    }
+
+    ///
+    /// The HTTP status code with which the resource server should respond.
+    ///
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            BearerTokenError::MissingToken | BearerTokenError::InvalidToken(_) => {
+                StatusCode::UNAUTHORIZED
+            }
+            BearerTokenError::InvalidRequest(_) => StatusCode::BAD_REQUEST,
+            BearerTokenError::InsufficientScope(_) => StatusCode::FORBIDDEN,
+            BearerTokenError::Verifier(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+///
+/// Extracts the bearer token from the `Authorization` header, as described in
+/// [Section 2.1 of RFC 6750](https://tools.ietf.org/html/rfc6750#section-2.1).
+///
+pub fn bearer_token<E>(headers: &HeaderMap) -> Result<AccessToken, BearerTokenError<E>>
+where
+    E: Error + 'static,
+{
+    let mut authorization = headers.get_all(AUTHORIZATION).iter();
+    let value = match (authorization.next(), authorization.next()) {
+        (None, _) => return Err(BearerTokenError::MissingToken),
+        (Some(value), None) => value,
+        (Some(_), Some(_)) => {
+            return Err(BearerTokenError::InvalidRequest(
+                "Multiple Authorization headers".to_string(),
+            ))
+        }
+    };
+    let value = value.to_str().map_err(|_| {
+        BearerTokenError::InvalidRequest("Malformed Authorization header".to_string())
+    })?;
+
+    let mut parts = value.splitn(2, ' ');
+    match (parts.next(), parts.next()) {
+        (Some(scheme), Some(token)) if scheme.eq_ignore_ascii_case("bearer") => {
+            let token = token.trim();
+            if is_b64token(token) {
+                Ok(AccessToken::new(token.to_string()))
+            } else {
+                Err(BearerTokenError::InvalidRequest(
+                    "Malformed bearer token".to_string(),
+                ))
+            }
+        }
+        (Some(scheme), None) if scheme.eq_ignore_ascii_case("bearer") => Err(
+            BearerTokenError::InvalidRequest("Malformed bearer token".to_string()),
+        ),
+        // Other authentication schemes do not provide a bearer token.
+        _ => Err(BearerTokenError::MissingToken),
+    }
+}
+
+/// Returns whether the token matches the `b64token` syntax of RFC 6750.
+fn is_b64token(token: &str) -> bool {
+    let token_chars = token.trim_end_matches('=');
+    !token_chars.is_empty()
+        && token_chars.chars().all(|c| {
+            c.is_ascii_alphanumeric()
+                || c == '-'
+                || c == '.'
+                || c == '_'
+                || c == '~'
+                || c == '+'
+                || c == '/'
+        })
+}
+
+/// Escapes a value for use in a quoted-string.
+fn quote(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
@@ -0,0 +1,161 @@
+use std::fmt::Error as FormatterError;
+use std::fmt::{Debug, Display, Formatter};
+
+use serde::{Deserialize, Serialize};
+
+use super::{AccessToken, ErrorResponseType, RefreshToken, StandardErrorResponse};
+use crate::basic::BasicErrorResponseType;
+
+///
+/// A token that can be revoked using the revocation endpoint.
+///
+/// See [Section 2.1 of RFC 7009](https://tools.ietf.org/html/rfc7009#section-2.1).
+///
+pub trait RevocableToken {
+    ///
+    /// The secret value of the token to be revoked.
+    ///
+    fn secret(&self) -> &str;
+
+    ///
+    /// A hint about the type of the token submitted for revocation, as defined by the
+    /// "OAuth Token Type Hints" registry of RFC 7009 (e.g., `access_token` or `refresh_token`).
+    ///
+    fn type_hint(&self) -> Option<&str>;
+}
+
+impl RevocableToken for AccessToken {
+    fn secret(&self) -> &str {
+        AccessToken::secret(self)
+    }
+
+    fn type_hint(&self) -> Option<&str> {
+        Some("access_token")
+    }
+}
+
+impl RevocableToken for RefreshToken {
+    fn secret(&self) -> &str {
+        RefreshToken::secret(self)
+    }
+
+    fn type_hint(&self) -> Option<&str> {
+        Some("refresh_token")
+    }
+}
+
+///
+/// Either an access token or a refresh token, for callers that need to store a token of either
+/// kind prior to revoking it.
+///
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StandardRevocableToken {
+    ///
+    /// An access token issued by the token endpoint.
+    ///
+    AccessToken(AccessToken),
+    ///
+    /// A refresh token issued by the token endpoint.
+    ///
+    RefreshToken(RefreshToken),
+}
+impl RevocableToken for StandardRevocableToken {
+    fn secret(&self) -> &str {
+        match self {
+            StandardRevocableToken::AccessToken(token) => token.secret(),
+            StandardRevocableToken::RefreshToken(token) => token.secret(),
+        }
+    }
+
+    fn type_hint(&self) -> Option<&str> {
+        match self {
+            StandardRevocableToken::AccessToken(token) => RevocableToken::type_hint(token),
+            StandardRevocableToken::RefreshToken(token) => RevocableToken::type_hint(token),
+        }
+    }
+}
+impl From<AccessToken> for StandardRevocableToken {
+    fn from(token: AccessToken) -> Self {
+        StandardRevocableToken::AccessToken(token)
+    }
+}
+impl From<RefreshToken> for StandardRevocableToken {
+    fn from(token: RefreshToken) -> Self {
+        StandardRevocableToken::RefreshToken(token)
+    }
+}
+
+///
+/// Token revocation error types.
+///
+/// These error types are defined in
+/// [Section 5.2 of RFC 6749](https://tools.ietf.org/html/rfc6749#section-5.2) and
+/// [Section 2.2.1 of RFC 7009](https://tools.ietf.org/html/rfc7009#section-2.2.1).
+///
+#[derive(Clone, PartialEq)]
+pub enum RevocationErrorResponseType {
+    ///
+    /// The authorization server does not support the revocation of the presented token type.
+    /// That is, the client tried to revoke an access token on a server not supporting this
+    /// feature.
+    ///
+    UnsupportedTokenType,
+    ///
+    /// A Basic response type
+    ///
+    Basic(BasicErrorResponseType),
+}
+impl RevocationErrorResponseType {
+    fn from_str(s: &str) -> Self {
+        match BasicErrorResponseType::from_str(s) {
+            BasicErrorResponseType::Extension(ext) => match ext.as_str() {
+                "unsupported_token_type" => RevocationErrorResponseType::UnsupportedTokenType,
+                _ => RevocationErrorResponseType::Basic(BasicErrorResponseType::Extension(ext)),
+            },
+            basic => RevocationErrorResponseType::Basic(basic),
+        }
+    }
+}
+impl AsRef<str> for RevocationErrorResponseType {
+    fn as_ref(&self) -> &str {
+        match self {
+            RevocationErrorResponseType::UnsupportedTokenType => "unsupported_token_type",
+            RevocationErrorResponseType::Basic(basic) => basic.as_ref(),
+        }
+    }
+}
+impl<'de> serde::Deserialize<'de> for RevocationErrorResponseType {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::de::Deserializer<'de>,
+    {
+        let variant_str = String::deserialize(deserializer)?;
+        Ok(Self::from_str(&variant_str))
+    }
+}
+impl serde::ser::Serialize for RevocationErrorResponseType {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::ser::Serializer,
+    {
+        serializer.serialize_str(self.as_ref())
+    }
+}
+impl ErrorResponseType for RevocationErrorResponseType {}
+impl Debug for RevocationErrorResponseType {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FormatterError> {
+        Display::fmt(self, f)
+    }
+}
+
+impl Display for RevocationErrorResponseType {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FormatterError> {
+        write!(f, "{}", self.as_ref())
+    }
+}
+
+///
+/// Error response specialization for token revocation.
+///
+pub type RevocationErrorResponse = StandardErrorResponse<RevocationErrorResponseType>;
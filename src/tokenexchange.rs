@@ -0,0 +1,272 @@
+use std::fmt::Error as FormatterError;
+use std::fmt::{Debug, Display, Formatter};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    helpers, AccessToken, ExtraTokenFields, RefreshToken, Scope, TokenResponse, TokenType,
+};
+
+///
+/// The `grant_type` value used for token exchange requests.
+///
+pub const TOKEN_EXCHANGE_GRANT_TYPE: &str = "urn:ietf:params:oauth:grant-type:token-exchange";
+
+///
+/// Token type identifiers, as defined in
+/// [Section 3 of RFC 8693](https://tools.ietf.org/html/rfc8693#section-3).
+///
+/// These identifiers describe the type of the `subject_token`, `actor_token` and
+/// `requested_token_type` parameters of a token exchange request, as well as the
+/// `issued_token_type` of the response.
+///
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub enum TokenTypeIdentifier {
+    ///
+    /// An OAuth 2.0 access token (`urn:ietf:params:oauth:token-type:access_token`).
+    ///
+    AccessToken,
+    ///
+    /// An OAuth 2.0 refresh token (`urn:ietf:params:oauth:token-type:refresh_token`).
+    ///
+    RefreshToken,
+    ///
+    /// An OpenID Connect ID Token (`urn:ietf:params:oauth:token-type:id_token`).
+    ///
+    IdToken,
+    ///
+    /// A base64url-encoded SAML 1.1 assertion (`urn:ietf:params:oauth:token-type:saml1`).
+    ///
+    Saml1,
+    ///
+    /// A base64url-encoded SAML 2.0 assertion (`urn:ietf:params:oauth:token-type:saml2`).
+    ///
+    Saml2,
+    ///
+    /// A JSON Web Token (`urn:ietf:params:oauth:token-type:jwt`).
+    ///
+    Jwt,
+    ///
+    /// A token type identifier not defined by RFC 8693.
+    ///
+    Extension(String),
+}
+impl TokenTypeIdentifier {
+    fn from_str(s: &str) -> Self {
+        match s {
+            "urn:ietf:params:oauth:token-type:access_token" => TokenTypeIdentifier::AccessToken,
+            "urn:ietf:params:oauth:token-type:refresh_token" => TokenTypeIdentifier::RefreshToken,
+            "urn:ietf:params:oauth:token-type:id_token" => TokenTypeIdentifier::IdToken,
+            "urn:ietf:params:oauth:token-type:saml1" => TokenTypeIdentifier::Saml1,
+            "urn:ietf:params:oauth:token-type:saml2" => TokenTypeIdentifier::Saml2,
+            "urn:ietf:params:oauth:token-type:jwt" => TokenTypeIdentifier::Jwt,
+            ext => TokenTypeIdentifier::Extension(ext.to_string()),
+        }
+    }
+}
+impl AsRef<str> for TokenTypeIdentifier {
+    fn as_ref(&self) -> &str {
+        match *self {
+            TokenTypeIdentifier::AccessToken => "urn:ietf:params:oauth:token-type:access_token",
+            TokenTypeIdentifier::RefreshToken => "urn:ietf:params:oauth:token-type:refresh_token",
+            TokenTypeIdentifier::IdToken => "urn:ietf:params:oauth:token-type:id_token",
+            TokenTypeIdentifier::Saml1 => "urn:ietf:params:oauth:token-type:saml1",
+            TokenTypeIdentifier::Saml2 => "urn:ietf:params:oauth:token-type:saml2",
+            TokenTypeIdentifier::Jwt => "urn:ietf:params:oauth:token-type:jwt",
+            TokenTypeIdentifier::Extension(ref ext) => ext.as_str(),
+        }
+    }
+}
+impl<'de> serde::Deserialize<'de> for TokenTypeIdentifier {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::de::Deserializer<'de>,
+    {
+        let variant_str = String::deserialize(deserializer)?;
+        Ok(Self::from_str(&variant_str))
+    }
+}
+impl serde::ser::Serialize for TokenTypeIdentifier {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::ser::Serializer,
+    {
+        serializer.serialize_str(self.as_ref())
+    }
+}
+impl Debug for TokenTypeIdentifier {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FormatterError> {
+        Display::fmt(self, f)
+    }
+}
+impl Display for TokenTypeIdentifier {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FormatterError> {
+        write!(f, "{}", self.as_ref())
+    }
+}
+
+///
+/// Token exchange response.
+///
+/// This struct includes the fields defined in
+/// [Section 2.2.1 of RFC 8693](https://tools.ietf.org/html/rfc8693#section-2.2.1), as well as
+/// extensions defined by the `EF` type parameter. Note that the `access_token` field contains
+/// the issued security token even when it is not an OAuth 2.0 access token (see
+/// `issued_token_type`).
+///
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct TokenExchangeTokenResponse<EF, TT>
+where
+    EF: ExtraTokenFields,
+    TT: TokenType,
+{
+    access_token: AccessToken,
+    issued_token_type: TokenTypeIdentifier,
+    #[serde(bound = "TT: TokenType")]
+    #[serde(deserialize_with = "helpers::deserialize_untagged_enum_case_insensitive")]
+    token_type: TT,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    expires_in: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    refresh_token: Option<RefreshToken>,
+    #[serde(rename = "scope")]
+    #[serde(deserialize_with = "helpers::deserialize_space_delimited_vec")]
+    #[serde(serialize_with = "helpers::serialize_space_delimited_vec")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    scopes: Option<Vec<Scope>>,
+
+    #[serde(bound = "EF: ExtraTokenFields")]
+    #[serde(flatten)]
+    extra_fields: EF,
+}
+impl<EF, TT> TokenExchangeTokenResponse<EF, TT>
+where
+    EF: ExtraTokenFields,
+    TT: TokenType,
+{
+    ///
+    /// Instantiate a new token exchange response.
+    ///
+    pub fn new(
+        access_token: AccessToken,
+        issued_token_type: TokenTypeIdentifier,
+        token_type: TT,
+        extra_fields: EF,
+    ) -> Self {
+        Self {
+            access_token,
+            issued_token_type,
+            token_type,
+            expires_in: None,
+            refresh_token: None,
+            scopes: None,
+            extra_fields,
+        }
+    }
+
+    ///
+    /// REQUIRED. An identifier for the representation of the issued security token.
+    ///
+    pub fn issued_token_type(&self) -> &TokenTypeIdentifier {
+        &self.issued_token_type
+    }
+
+    ///
+    /// Set the `access_token` field.
+    ///
+    pub fn set_access_token(&mut self, access_token: AccessToken) {
+        self.access_token = access_token;
+    }
+
+    ///
+    /// Set the `issued_token_type` field.
+    ///
+    pub fn set_issued_token_type(&mut self, issued_token_type: TokenTypeIdentifier) {
+        self.issued_token_type = issued_token_type;
+    }
+
+    ///
+    /// Set the `token_type` field.
+    ///
+    pub fn set_token_type(&mut self, token_type: TT) {
+        self.token_type = token_type;
+    }
+
+    ///
+    /// Set the `expires_in` field.
+    ///
+    pub fn set_expires_in(&mut self, expires_in: Option<&Duration>) {
+        self.expires_in = expires_in.map(Duration::as_secs);
+    }
+
+    ///
+    /// Set the `refresh_token` field.
+    ///
+    pub fn set_refresh_token(&mut self, refresh_token: Option<RefreshToken>) {
+        self.refresh_token = refresh_token;
+    }
+
+    ///
+    /// Set the `scopes` field.
+    ///
+    pub fn set_scopes(&mut self, scopes: Option<Vec<Scope>>) {
+        self.scopes = scopes;
+    }
+
+    ///
+    /// Extra fields defined by the client application.
+    ///
+    pub fn extra_fields(&self) -> &EF {
+        &self.extra_fields
+    }
+
+    ///
+    /// Set the extra fields defined by the client application.
+    ///
+    pub fn set_extra_fields(&mut self, extra_fields: EF) {
+        self.extra_fields = extra_fields;
+    }
+}
+impl<EF, TT> TokenResponse<TT> for TokenExchangeTokenResponse<EF, TT>
+where
+    EF: ExtraTokenFields,
+    TT: TokenType,
+{
+    ///
+    /// REQUIRED. The security token issued by the authorization server in response to the token
+    /// exchange request.
+    ///
+    fn access_token(&self) -> &AccessToken {
+        &self.access_token
+    }
+    ///
+    /// REQUIRED. The type of the issued token as described in
+    /// [Section 7.1 of RFC 6749](https://tools.ietf.org/html/rfc6749#section-7.1). If the issued
+    /// token is not an access token, the authorization server returns `N_A`.
+    ///
+    fn token_type(&self) -> &TT {
+        &self.token_type
+    }
+    ///
+    /// RECOMMENDED. The lifetime in seconds of the issued token.
+    ///
+    fn expires_in(&self) -> Option<Duration> {
+        self.expires_in.map(Duration::from_secs)
+    }
+    ///
+    /// OPTIONAL. A refresh token, which is typically only issued when the exchange grants
+    /// long-lived access to the target resource.
+    ///
+    fn refresh_token(&self) -> Option<&RefreshToken> {
+        self.refresh_token.as_ref()
+    }
+    ///
+    /// OPTIONAL, if the scope of the issued token is identical to the scope requested by the
+    /// client; otherwise, REQUIRED.
+    ///
+    fn scopes(&self) -> Option<&Vec<Scope>> {
+        self.scopes.as_ref()
+    }
+}
@@ -0,0 +1,523 @@
+use std::error::Error;
+use std::fmt::Error as FormatterError;
+use std::fmt::{Debug, Formatter};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::task::{Context, Poll, Waker};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+
+use super::tokenstore::{StoredToken, TokenKey, TokenStore};
+use super::{
+    AccessToken, AsyncHttpClient, Client, ErrorResponse, RefreshToken, RequestTokenError,
+    SyncHttpClient, TokenInspectionResponse, TokenResponse, TokenType,
+};
+
+///
+/// Default amount of time before the access token expires at which `TokenManager` refreshes it.
+///
+pub const DEFAULT_REFRESH_SKEW: Duration = Duration::from_secs(60);
+
+///
+/// Manages the lifecycle of an access token, refreshing it as needed.
+///
+/// The manager records the absolute expiration time of the access token when the token response
+/// is received (based on its `expires_in` field) and uses the refresh token to obtain a new
+/// access token once the current one is about to expire (see `set_refresh_skew`). If the
+/// authorization server issues a new refresh token
+/// ([Section 6 of RFC 6749](https://tools.ietf.org/html/rfc6749#section-6)), the new refresh
+/// token replaces the previous one. Otherwise, the previous refresh token is retained.
+///
+/// A `TokenManager` may be shared between threads or tasks (e.g., using an `Arc`). Concurrent
+/// callers of `access_token` and `access_token_async` share a single in-flight refresh request
+/// rather than each sending their own request to the token endpoint. If the shared refresh
+/// fails, each waiting caller returns `RequestTokenError::Other` describing the failure instead
+/// of retrying the refresh itself.
+///
+/// Access tokens without an `expires_in` field are assumed to remain valid until `expire` is
+/// called (e.g., after a resource server rejects the token).
+///
+/// Refreshed tokens may be written back to a `tokenstore::TokenStore` using `set_token_store`.
+///
+#[derive(Debug)]
+pub struct TokenManager<TE, TR, TT, TIR>
+where
+    TE: ErrorResponse,
+    TR: TokenResponse<TT>,
+    TT: TokenType,
+    TIR: TokenInspectionResponse<TT>,
+{
+    client: Client<TE, TR, TT, TIR>,
+    refresh_skew: Duration,
+    state: Mutex<TokenState<TR>>,
+    refreshed: Condvar,
+    token_store: Option<TokenStoreWriter<TR>>,
+}
+
+type SaveTokenFn<TR> = dyn Fn(&TokenKey, &StoredToken<TR>) -> Result<(), String> + Send + Sync;
+
+struct TokenStoreWriter<TR> {
+    key: TokenKey,
+    save: Box<SaveTokenFn<TR>>,
+}
+impl<TR> Debug for TokenStoreWriter<TR> {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FormatterError> {
+        f.debug_struct("TokenStoreWriter")
+            .field("key", &self.key)
+            .finish()
+    }
+}
+
+#[derive(Debug)]
+struct TokenState<TR> {
+    token_response: TR,
+    refresh_token: Option<RefreshToken>,
+    expires_at: Option<DateTime<Utc>>,
+    expired: bool,
+    refreshing: bool,
+    generation: u64,
+    // Generation of the most recent failed refresh and a description of the error.
+    refresh_error: Option<(u64, String)>,
+    wakers: Vec<Waker>,
+}
+impl<TR> TokenState<TR> {
+    fn check_refresh_error<RE, TE>(&self, generation: u64) -> Result<(), RequestTokenError<RE, TE>>
+    where
+        RE: Error + 'static,
+        TE: ErrorResponse + 'static,
+    {
+        match self.refresh_error {
+            Some((failed_generation, ref err)) if failed_generation == generation => Err(
+                RequestTokenError::Other(format!("token refresh failed: {}", err)),
+            ),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl<TE, TR, TT, TIR> TokenManager<TE, TR, TT, TIR>
+where
+    TE: ErrorResponse + 'static,
+    TR: TokenResponse<TT>,
+    TT: TokenType,
+    TIR: TokenInspectionResponse<TT>,
+{
+    ///
+    /// Instantiate a new token manager from a token response that was just received from the
+    /// authorization server.
+    ///
+    /// The `client` must be configured with a token URL in order to refresh the access token.
+    ///
+    pub fn new(client: Client<TE, TR, TT, TIR>, token_response: TR) -> Self {
+        let expires_at = expires_at(&token_response);
+        Self::new_with_expiration(client, token_response, expires_at)
+    }
+
+    ///
+    /// Instantiate a new token manager from a previously received token response that expires
+    /// at the given time.
+    ///
+    /// This is useful when restoring a token response that was persisted, since the response's
+    /// `expires_in` field is relative to the time it was issued.
+    ///
+    pub fn new_with_expiration(
+        client: Client<TE, TR, TT, TIR>,
+        token_response: TR,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> Self {
+        Self {
+            client,
+            refresh_skew: DEFAULT_REFRESH_SKEW,
+            state: Mutex::new(TokenState {
+                refresh_token: token_response.refresh_token().cloned(),
+                token_response,
+                expires_at,
+                expired: false,
+                refreshing: false,
+                generation: 0,
+                refresh_error: None,
+                wakers: Vec::new(),
+            }),
+            refreshed: Condvar::new(),
+            token_store: None,
+        }
+    }
+
+    ///
+    /// Instantiate a new token manager from a token previously loaded from a
+    /// `tokenstore::TokenStore`.
+    ///
+    pub fn from_stored_token(
+        client: Client<TE, TR, TT, TIR>,
+        stored_token: StoredToken<TR>,
+    ) -> Self {
+        let (token_response, refresh_token, expires_at) = stored_token.into_parts();
+        let manager = Self::new_with_expiration(client, token_response, expires_at);
+        if refresh_token.is_some() {
+            manager.lock().refresh_token = refresh_token;
+        }
+        manager
+    }
+
+    ///
+    /// Writes the token back to the given store (using the given key) each time it is
+    /// refreshed.
+    ///
+    /// If the refreshed token can't be saved, `access_token` and `access_token_async` return
+    /// `RequestTokenError::Other`. The refreshed token is still used by subsequent calls.
+    ///
+    pub fn set_token_store<S>(mut self, token_store: Arc<S>, key: TokenKey) -> Self
+    where
+        S: TokenStore<TR> + ?Sized + 'static,
+    {
+        self.token_store = Some(TokenStoreWriter {
+            key,
+            save: Box::new(move |key, stored_token| {
+                token_store
+                    .save(key, stored_token)
+                    .map_err(|err| err.to_string())
+            }),
+        });
+        self
+    }
+
+    ///
+    /// Sets how long before the access token expires it should be refreshed.
+    ///
+    /// Defaults to `DEFAULT_REFRESH_SKEW` (60 seconds).
+    ///
+    pub fn set_refresh_skew(mut self, refresh_skew: Duration) -> Self {
+        self.refresh_skew = refresh_skew;
+        self
+    }
+
+    ///
+    /// Returns the client used to refresh the access token.
+    ///
+    pub fn client(&self) -> &Client<TE, TR, TT, TIR> {
+        &self.client
+    }
+
+    ///
+    /// Returns a copy of the most recent token response.
+    ///
+    /// Note that the refresh token included in this response may be missing if the
+    /// authorization server did not rotate the refresh token. Use `refresh_token` to obtain the
+    /// current refresh token.
+    ///
+    pub fn token_response(&self) -> TR
+    where
+        TR: Clone,
+    {
+        self.lock().token_response.clone()
+    }
+
+    ///
+    /// Returns a copy of the current token, suitable for saving to a `tokenstore::TokenStore`.
+    ///
+    pub fn stored_token(&self) -> StoredToken<TR>
+    where
+        TR: Clone,
+    {
+        let state = self.lock();
+        StoredToken::new(
+            state.token_response.clone(),
+            state.refresh_token.clone(),
+            state.expires_at,
+        )
+    }
+
+    ///
+    /// Returns the current refresh token, if any.
+    ///
+    pub fn refresh_token(&self) -> Option<RefreshToken> {
+        self.lock().refresh_token.clone()
+    }
+
+    ///
+    /// Returns the time at which the current access token expires, if known.
+    ///
+    pub fn expires_at(&self) -> Option<DateTime<Utc>> {
+        self.lock().expires_at
+    }
+
+    ///
+    /// Marks the current access token as expired, causing it to be refreshed by the next call to
+    /// `access_token` or `access_token_async`.
+    ///
+    pub fn expire(&self) {
+        self.lock().expired = true;
+    }
+
+    ///
+    /// Returns a valid access token, synchronously refreshing it first if it has expired or is
+    /// about to expire.
+    ///
+    /// If another thread is already refreshing the access token, this method waits for that
+    /// refresh to complete instead of sending another request.
+    ///
+    pub fn access_token<F, RE>(
+        &self,
+        http_client: F,
+    ) -> Result<AccessToken, RequestTokenError<RE, TE>>
+    where
+        for<'c> &'c F: SyncHttpClient<Error = RE>,
+        RE: Error + 'static,
+    {
+        let refresh_token = {
+            let mut state = self.lock();
+            loop {
+                if !self.needs_refresh(&state) {
+                    return Ok(state.token_response.access_token().clone());
+                }
+                if !state.refreshing {
+                    break;
+                }
+                let generation = state.generation;
+                while state.refreshing && state.generation == generation {
+                    state = self
+                        .refreshed
+                        .wait(state)
+                        .unwrap_or_else(|err| err.into_inner());
+                }
+                state.check_refresh_error(generation)?;
+            }
+            self.begin_refresh(&mut state)?
+        };
+
+        let guard = RefreshGuard { manager: self };
+        let result = self
+            .client
+            .exchange_refresh_token(&refresh_token)
+            .request(http_client);
+        guard.finish(result)
+    }
+
+    ///
+    /// Returns a valid access token, asynchronously refreshing it first if it has expired or is
+    /// about to expire.
+    ///
+    /// If another task is already refreshing the access token, this method waits for that
+    /// refresh to complete instead of sending another request.
+    ///
+    pub async fn access_token_async<C, RE>(
+        &self,
+        http_client: C,
+    ) -> Result<AccessToken, RequestTokenError<RE, TE>>
+    where
+        for<'c> &'c C: AsyncHttpClient<Error = RE>,
+        RE: Error + 'static,
+    {
+        let refresh_token = loop {
+            let generation = {
+                let mut state = self.lock();
+                if !self.needs_refresh(&state) {
+                    return Ok(state.token_response.access_token().clone());
+                }
+                if !state.refreshing {
+                    break self.begin_refresh(&mut state)?;
+                }
+                state.generation
+            };
+            RefreshWait {
+                manager: self,
+                generation,
+            }
+            .await;
+            self.lock().check_refresh_error(generation)?;
+        };
+
+        // The guard resets the refresh state even if this future is dropped before the refresh
+        // request completes.
+        let guard = RefreshGuard { manager: self };
+        let result = self
+            .client
+            .exchange_refresh_token(&refresh_token)
+            .request_async(http_client)
+            .await;
+        guard.finish(result)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, TokenState<TR>> {
+        self.state.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    fn needs_refresh(&self, state: &TokenState<TR>) -> bool {
+        if state.expired {
+            return true;
+        }
+        let expires_at = match state.expires_at {
+            Some(expires_at) => expires_at,
+            None => return false,
+        };
+        match chrono::Duration::from_std(self.refresh_skew)
+            .ok()
+            .and_then(|skew| Utc::now().checked_add_signed(skew))
+        {
+            Some(refresh_at) => refresh_at >= expires_at,
+            // The skew is too large to represent, so the token always needs to be refreshed.
+            None => true,
+        }
+    }
+
+    fn begin_refresh<RE>(
+        &self,
+        state: &mut TokenState<TR>,
+    ) -> Result<RefreshToken, RequestTokenError<RE, TE>>
+    where
+        RE: Error + 'static,
+    {
+        let refresh_token = state.refresh_token.clone().ok_or_else(|| {
+            RequestTokenError::Other("access token expired and no refresh token provided".into())
+        })?;
+        state.refreshing = true;
+        Ok(refresh_token)
+    }
+}
+
+fn expires_at<TR, TT>(token_response: &TR) -> Option<DateTime<Utc>>
+where
+    TR: TokenResponse<TT>,
+    TT: TokenType,
+{
+    token_response.expires_in().and_then(|expires_in| {
+        chrono::Duration::from_std(expires_in)
+            .ok()
+            .and_then(|expires_in| Utc::now().checked_add_signed(expires_in))
+    })
+}
+
+fn refresh_error_message<RE, TE>(err: &RequestTokenError<RE, TE>) -> String
+where
+    RE: Error + 'static,
+    TE: ErrorResponse + 'static,
+{
+    match err {
+        RequestTokenError::ServerResponse(err_response) => {
+            format!("server returned error response: {:?}", err_response)
+        }
+        RequestTokenError::Request(err) => format!("request failed: {}", err),
+        RequestTokenError::Parse(err, _) => format!("failed to parse server response: {}", err),
+        RequestTokenError::Other(msg) => msg.clone(),
+    }
+}
+
+///
+/// Resets the refresh state and wakes any waiting callers when dropped.
+///
+struct RefreshGuard<'a, TE, TR, TT, TIR>
+where
+    TE: ErrorResponse + 'static,
+    TR: TokenResponse<TT>,
+    TT: TokenType,
+    TIR: TokenInspectionResponse<TT>,
+{
+    manager: &'a TokenManager<TE, TR, TT, TIR>,
+}
+impl<'a, TE, TR, TT, TIR> RefreshGuard<'a, TE, TR, TT, TIR>
+where
+    TE: ErrorResponse + 'static,
+    TR: TokenResponse<TT>,
+    TT: TokenType,
+    TIR: TokenInspectionResponse<TT>,
+{
+    fn finish<RE>(
+        self,
+        result: Result<TR, RequestTokenError<RE, TE>>,
+    ) -> Result<AccessToken, RequestTokenError<RE, TE>>
+    where
+        RE: Error + 'static,
+    {
+        let token_response = match result {
+            Ok(token_response) => token_response,
+            Err(err) => {
+                let mut state = self.manager.lock();
+                state.refresh_error = Some((state.generation, refresh_error_message(&err)));
+                return Err(err);
+            }
+        };
+        let access_token = token_response.access_token().clone();
+
+        // Keep the previous refresh token unless the server issued a new one.
+        let refresh_token = match token_response.refresh_token() {
+            Some(refresh_token) => Some(refresh_token.clone()),
+            None => self.manager.lock().refresh_token.clone(),
+        };
+        let expires_at = expires_at(&token_response);
+        let stored_token = StoredToken::new(token_response, refresh_token, expires_at);
+
+        // The token store may be slow (e.g., writing to disk), so it's called without holding
+        // the lock. Other callers continue to wait for this refresh in the meantime.
+        let saved = match self.manager.token_store {
+            Some(ref token_store) => (token_store.save)(&token_store.key, &stored_token),
+            None => Ok(()),
+        };
+
+        let (token_response, refresh_token, expires_at) = stored_token.into_parts();
+        let mut state = self.manager.lock();
+        state.token_response = token_response;
+        state.refresh_token = refresh_token;
+        state.expires_at = expires_at;
+        state.expired = false;
+        drop(state);
+
+        saved.map_err(|err| {
+            RequestTokenError::Other(format!("failed to save refreshed token: {}", err))
+        })?;
+
+        Ok(access_token)
+    }
+}
+impl<'a, TE, TR, TT, TIR> Drop for RefreshGuard<'a, TE, TR, TT, TIR>
+where
+    TE: ErrorResponse + 'static,
+    TR: TokenResponse<TT>,
+    TT: TokenType,
+    TIR: TokenInspectionResponse<TT>,
+{
+    fn drop(&mut self) {
+        let mut state = self.manager.lock();
+        state.refreshing = false;
+        state.generation = state.generation.wrapping_add(1);
+        for waker in state.wakers.drain(..) {
+            waker.wake();
+        }
+        self.manager.refreshed.notify_all();
+    }
+}
+
+///
+/// Future that resolves once the in-flight refresh with the given generation completes.
+///
+struct RefreshWait<'a, TE, TR, TT, TIR>
+where
+    TE: ErrorResponse + 'static,
+    TR: TokenResponse<TT>,
+    TT: TokenType,
+    TIR: TokenInspectionResponse<TT>,
+{
+    manager: &'a TokenManager<TE, TR, TT, TIR>,
+    generation: u64,
+}
+impl<'a, TE, TR, TT, TIR> Future for RefreshWait<'a, TE, TR, TT, TIR>
+where
+    TE: ErrorResponse + 'static,
+    TR: TokenResponse<TT>,
+    TT: TokenType,
+    TIR: TokenInspectionResponse<TT>,
+{
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let mut state = self.manager.lock();
+        if !state.refreshing || state.generation != self.generation {
+            return Poll::Ready(());
+        }
+        if !state.wakers.iter().any(|waker| waker.will_wake(cx.waker())) {
+            state.wakers.push(cx.waker().clone());
+        }
+        Poll::Pending
+    }
+}
@@ -0,0 +1,480 @@
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::error::Error;
+use std::fmt::Error as FormatterError;
+use std::fmt::{Debug, Formatter};
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use chrono::{DateTime, Utc};
+use rand::{thread_rng, Rng};
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN};
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use super::{ClientId, RefreshToken, Scope};
+
+///
+/// Key identifying a token in a `TokenStore`.
+///
+/// Tokens are keyed by the client they were issued to, the user they were issued for, and the
+/// scopes that were requested. The order of the scopes is not significant.
+///
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct TokenKey {
+    client_id: ClientId,
+    user: String,
+    scopes: Vec<Scope>,
+}
+impl TokenKey {
+    ///
+    /// Instantiate a new token key.
+    ///
+    pub fn new(client_id: ClientId, user: String, mut scopes: Vec<Scope>) -> Self {
+        scopes.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+        scopes.dedup();
+        Self {
+            client_id,
+            user,
+            scopes,
+        }
+    }
+
+    ///
+    /// Client the token was issued to.
+    ///
+    pub fn client_id(&self) -> &ClientId {
+        &self.client_id
+    }
+
+    ///
+    /// User the token was issued for.
+    ///
+    pub fn user(&self) -> &str {
+        &self.user
+    }
+
+    ///
+    /// Scopes that were requested, sorted lexicographically.
+    ///
+    pub fn scopes(&self) -> &Vec<Scope> {
+        &self.scopes
+    }
+}
+
+///
+/// Token response persisted in a `TokenStore`.
+///
+/// In addition to the token response, this records the absolute time at which the access token
+/// expires (since the response's `expires_in` field is relative to the time it was issued) and
+/// the current refresh token (which may have been issued in an earlier response if the
+/// authorization server does not rotate refresh tokens).
+///
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct StoredToken<TR> {
+    token_response: TR,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    refresh_token: Option<RefreshToken>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    expires_at: Option<DateTime<Utc>>,
+}
+impl<TR> StoredToken<TR> {
+    ///
+    /// Instantiate a new stored token.
+    ///
+    pub fn new(
+        token_response: TR,
+        refresh_token: Option<RefreshToken>,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> Self {
+        Self {
+            token_response,
+            refresh_token,
+            expires_at,
+        }
+    }
+
+    ///
+    /// The most recent token response.
+    ///
+    pub fn token_response(&self) -> &TR {
+        &self.token_response
+    }
+
+    ///
+    /// The current refresh token, if any.
+    ///
+    pub fn refresh_token(&self) -> Option<&RefreshToken> {
+        self.refresh_token.as_ref()
+    }
+
+    ///
+    /// The time at which the access token expires, if known.
+    ///
+    pub fn expires_at(&self) -> Option<DateTime<Utc>> {
+        self.expires_at
+    }
+
+    ///
+    /// Returns the token response, refresh token and expiration time.
+    ///
+    pub fn into_parts(self) -> (TR, Option<RefreshToken>, Option<DateTime<Utc>>) {
+        (self.token_response, self.refresh_token, self.expires_at)
+    }
+}
+
+///
+/// Persistent storage for tokens.
+///
+/// Implementations must be safe to share between threads. See `tokenmanager::TokenManager` for
+/// writing refreshed tokens back to a store.
+///
+pub trait TokenStore<TR>: Send + Sync {
+    ///
+    /// Error type returned by this store.
+    ///
+    type Error: Error + Send + Sync + 'static;
+
+    ///
+    /// Loads the token with the given key, returning `None` if no such token is stored.
+    ///
+    fn load(&self, key: &TokenKey) -> Result<Option<StoredToken<TR>>, Self::Error>;
+
+    ///
+    /// Saves the token with the given key, replacing any previously stored token.
+    ///
+    fn save(&self, key: &TokenKey, token: &StoredToken<TR>) -> Result<(), Self::Error>;
+
+    ///
+    /// Deletes the token with the given key, if one is stored.
+    ///
+    fn delete(&self, key: &TokenKey) -> Result<(), Self::Error>;
+}
+
+///
+/// Token store that keeps tokens in memory for the lifetime of the process.
+///
+#[derive(Debug)]
+pub struct MemoryTokenStore<TR> {
+    tokens: Mutex<HashMap<TokenKey, StoredToken<TR>>>,
+}
+impl<TR> MemoryTokenStore<TR> {
+    ///
+    /// Instantiate a new, empty token store.
+    ///
+    pub fn new() -> Self {
+        Self {
+            tokens: Mutex::new(HashMap::new()),
+        }
+    }
+}
+impl<TR> Default for MemoryTokenStore<TR> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl<TR> TokenStore<TR> for MemoryTokenStore<TR>
+where
+    TR: Clone + Send,
+{
+    type Error = Infallible;
+
+    fn load(&self, key: &TokenKey) -> Result<Option<StoredToken<TR>>, Self::Error> {
+        Ok(self
+            .tokens
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .get(key)
+            .cloned())
+    }
+
+    fn save(&self, key: &TokenKey, token: &StoredToken<TR>) -> Result<(), Self::Error> {
+        self.tokens
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .insert(key.clone(), token.clone());
+        Ok(())
+    }
+
+    fn delete(&self, key: &TokenKey) -> Result<(), Self::Error> {
+        self.tokens
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .remove(key);
+        Ok(())
+    }
+}
+
+///
+/// Error encountered while accessing a `FileTokenStore`.
+///
+#[derive(Debug, thiserror::Error)]
+pub enum FileTokenStoreError {
+    ///
+    /// An I/O error occurred while reading or writing the file.
+    ///
+    #[error("I/O error: {}", _0)]
+    Io(#[source] io::Error),
+    ///
+    /// The file contents could not be parsed, or a token could not be serialized.
+    ///
+    #[error("Failed to parse token store: {}", _0)]
+    Parse(#[source] serde_json::Error),
+    ///
+    /// The file could not be decrypted (e.g., because the encryption key is incorrect or the file
+    /// was modified).
+    ///
+    #[error("Failed to decrypt token store")]
+    Decrypt,
+    ///
+    /// The file could not be encrypted.
+    ///
+    #[error("Failed to encrypt token store")]
+    Encrypt,
+}
+
+///
+/// 256-bit key used to encrypt a `FileTokenStore` at rest.
+///
+#[derive(Clone)]
+pub struct TokenStoreEncryptionKey([u8; 32]);
+impl TokenStoreEncryptionKey {
+    ///
+    /// Create a new `TokenStoreEncryptionKey` to wrap the given key.
+    ///
+    /// The key should be generated randomly and stored separately from the token store (e.g., in
+    /// the operating system's keychain).
+    ///
+    pub fn new(key: [u8; 32]) -> Self {
+        TokenStoreEncryptionKey(key)
+    }
+}
+impl Debug for TokenStoreEncryptionKey {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FormatterError> {
+        write!(f, "TokenStoreEncryptionKey([redacted])")
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+struct FileTokenEntry {
+    key: TokenKey,
+    token: serde_json::Value,
+}
+
+///
+/// Token store that persists tokens to a JSON file.
+///
+/// All tokens are stored in a single file, which is replaced atomically whenever a token is
+/// saved or deleted. On Unix platforms, the file is only readable and writable by its owner
+/// (mode `0600`).
+///
+/// If an encryption key is set using `set_encryption_key`, the file is encrypted using
+/// AES-256-GCM.
+///
+/// Saving or deleting a token reads the file, updates it, and writes it back. These updates are
+/// serialized between all threads sharing a single `FileTokenStore`, but not between separate
+/// instances or processes: if the same file is updated concurrently by more than one instance,
+/// some updates may be lost. Each file should therefore be owned by a single `FileTokenStore`
+/// (e.g., shared via an `Arc`) in a single process.
+///
+#[derive(Debug)]
+pub struct FileTokenStore {
+    path: PathBuf,
+    encryption_key: Option<TokenStoreEncryptionKey>,
+    lock: Mutex<()>,
+}
+impl FileTokenStore {
+    ///
+    /// Instantiate a new token store backed by the file at the given path.
+    ///
+    /// The file (but not its parent directory) is created the first time a token is saved.
+    ///
+    pub fn new<P>(path: P) -> Self
+    where
+        P: Into<PathBuf>,
+    {
+        Self {
+            path: path.into(),
+            encryption_key: None,
+            lock: Mutex::new(()),
+        }
+    }
+
+    ///
+    /// Encrypts the file at rest using the given key.
+    ///
+    pub fn set_encryption_key(mut self, encryption_key: TokenStoreEncryptionKey) -> Self {
+        self.encryption_key = Some(encryption_key);
+        self
+    }
+
+    ///
+    /// Path of the file backing this store.
+    ///
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn read_entries(&self) -> Result<Vec<FileTokenEntry>, FileTokenStoreError> {
+        let contents = match fs::read(&self.path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(FileTokenStoreError::Io(err)),
+        };
+
+        let contents = match self.encryption_key {
+            Some(ref encryption_key) => decrypt(encryption_key, contents)?,
+            None => contents,
+        };
+
+        serde_json::from_slice(&contents).map_err(FileTokenStoreError::Parse)
+    }
+
+    fn write_entries(&self, entries: &[FileTokenEntry]) -> Result<(), FileTokenStoreError> {
+        let contents = serde_json::to_vec_pretty(entries).map_err(FileTokenStoreError::Parse)?;
+        let contents = match self.encryption_key {
+            Some(ref encryption_key) => encrypt(encryption_key, contents)?,
+            None => contents,
+        };
+
+        // Write to a temporary file in the same directory and then rename it over the original so
+        // that readers never observe a partially written file.
+        let file_name = self
+            .path
+            .file_name()
+            .map(|file_name| file_name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        // The random suffix keeps concurrent writers (and stale files left behind by a crashed
+        // process with the same PID) from clobbering each other's temporary files.
+        let tmp_path = self.path.with_file_name(format!(
+            ".{}.{}.{:016x}.tmp",
+            file_name,
+            std::process::id(),
+            thread_rng().gen::<u64>()
+        ));
+
+        write_private_file(&tmp_path, &contents).map_err(FileTokenStoreError::Io)?;
+        fs::rename(&tmp_path, &self.path).map_err(|err| {
+            let _ = fs::remove_file(&tmp_path);
+            FileTokenStoreError::Io(err)
+        })
+    }
+}
+impl<TR> TokenStore<TR> for FileTokenStore
+where
+    TR: Serialize + DeserializeOwned,
+{
+    type Error = FileTokenStoreError;
+
+    fn load(&self, key: &TokenKey) -> Result<Option<StoredToken<TR>>, Self::Error> {
+        let _guard = self.lock.lock().unwrap_or_else(|err| err.into_inner());
+        self.read_entries()?
+            .into_iter()
+            .find(|entry| entry.key == *key)
+            .map(|entry| serde_json::from_value(entry.token).map_err(FileTokenStoreError::Parse))
+            .transpose()
+    }
+
+    fn save(&self, key: &TokenKey, token: &StoredToken<TR>) -> Result<(), Self::Error> {
+        let token = serde_json::to_value(token).map_err(FileTokenStoreError::Parse)?;
+
+        let _guard = self.lock.lock().unwrap_or_else(|err| err.into_inner());
+        let mut entries = self.read_entries()?;
+        match entries.iter_mut().find(|entry| entry.key == *key) {
+            Some(entry) => entry.token = token,
+            None => entries.push(FileTokenEntry {
+                key: key.clone(),
+                token,
+            }),
+        }
+        self.write_entries(&entries)
+    }
+
+    fn delete(&self, key: &TokenKey) -> Result<(), Self::Error> {
+        let _guard = self.lock.lock().unwrap_or_else(|err| err.into_inner());
+        let mut entries = self.read_entries()?;
+        let num_entries = entries.len();
+        entries.retain(|entry| entry.key != *key);
+        if entries.len() == num_entries {
+            return Ok(());
+        }
+        self.write_entries(&entries)
+    }
+}
+
+fn write_private_file(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    // Only remove the file if it was created here, since `create_new` fails if the file already
+    // exists.
+    let mut file = options.open(path)?;
+    let result = file.write_all(contents).and_then(|()| file.sync_all());
+    drop(file);
+    if result.is_err() {
+        let _ = fs::remove_file(path);
+    }
+    result
+}
+
+fn encryption_key(key: &TokenStoreEncryptionKey) -> LessSafeKey {
+    LessSafeKey::new(
+        UnboundKey::new(&AES_256_GCM, &key.0).expect("AES-256-GCM key length should be valid"),
+    )
+}
+
+// The encrypted file consists of a random nonce followed by the ciphertext and tag.
+fn encrypt(
+    key: &TokenStoreEncryptionKey,
+    mut contents: Vec<u8>,
+) -> Result<Vec<u8>, FileTokenStoreError> {
+    let mut nonce = [0u8; NONCE_LEN];
+    SystemRandom::new()
+        .fill(&mut nonce)
+        .map_err(|_| FileTokenStoreError::Encrypt)?;
+
+    encryption_key(key)
+        .seal_in_place_append_tag(
+            Nonce::assume_unique_for_key(nonce),
+            Aad::empty(),
+            &mut contents,
+        )
+        .map_err(|_| FileTokenStoreError::Encrypt)?;
+
+    let mut encrypted = nonce.to_vec();
+    encrypted.append(&mut contents);
+    Ok(encrypted)
+}
+
+fn decrypt(
+    key: &TokenStoreEncryptionKey,
+    mut contents: Vec<u8>,
+) -> Result<Vec<u8>, FileTokenStoreError> {
+    if contents.len() < NONCE_LEN {
+        return Err(FileTokenStoreError::Decrypt);
+    }
+    let mut ciphertext = contents.split_off(NONCE_LEN);
+    let mut nonce = [0u8; NONCE_LEN];
+    nonce.copy_from_slice(&contents);
+
+    let plaintext_len = encryption_key(key)
+        .open_in_place(
+            Nonce::assume_unique_for_key(nonce),
+            Aad::empty(),
+            &mut ciphertext,
+        )
+        .map_err(|_| FileTokenStoreError::Decrypt)?
+        .len();
+    ciphertext.truncate(plaintext_len);
+    Ok(ciphertext)
+}
@@ -0,0 +1,566 @@
+use std::error::Error as StdError;
+use std::fmt::Error as FormatterError;
+use std::fmt::{Debug, Formatter};
+use std::future::Future;
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use http::header::{HeaderValue, AUTHORIZATION};
+use http::status::StatusCode;
+use thiserror::Error;
+use tower_layer::Layer;
+use tower_service::Service;
+
+use super::tokenmanager::TokenManager;
+use super::{
+    AccessToken, AsyncHttpClient, Client, ErrorResponse, HttpRequest, HttpResponse,
+    RequestTokenError, Scope, TokenInspectionResponse, TokenResponse, TokenType,
+};
+
+///
+/// Type-erased error returned by tower services and by `BearerTokenService`.
+///
+pub type BoxError = Box<dyn StdError + Send + Sync>;
+
+///
+/// Future returned by `TowerHttpClient`.
+///
+pub type TowerHttpClientFuture = Pin<Box<dyn Future<Output = Result<HttpResponse, Error>> + Send>>;
+
+///
+/// Error type returned by failed `TowerHttpClient` requests.
+///
+#[derive(Debug, Error)]
+pub enum Error {
+    /// Error returned by the tower service.
+    #[error("service request failed")]
+    Service(#[source] BoxError),
+    /// Failed to construct the HTTP request.
+    #[error("HTTP error")]
+    Http(#[source] http::Error),
+}
+
+///
+/// Asynchronous HTTP client backed by a tower `Service`.
+///
+/// This adapter allows any `Service<http::Request<Vec<u8>>, Response = http::Response<Vec<u8>>>`
+/// to be passed to the `request_async` methods of this crate, so that the layers of the service
+/// (e.g., retries, timeouts and tracing) also apply to requests sent to the authorization server.
+///
+/// When the client is called through a reference, the service is cloned for each request, as is
+/// customary for tower services.
+///
+#[derive(Clone, Debug)]
+pub struct TowerHttpClient<S> {
+    service: S,
+}
+impl<S> TowerHttpClient<S> {
+    ///
+    /// Creates a new HTTP client that sends requests using the given service.
+    ///
+    pub fn new(service: S) -> Self {
+        Self { service }
+    }
+
+    ///
+    /// Returns a reference to the underlying service.
+    ///
+    pub fn service(&self) -> &S {
+        &self.service
+    }
+}
+impl<S> AsyncHttpClient for TowerHttpClient<S>
+where
+    S: Service<http::Request<Vec<u8>>, Response = http::Response<Vec<u8>>> + Clone + Send + 'static,
+    S::Error: Into<BoxError>,
+    S::Future: Send,
+{
+    type Error = Error;
+    type Future = TowerHttpClientFuture;
+
+    fn call(self, request: HttpRequest) -> TowerHttpClientFuture {
+        let mut service = self.service;
+        Box::pin(async move {
+            let mut request_builder = http::Request::builder()
+                .method(request.method)
+                .uri(request.url.as_str());
+            for (name, value) in &request.headers {
+                request_builder = request_builder.header(name, value);
+            }
+            let request = request_builder.body(request.body).map_err(Error::Http)?;
+
+            ServiceReady::new(&mut service)
+                .await
+                .map_err(|err| Error::Service(err.into()))?;
+            let response = service
+                .call(request)
+                .await
+                .map_err(|err| Error::Service(err.into()))?;
+
+            let (parts, body) = response.into_parts();
+            Ok(HttpResponse {
+                status_code: parts.status,
+                headers: parts.headers,
+                body,
+            })
+        })
+    }
+}
+impl<S> AsyncHttpClient for &TowerHttpClient<S>
+where
+    S: Service<http::Request<Vec<u8>>, Response = http::Response<Vec<u8>>> + Clone + Send + 'static,
+    S::Error: Into<BoxError>,
+    S::Future: Send,
+{
+    type Error = Error;
+    type Future = TowerHttpClientFuture;
+
+    fn call(self, request: HttpRequest) -> TowerHttpClientFuture {
+        self.clone().call(request)
+    }
+}
+
+///
+/// Source of the access tokens attached to outgoing requests by `BearerTokenService`.
+///
+pub trait AccessTokenSource {
+    ///
+    /// Error type returned when an access token can't be obtained.
+    ///
+    type Error: Into<BoxError>;
+
+    ///
+    /// Future returned by `access_token`.
+    ///
+    type Future: Future<Output = Result<AccessToken, Self::Error>> + Send;
+
+    ///
+    /// Returns a valid access token, obtaining a new one from the authorization server if
+    /// necessary.
+    ///
+    fn access_token(&self) -> Self::Future;
+
+    ///
+    /// Marks the given access token (which was rejected by a resource server) as invalid, causing
+    /// the next call to `access_token` to obtain a new one.
+    ///
+    fn invalidate(&self, access_token: &AccessToken);
+}
+
+///
+/// Future returned by `RefreshTokenSource` and `ClientCredentialsTokenSource`.
+///
+pub type AccessTokenFuture<RE, TE> =
+    Pin<Box<dyn Future<Output = Result<AccessToken, RequestTokenError<RE, TE>>> + Send>>;
+
+///
+/// Access token source that refreshes the access token managed by a `TokenManager` using its
+/// refresh token.
+///
+pub struct RefreshTokenSource<TE, TR, TT, TIR, C>
+where
+    TE: ErrorResponse,
+    TR: TokenResponse<TT>,
+    TT: TokenType,
+    TIR: TokenInspectionResponse<TT>,
+{
+    manager: Arc<TokenManager<TE, TR, TT, TIR>>,
+    http_client: C,
+}
+impl<TE, TR, TT, TIR, C> RefreshTokenSource<TE, TR, TT, TIR, C>
+where
+    TE: ErrorResponse,
+    TR: TokenResponse<TT>,
+    TT: TokenType,
+    TIR: TokenInspectionResponse<TT>,
+{
+    ///
+    /// Creates a new access token source that refreshes tokens using the given token manager and
+    /// HTTP client.
+    ///
+    pub fn new(manager: Arc<TokenManager<TE, TR, TT, TIR>>, http_client: C) -> Self {
+        Self {
+            manager,
+            http_client,
+        }
+    }
+
+    ///
+    /// Returns the underlying token manager.
+    ///
+    pub fn manager(&self) -> &Arc<TokenManager<TE, TR, TT, TIR>> {
+        &self.manager
+    }
+}
+impl<TE, TR, TT, TIR, C> AccessTokenSource for RefreshTokenSource<TE, TR, TT, TIR, C>
+where
+    TE: ErrorResponse + Send + Sync + 'static,
+    TR: TokenResponse<TT> + Clone + Send + Sync + 'static,
+    TT: TokenType + Send + Sync + 'static,
+    TIR: TokenInspectionResponse<TT> + Send + Sync + 'static,
+    C: AsyncHttpClient + Clone + Send + Sync + 'static,
+    for<'c> &'c C: AsyncHttpClient<Error = C::Error>,
+    C::Error: Send + Sync,
+{
+    type Error = RequestTokenError<C::Error, TE>;
+    type Future = AccessTokenFuture<C::Error, TE>;
+
+    fn access_token(&self) -> Self::Future {
+        let manager = self.manager.clone();
+        let http_client = self.http_client.clone();
+        Box::pin(async move { manager.access_token_async(http_client).await })
+    }
+
+    fn invalidate(&self, access_token: &AccessToken) {
+        // Another request may have already refreshed the rejected token.
+        if self.manager.token_response().access_token().secret() == access_token.secret() {
+            self.manager.expire();
+        }
+    }
+}
+impl<TE, TR, TT, TIR, C> Debug for RefreshTokenSource<TE, TR, TT, TIR, C>
+where
+    TE: ErrorResponse,
+    TR: TokenResponse<TT>,
+    TT: TokenType,
+    TIR: TokenInspectionResponse<TT>,
+{
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FormatterError> {
+        f.debug_struct("RefreshTokenSource")
+            .field("manager", &self.manager)
+            .finish()
+    }
+}
+
+///
+/// Default amount of time before the access token expires at which `ClientCredentialsTokenSource`
+/// requests a new one.
+///
+pub const DEFAULT_REFRESH_SKEW: Duration = Duration::from_secs(60);
+
+type CachedToken = Option<(AccessToken, Option<DateTime<Utc>>)>;
+
+///
+/// Access token source that obtains access tokens using the Client Credentials Grant
+/// ([Section 4.4 of RFC 6749](https://tools.ietf.org/html/rfc6749#section-4.4)).
+///
+/// Access tokens are cached until shortly before they expire (see `set_refresh_skew`) or until
+/// they are rejected by a resource server. Concurrent callers may each request a new access
+/// token when none is cached.
+///
+pub struct ClientCredentialsTokenSource<TE, TR, TT, TIR, C>
+where
+    TE: ErrorResponse,
+    TR: TokenResponse<TT>,
+    TT: TokenType,
+    TIR: TokenInspectionResponse<TT>,
+{
+    client: Arc<Client<TE, TR, TT, TIR>>,
+    scopes: Vec<Scope>,
+    http_client: C,
+    refresh_skew: Duration,
+    token: Arc<Mutex<CachedToken>>,
+}
+impl<TE, TR, TT, TIR, C> ClientCredentialsTokenSource<TE, TR, TT, TIR, C>
+where
+    TE: ErrorResponse,
+    TR: TokenResponse<TT>,
+    TT: TokenType,
+    TIR: TokenInspectionResponse<TT>,
+{
+    ///
+    /// Creates a new access token source that requests tokens using the given client and HTTP
+    /// client.
+    ///
+    pub fn new(client: Client<TE, TR, TT, TIR>, http_client: C) -> Self {
+        Self {
+            client: Arc::new(client),
+            scopes: Vec::new(),
+            http_client,
+            refresh_skew: DEFAULT_REFRESH_SKEW,
+            token: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    ///
+    /// Appends a new scope to the token requests.
+    ///
+    pub fn add_scope(mut self, scope: Scope) -> Self {
+        self.scopes.push(scope);
+        self
+    }
+
+    ///
+    /// Sets how long before the access token expires a new one should be requested.
+    ///
+    /// Defaults to `DEFAULT_REFRESH_SKEW` (60 seconds).
+    ///
+    pub fn set_refresh_skew(mut self, refresh_skew: Duration) -> Self {
+        self.refresh_skew = refresh_skew;
+        self
+    }
+}
+impl<TE, TR, TT, TIR, C> AccessTokenSource for ClientCredentialsTokenSource<TE, TR, TT, TIR, C>
+where
+    TE: ErrorResponse + Send + Sync + 'static,
+    TR: TokenResponse<TT> + Send + Sync + 'static,
+    TT: TokenType + Send + Sync + 'static,
+    TIR: TokenInspectionResponse<TT> + Send + Sync + 'static,
+    C: AsyncHttpClient + Clone + Send + Sync + 'static,
+    for<'c> &'c C: AsyncHttpClient<Error = C::Error>,
+    C::Error: Send + Sync,
+{
+    type Error = RequestTokenError<C::Error, TE>;
+    type Future = AccessTokenFuture<C::Error, TE>;
+
+    fn access_token(&self) -> Self::Future {
+        let client = self.client.clone();
+        let scopes = self.scopes.clone();
+        let http_client = self.http_client.clone();
+        let refresh_skew = self.refresh_skew;
+        let token = self.token.clone();
+        Box::pin(async move {
+            if let Some(access_token) = cached_token(&lock(&token), refresh_skew) {
+                return Ok(access_token);
+            }
+
+            let mut token_request = client.exchange_client_credentials();
+            for scope in scopes {
+                token_request = token_request.add_scope(scope);
+            }
+            let token_response = token_request.request_async(http_client).await?;
+            let access_token = token_response.access_token().clone();
+            let expires_at = token_response.expires_in().and_then(|expires_in| {
+                chrono::Duration::from_std(expires_in)
+                    .ok()
+                    .and_then(|expires_in| Utc::now().checked_add_signed(expires_in))
+            });
+            *lock(&token) = Some((access_token.clone(), expires_at));
+            Ok(access_token)
+        })
+    }
+
+    fn invalidate(&self, access_token: &AccessToken) {
+        let mut token = lock(&self.token);
+        let rejected = match *token {
+            Some((ref cached_token, _)) => cached_token.secret() == access_token.secret(),
+            None => false,
+        };
+        if rejected {
+            *token = None;
+        }
+    }
+}
+impl<TE, TR, TT, TIR, C> Debug for ClientCredentialsTokenSource<TE, TR, TT, TIR, C>
+where
+    TE: ErrorResponse,
+    TR: TokenResponse<TT>,
+    TT: TokenType,
+    TIR: TokenInspectionResponse<TT>,
+{
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FormatterError> {
+        f.debug_struct("ClientCredentialsTokenSource")
+            .field("client", &self.client)
+            .field("scopes", &self.scopes)
+            .field("refresh_skew", &self.refresh_skew)
+            .finish()
+    }
+}
+
+///
+/// Tower `Layer` that attaches a bearer token ([RFC 6750](https://tools.ietf.org/html/rfc6750))
+/// obtained from an `AccessTokenSource` to outgoing requests.
+///
+/// If the inner service responds with `401 Unauthorized`, the access token is invalidated and
+/// the request is retried once with a new access token. Since the request must be retained for
+/// the retry, its body must implement `Clone`.
+///
+pub struct BearerTokenLayer<TS> {
+    token_source: Arc<TS>,
+}
+impl<TS> BearerTokenLayer<TS> {
+    ///
+    /// Creates a new layer that obtains access tokens from the given source.
+    ///
+    pub fn new(token_source: TS) -> Self {
+        Self {
+            token_source: Arc::new(token_source),
+        }
+    }
+}
+impl<TS> Clone for BearerTokenLayer<TS> {
+    fn clone(&self) -> Self {
+        Self {
+            token_source: self.token_source.clone(),
+        }
+    }
+}
+impl<TS> Debug for BearerTokenLayer<TS>
+where
+    TS: Debug,
+{
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FormatterError> {
+        f.debug_struct("BearerTokenLayer")
+            .field("token_source", &self.token_source)
+            .finish()
+    }
+}
+impl<S, TS> Layer<S> for BearerTokenLayer<TS> {
+    type Service = BearerTokenService<S, TS>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        BearerTokenService {
+            inner,
+            token_source: self.token_source.clone(),
+        }
+    }
+}
+
+///
+/// Tower `Service` created by `BearerTokenLayer`.
+///
+pub struct BearerTokenService<S, TS> {
+    inner: S,
+    token_source: Arc<TS>,
+}
+impl<S, TS> Clone for BearerTokenService<S, TS>
+where
+    S: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            token_source: self.token_source.clone(),
+        }
+    }
+}
+impl<S, TS> Debug for BearerTokenService<S, TS>
+where
+    S: Debug,
+    TS: Debug,
+{
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FormatterError> {
+        f.debug_struct("BearerTokenService")
+            .field("inner", &self.inner)
+            .field("token_source", &self.token_source)
+            .finish()
+    }
+}
+impl<S, TS, B, RB> Service<http::Request<B>> for BearerTokenService<S, TS>
+where
+    S: Service<http::Request<B>, Response = http::Response<RB>> + Clone + Send + 'static,
+    S::Error: Into<BoxError>,
+    S::Future: Send,
+    TS: AccessTokenSource + Send + Sync + 'static,
+    B: Clone + Send + 'static,
+    RB: Send + 'static,
+{
+    type Response = http::Response<RB>;
+    type Error = BoxError;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, BoxError>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), BoxError>> {
+        self.inner.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, request: http::Request<B>) -> Self::Future {
+        // The service that was polled for readiness must be the one that handles the request.
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let token_source = self.token_source.clone();
+        Box::pin(async move {
+            let retry_request = clone_request(&request);
+
+            let access_token = token_source.access_token().await.map_err(Into::into)?;
+            let response = inner
+                .call(set_bearer_token(request, &access_token)?)
+                .await
+                .map_err(Into::into)?;
+            if response.status() != StatusCode::UNAUTHORIZED {
+                return Ok(response);
+            }
+
+            token_source.invalidate(&access_token);
+            let access_token = token_source.access_token().await.map_err(Into::into)?;
+            ServiceReady::new(&mut inner).await.map_err(Into::into)?;
+            inner
+                .call(set_bearer_token(retry_request, &access_token)?)
+                .await
+                .map_err(Into::into)
+        })
+    }
+}
+
+fn lock(token: &Mutex<CachedToken>) -> MutexGuard<'_, CachedToken> {
+    token.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+fn cached_token(token: &CachedToken, refresh_skew: Duration) -> Option<AccessToken> {
+    let (access_token, expires_at) = token.as_ref()?;
+    let expires_at = match expires_at {
+        Some(expires_at) => expires_at,
+        None => return Some(access_token.clone()),
+    };
+    match chrono::Duration::from_std(refresh_skew)
+        .ok()
+        .and_then(|skew| Utc::now().checked_add_signed(skew))
+    {
+        Some(refresh_at) if refresh_at < *expires_at => Some(access_token.clone()),
+        _ => None,
+    }
+}
+
+fn clone_request<B>(request: &http::Request<B>) -> http::Request<B>
+where
+    B: Clone,
+{
+    let mut cloned = http::Request::new(request.body().clone());
+    *cloned.method_mut() = request.method().clone();
+    *cloned.uri_mut() = request.uri().clone();
+    *cloned.version_mut() = request.version();
+    *cloned.headers_mut() = request.headers().clone();
+    cloned
+}
+
+fn set_bearer_token<B>(
+    mut request: http::Request<B>,
+    access_token: &AccessToken,
+) -> Result<http::Request<B>, BoxError> {
+    let mut value = HeaderValue::from_str(&format!("Bearer {}", access_token.secret()))?;
+    value.set_sensitive(true);
+    request.headers_mut().insert(AUTHORIZATION, value);
+    Ok(request)
+}
+
+///
+/// Future that resolves once the given service is ready to accept a request.
+///
+struct ServiceReady<'a, S, R> {
+    service: &'a mut S,
+    _phantom: PhantomData<fn(R)>,
+}
+impl<'a, S, R> ServiceReady<'a, S, R>
+where
+    S: Service<R>,
+{
+    fn new(service: &'a mut S) -> Self {
+        Self {
+            service,
+            _phantom: PhantomData,
+        }
+    }
+}
+impl<'a, S, R> Future for ServiceReady<'a, S, R>
+where
+    S: Service<R>,
+{
+    type Output = Result<(), S::Error>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.service.poll_ready(cx)
+    }
+}
@@ -249,6 +249,11 @@ macro_rules! new_url_type {
             }
             $($item)*
         }
+        new_url_type![@impls $name];
+    };
+    // Trait implementations shared by all URL types, which must wrap a parsed `Url` and its
+    // original `String` and provide a `new(String)` constructor.
+    (@impls $name:ident) => {
         impl Deref for $name {
             type Target = String;
             fn deref(&self) -> &String {
@@ -359,12 +364,130 @@ new_url_type![
     ///
     DeviceAuthorizationUrl
 ];
+new_url_type![
+    ///
+    /// URL of the authorization server's backchannel authentication endpoint, as defined in
+    /// [Section 7 of CIBA](https://openid.net/specs/openid-client-initiated-backchannel-authentication-core-1_0.html#rfc.section.7).
+    ///
+    BackchannelAuthenticationUrl
+];
+new_url_type![
+    ///
+    /// URL of the authorization server's pushed authorization request (PAR) endpoint, as defined
+    /// in [RFC 9126](https://tools.ietf.org/html/rfc9126).
+    ///
+    ParUrl
+];
+new_url_type![
+    ///
+    /// URL of the authorization server's token revocation endpoint.
+    ///
+    RevocationUrl
+];
+new_url_type![
+    ///
+    /// URL of the authorization server's issuer identifier, as defined in
+    /// [Section 2 of RFC 8414](https://tools.ietf.org/html/rfc8414#section-2).
+    ///
+    IssuerUrl
+];
+new_url_type![
+    ///
+    /// URL of the authorization server's JSON Web Key Set (JWKS) document.
+    ///
+    JsonWebKeySetUrl
+];
+new_url_type![
+    ///
+    /// URL of the authorization server's dynamic client registration endpoint.
+    ///
+    RegistrationUrl
+];
+new_url_type![
+    ///
+    /// URL of the client configuration endpoint used to read, update and delete a dynamically
+    /// registered client (the `registration_client_uri` of
+    /// [Section 3 of RFC 7592](https://tools.ietf.org/html/rfc7592#section-3)).
+    ///
+    ClientConfigurationUrl
+];
 new_url_type![
     ///
     /// URL of the end-user verification URI on the authorization server.
     ///
     EndUserVerificationUrl
 ];
+
+///
+/// URI of a target service or resource where the client intends to use the requested token,
+/// as defined in [Section 2 of RFC 8707](https://tools.ietf.org/html/rfc8707#section-2) and
+/// [Section 2.1 of RFC 8693](https://tools.ietf.org/html/rfc8693#section-2.1).
+///
+/// Resource URIs must be absolute and must not include a fragment component.
+///
+#[derive(Clone)]
+pub struct ResourceUrl(Url, String);
+impl ResourceUrl {
+    ///
+    /// Create a new `ResourceUrl` from a `String` to wrap a URL, validating that it is an absolute
+    /// URI without a fragment component.
+    ///
+    pub fn new(url: String) -> Result<Self, ResourceUrlError> {
+        let parsed = Url::parse(&url)?;
+        Self::validate(&parsed)?;
+        Ok(ResourceUrl(parsed, url))
+    }
+    ///
+    /// Create a new `ResourceUrl` from a `Url` to wrap a URL, validating that it does not
+    /// include a fragment component.
+    ///
+    pub fn from_url(url: Url) -> Result<Self, ResourceUrlError> {
+        Self::validate(&url)?;
+        let s = url.to_string();
+        Ok(ResourceUrl(url, s))
+    }
+    ///
+    /// Return this `ResourceUrl` as a parsed `Url`.
+    ///
+    pub fn url(&self) -> &Url {
+        &self.0
+    }
+    fn validate(url: &Url) -> Result<(), ResourceUrlError> {
+        if url.fragment().is_some() {
+            Err(ResourceUrlError::Fragment)
+        } else {
+            Ok(())
+        }
+    }
+}
+new_url_type![@impls ResourceUrl];
+
+///
+/// Error encountered while validating a `ResourceUrl`.
+///
+#[derive(Clone, Debug, PartialEq, thiserror::Error)]
+pub enum ResourceUrlError {
+    ///
+    /// The URI is not a valid absolute URI.
+    ///
+    #[error("Failed to parse resource URI")]
+    Parse(#[from] url::ParseError),
+    ///
+    /// The URI includes a fragment component, which is not allowed by
+    /// [Section 2 of RFC 8707](https://tools.ietf.org/html/rfc8707#section-2).
+    ///
+    #[error("Resource URI must not include a fragment component")]
+    Fragment,
+}
+new_type![
+    ///
+    /// Logical name of a target service where the client intends to use the requested token
+    /// (the `audience` parameter of
+    /// [Section 2.1 of RFC 8693](https://tools.ietf.org/html/rfc8693#section-2.1)).
+    ///
+    #[derive(Deserialize, Serialize, Eq, Hash)]
+    Audience(String)
+];
 new_type![
     ///
     /// Authorization endpoint response (grant) type defined in
@@ -373,6 +496,33 @@ new_type![
     #[derive(Deserialize, Serialize, Eq, Hash)]
     ResponseType(String)
 ];
+new_type![
+    ///
+    /// Grant type used at the token endpoint (e.g., `authorization_code` or `refresh_token`),
+    /// as registered in the "OAuth Parameters" registry of
+    /// [RFC 6749](https://tools.ietf.org/html/rfc6749#section-11.2).
+    ///
+    #[derive(Deserialize, Serialize, Eq, Hash)]
+    GrantType(String)
+];
+new_type![
+    ///
+    /// Client authentication method used at the token endpoint (e.g., `client_secret_basic` or
+    /// `private_key_jwt`), as registered in the "OAuth Token Endpoint Authentication Methods"
+    /// registry of [RFC 7591](https://tools.ietf.org/html/rfc7591#section-4.2).
+    ///
+    #[derive(Deserialize, Serialize, Eq, Hash)]
+    ClientAuthMethod(String)
+];
+new_type![
+    ///
+    /// Reference to an authorization request previously pushed to the authorization server
+    /// (the `request_uri` parameter of
+    /// [Section 2.2 of RFC 9126](https://tools.ietf.org/html/rfc9126#section-2.2)).
+    ///
+    #[derive(Deserialize, Serialize, Eq, Hash)]
+    RequestUri(String)
+];
 new_type![
     ///
     /// Resource owner's username used directly as an authorization grant to obtain an access
@@ -616,6 +766,70 @@ new_secret_type![
     #[derive(Clone, Deserialize, Serialize)]
     AccessToken(String)
 ];
+new_secret_type![
+    ///
+    /// Signed JWT used as an authorization grant (the `assertion` parameter of
+    /// [Section 2.1 of RFC 7523](https://tools.ietf.org/html/rfc7523#section-2.1)).
+    ///
+    #[derive(Clone, Deserialize, Serialize)]
+    JwtBearerAssertion(String)
+];
+new_secret_type![
+    ///
+    /// Security token representing the identity of the party on behalf of whom a token exchange
+    /// request is being made (the `subject_token` parameter of
+    /// [Section 2.1 of RFC 8693](https://tools.ietf.org/html/rfc8693#section-2.1)).
+    ///
+    #[derive(Clone, Deserialize, Serialize)]
+    SubjectToken(String)
+];
+new_secret_type![
+    ///
+    /// Security token representing the identity of the acting party in a token exchange request
+    /// (the `actor_token` parameter of
+    /// [Section 2.1 of RFC 8693](https://tools.ietf.org/html/rfc8693#section-2.1)).
+    ///
+    #[derive(Clone, Deserialize, Serialize)]
+    ActorToken(String)
+];
+new_secret_type![
+    ///
+    /// Access token used to access the client configuration endpoint of a dynamically
+    /// registered client, as defined in
+    /// [Section 3 of RFC 7592](https://tools.ietf.org/html/rfc7592#section-3).
+    ///
+    #[derive(Clone, Deserialize, Serialize)]
+    RegistrationAccessToken(String)
+];
+new_secret_type![
+    ///
+    /// Unique identifier of a backchannel authentication request (the `auth_req_id` of
+    /// [Section 7.3 of CIBA](https://openid.net/specs/openid-client-initiated-backchannel-authentication-core-1_0.html#rfc.section.7.3)).
+    ///
+    #[derive(Clone, Deserialize, Serialize)]
+    AuthReqId(String)
+];
+new_secret_type![
+    ///
+    /// Bearer token used by the authorization server to authenticate its ping mode callbacks to
+    /// the client notification endpoint (the `client_notification_token` of
+    /// [Section 7.1 of CIBA](https://openid.net/specs/openid-client-initiated-backchannel-authentication-core-1_0.html#rfc.section.7.1)).
+    ///
+    #[derive(Clone, Deserialize, Serialize)]
+    ClientNotificationToken(String)
+    impl {
+        ///
+        /// Generate a new random, base64-encoded 256-bit client notification token.
+        ///
+        pub fn new_random() -> Self {
+            let random_bytes: Vec<u8> = (0..32).map(|_| thread_rng().gen::<u8>()).collect();
+            ClientNotificationToken::new(base64::encode_config(
+                &random_bytes,
+                base64::URL_SAFE_NO_PAD,
+            ))
+        }
+    }
+];
 new_secret_type![
     ///
     /// Resource owner's password used directly as an authorization grant to obtain an access
@@ -0,0 +1,206 @@
+use std::io::Read;
+use std::time::Duration;
+
+use http::header::{HeaderMap, HeaderName, HeaderValue};
+use http::status::StatusCode;
+
+use super::{HttpRequest, HttpResponse, SyncHttpClient};
+
+///
+/// Error type returned by failed ureq HTTP requests.
+///
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// Error returned by ureq crate.
+    #[error("ureq request failed")]
+    Ureq(#[source] Box<ureq::Transport>),
+    /// Non-ureq HTTP error.
+    #[error("HTTP error")]
+    Http(#[source] http::Error),
+    /// I/O error.
+    #[error("I/O error")]
+    Io(#[source] std::io::Error),
+    /// Other error.
+    #[error("Other error: {}", _0)]
+    Other(String),
+}
+
+///
+/// Synchronous HTTP client.
+///
+/// A new ureq agent is constructed for each request. Applications sending many requests should
+/// use a `UreqClient` instead.
+///
+pub fn http_client(request: HttpRequest) -> Result<HttpResponse, Error> {
+    UreqClientBuilder::new().build().execute(request)
+}
+
+///
+/// Builder for reusable ureq HTTP clients.
+///
+/// Redirects are never followed, since following them opens the client up to SSRF
+/// vulnerabilities.
+///
+#[derive(Clone, Debug, Default)]
+pub struct UreqClientBuilder {
+    timeout: Option<Duration>,
+    connect_timeout: Option<Duration>,
+    read_timeout: Option<Duration>,
+    write_timeout: Option<Duration>,
+}
+impl UreqClientBuilder {
+    ///
+    /// Creates a new builder with ureq's default configuration (aside from redirects, which are
+    /// disabled).
+    ///
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    ///
+    /// Sets the total timeout of each request, from resolving the host until the response body
+    /// has been read. Takes precedence over the read and write timeouts.
+    ///
+    pub fn set_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    ///
+    /// Sets the timeout for establishing connections.
+    ///
+    pub fn set_connect_timeout(mut self, connect_timeout: Duration) -> Self {
+        self.connect_timeout = Some(connect_timeout);
+        self
+    }
+
+    ///
+    /// Sets the timeout for each individual read from the socket.
+    ///
+    pub fn set_read_timeout(mut self, read_timeout: Duration) -> Self {
+        self.read_timeout = Some(read_timeout);
+        self
+    }
+
+    ///
+    /// Sets the timeout for each individual write to the socket.
+    ///
+    pub fn set_write_timeout(mut self, write_timeout: Duration) -> Self {
+        self.write_timeout = Some(write_timeout);
+        self
+    }
+
+    ///
+    /// Builds a synchronous HTTP client.
+    ///
+    pub fn build(self) -> UreqClient {
+        let mut builder = ureq::AgentBuilder::new().redirects(0);
+        if let Some(timeout) = self.timeout {
+            builder = builder.timeout(timeout);
+        }
+        if let Some(connect_timeout) = self.connect_timeout {
+            builder = builder.timeout_connect(connect_timeout);
+        }
+        if let Some(read_timeout) = self.read_timeout {
+            builder = builder.timeout_read(read_timeout);
+        }
+        if let Some(write_timeout) = self.write_timeout {
+            builder = builder.timeout_write(write_timeout);
+        }
+        UreqClient {
+            agent: builder.build(),
+        }
+    }
+}
+
+///
+/// Reusable synchronous HTTP client, created using `UreqClientBuilder::build`.
+///
+/// This client (and a reference to it) implements `SyncHttpClient`, so it may be passed directly
+/// to any of the `request` methods of this crate. The underlying ureq agent maintains a pool of
+/// connections, which is shared between clones of this client.
+///
+#[derive(Clone, Debug)]
+pub struct UreqClient {
+    agent: ureq::Agent,
+}
+impl UreqClient {
+    ///
+    /// Sends the given request.
+    ///
+    pub fn execute(&self, request: HttpRequest) -> Result<HttpResponse, Error> {
+        let mut ureq_request = self
+            .agent
+            .request(request.method.as_str(), request.url.as_str());
+        for (name, value) in &request.headers {
+            ureq_request = ureq_request.set(
+                name.as_str(),
+                value.to_str().map_err(|_| {
+                    Error::Other(format!(
+                        "invalid {} header value {:?}",
+                        name,
+                        value.as_bytes()
+                    ))
+                })?,
+            );
+        }
+
+        let result = if request.body.is_empty() {
+            ureq_request.call()
+        } else {
+            ureq_request.send_bytes(&request.body)
+        };
+        let response = match result {
+            Ok(response) => response,
+            // ureq treats 4xx and 5xx responses as errors, but OAuth2 error responses need to be
+            // parsed by the caller.
+            Err(ureq::Error::Status(_, response)) => response,
+            Err(ureq::Error::Transport(err)) => return Err(Error::Ureq(Box::new(err))),
+        };
+
+        let status_code =
+            StatusCode::from_u16(response.status()).map_err(|err| Error::Http(err.into()))?;
+        let mut headers = HeaderMap::new();
+        for name in response.headers_names() {
+            let header_name =
+                HeaderName::from_bytes(name.as_bytes()).map_err(|err| Error::Http(err.into()))?;
+            // Repeated headers are listed once per occurrence, but `all` already returns every
+            // value.
+            if headers.contains_key(&header_name) {
+                continue;
+            }
+            for value in response.all(&name) {
+                headers.append(
+                    header_name.clone(),
+                    HeaderValue::from_str(value).map_err(|err| Error::Http(err.into()))?,
+                );
+            }
+        }
+
+        let mut body = Vec::new();
+        response
+            .into_reader()
+            .read_to_end(&mut body)
+            .map_err(Error::Io)?;
+
+        Ok(HttpResponse {
+            status_code,
+            headers,
+            body,
+        })
+    }
+}
+impl SyncHttpClient for UreqClient {
+    type Error = Error;
+
+    fn call(self, request: HttpRequest) -> Result<HttpResponse, Error> {
+        self.execute(request)
+    }
+}
+impl SyncHttpClient for &UreqClient {
+    type Error = Error;
+
+    fn call(self, request: HttpRequest) -> Result<HttpResponse, Error> {
+        self.execute(request)
+    }
+}